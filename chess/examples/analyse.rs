@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use chess::{
+    Chessboard, START_POS,
+    uci::{Engine, Go, Score, format_move},
+};
+
+/// Analyse the position with an external UCI engine, printing each depth
+///
+/// Usage: `analyse <engine> [depth] [fen]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        println!("Usage: analyse <engine> [depth] [fen]");
+        return;
+    };
+    let depth = args.next().and_then(|d| d.parse().ok()).unwrap_or(12);
+    let fen = args.collect::<Vec<_>>().join(" ");
+    let cb = match Chessboard::from_fen(if fen.is_empty() { START_POS } else { &fen }) {
+        Ok(cb) => cb,
+        Err(e) => {
+            println!("Invalid FEN: {e:?}");
+            return;
+        }
+    };
+
+    let mut engine = match Engine::spawn(&path) {
+        Ok(engine) => engine,
+        Err(e) => {
+            println!("Failed to start {path}: {e}");
+            return;
+        }
+    };
+    println!("{}", engine.name().unwrap_or(&path));
+
+    let go = Go {
+        depth: Some(depth),
+        ..Default::default()
+    };
+    let result = engine.set_position(&cb, &[]).and_then(|()| {
+        engine.search(&go, Duration::from_secs(600), |info| {
+            let (Some(depth), Some(score)) = (info.depth, info.score) else {
+                return;
+            };
+            let score = match score {
+                Score::Cp(cp) => format!("{:+.2}", f64::from(cp) / 100.0),
+                Score::Mate(moves) => format!("#{moves}"),
+            };
+            let mut position = cb;
+            let pv: Vec<_> = info
+                .pv
+                .iter()
+                .filter_map(|&m| {
+                    let san = position.to_san(m);
+                    position.make_move(m);
+                    san
+                })
+                .collect();
+            println!("{depth:>3} {score:>7} {}", pv.join(" "));
+        })
+    });
+    match result {
+        Ok(best) => println!(
+            "bestmove {}",
+            best.m
+                .and_then(format_move)
+                .unwrap_or_else(|| String::from("(none)"))
+        ),
+        Err(e) => println!("Engine failed: {e}"),
+    }
+}
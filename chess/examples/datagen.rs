@@ -0,0 +1,43 @@
+use std::time::Instant;
+
+use chess::{
+    datagen::{DatagenConfig, Format, generate},
+    eval::HandCrafted,
+    search::Limit,
+};
+
+/// Play self-play games with the hand-crafted evaluation and write the quiet
+/// positions to `shard-N` files in the directory, as text or packed records
+///
+/// Usage: `datagen <dir> [games] [depth] [threads] [txt|bin]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(dir) = args.next() else {
+        println!("Usage: datagen <dir> [games] [depth] [threads] [txt|bin]");
+        return;
+    };
+    let mut config = DatagenConfig::default();
+    if let Some(games) = args.next().and_then(|g| g.parse().ok()) {
+        config.games = games;
+    }
+    if let Some(depth) = args.next().and_then(|d| d.parse().ok()) {
+        config.limit = Limit::Depth(depth);
+    }
+    if let Some(threads) = args.next().and_then(|t| t.parse().ok()) {
+        config.threads = threads;
+    }
+    if args.next().as_deref() == Some("bin") {
+        config.format = Format::Packed;
+    }
+
+    let now = Instant::now();
+    match generate(&config, &dir, HandCrafted::new) {
+        Ok(stats) => println!(
+            "{} games, {} positions in {:?}",
+            stats.games,
+            stats.positions,
+            now.elapsed()
+        ),
+        Err(e) => println!("Failed to write to {dir}: {e}"),
+    }
+}
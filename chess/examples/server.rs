@@ -0,0 +1,26 @@
+use chess::server::Server;
+
+/// Serve the library over HTTP on localhost, see [`chess::server`]
+///
+/// Usage: `server [port]`
+fn main() {
+    let port: u16 = match std::env::args().nth(1).map(|port| port.parse()) {
+        Some(Ok(port)) => port,
+        Some(Err(_)) => {
+            println!("Usage: server [port]");
+            return;
+        }
+        None => 8080,
+    };
+    let server = match Server::bind(("127.0.0.1", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("Failed to listen on port {port}: {e}");
+            return;
+        }
+    };
+    if let Ok(addr) = server.local_addr() {
+        println!("Listening on http://{addr}");
+    }
+    server.run();
+}
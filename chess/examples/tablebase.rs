@@ -0,0 +1,32 @@
+use std::time::Instant;
+
+use chess::tablebase::Generator;
+
+/// Generate the tables given on the command line, like `KBNvK`, and write
+/// them to the current directory
+fn main() {
+    let mut generator = Generator::new();
+    for name in std::env::args().skip(1) {
+        let now = Instant::now();
+        let table = match generator.generate(&name) {
+            Ok(table) => table,
+            Err(e) => {
+                println!("{name}: {e:?}");
+                continue;
+            }
+        };
+        let stats = table.stats();
+        println!("{} in {:?}", table.name(), now.elapsed());
+        for (side, label) in ["White", "Black"].into_iter().enumerate() {
+            println!(
+                " {label} to move: {} wins, {} draws, {} losses, longest mate {} plies",
+                stats.wins[side], stats.draws[side], stats.losses[side], stats.longest[side]
+            );
+        }
+
+        let path = format!("{}.cetb", table.name());
+        if let Err(e) = std::fs::write(&path, table.to_bytes()) {
+            println!(" Failed to write {path}: {e}");
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    sync::Arc,
+};
+
+use chess::{
+    game::Game,
+    syzygy::Tablebase,
+    tournament::{
+        DrawRule, MatchConfig, MatchStats, Opening, Player, ResignRule, Sprt, SprtStatus, run,
+    },
+    uci::{BestMove, Engine, Go},
+};
+
+const USAGE: &str = "Usage: tournament -engine cmd=<path> [name=<name>] [option.<name>=<value>]... \
+-engine ... [-games n] [-concurrency n] [-tc 40/60+0.5] [-depth n] [-nodes n] \
+[-openings <file.epd|file.pgn> [plies]] [-pgnout <file>] [-sprt elo0 elo1 alpha beta] \
+[-resign moves score] [-draw movenumber moves score] [-maxmoves n] [-tb <dir>]";
+
+#[derive(Debug, Default, Clone)]
+struct EngineConfig {
+    cmd: String,
+    name: Option<String>,
+    options: Vec<(String, String)>,
+}
+
+/// Engine with the name given on the command line
+struct Named {
+    engine: Engine,
+    name: Option<String>,
+}
+
+impl Player for Named {
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| Player::name(&self.engine))
+    }
+
+    fn new_game(&mut self) -> io::Result<()> {
+        self.engine.new_game()
+    }
+
+    fn think(&mut self, game: &Game, go: &Go, limit: std::time::Duration) -> io::Result<BestMove> {
+        self.engine.think(game, go, limit)
+    }
+}
+
+fn spawn(config: &EngineConfig) -> io::Result<Named> {
+    let mut engine = Engine::spawn(&config.cmd)?;
+    for (name, value) in &config.options {
+        engine.set_option(name, Some(value))?;
+    }
+    Ok(Named {
+        engine,
+        name: config.name.clone(),
+    })
+}
+
+/// Play a match between two UCI engines, printing the score after each game
+///
+/// Usage: see [`USAGE`]
+fn main() {
+    match parse(std::env::args().skip(1).collect()) {
+        Ok((config, engines, pgnout)) => {
+            if let Err(e) = play(&config, &engines, pgnout.as_deref()) {
+                println!("Match failed: {e}");
+            }
+        }
+        Err(e) => {
+            println!("{e}");
+            println!("{USAGE}");
+        }
+    }
+}
+
+fn play(config: &MatchConfig, engines: &[EngineConfig; 2], pgnout: Option<&str>) -> io::Result<()> {
+    let mut pgn = match pgnout {
+        Some(path) => Some(File::options().create(true).append(true).open(path)?),
+        None => None,
+    };
+    let mut failed = None;
+    let stats = run(
+        config,
+        |i| spawn(&engines[i]),
+        |game, stats| {
+            let white = game.pgn.tag("White").unwrap_or("?").to_string();
+            let black = game.pgn.tag("Black").unwrap_or("?").to_string();
+            println!(
+                "Game {} ({white} vs {black}): {} {{{}}}",
+                game.round + 1,
+                game.result.pgn_result(),
+                game.pgn.tag("Termination").unwrap_or("?")
+            );
+            let (first, second) = match game.white {
+                0 => (white, black),
+                _ => (black, white),
+            };
+            report(config, &first, &second, stats);
+            if let Some(file) = &mut pgn
+                && let Err(e) = writeln!(file, "{}", game.pgn)
+            {
+                failed.get_or_insert(e);
+            }
+        },
+    )?;
+    if let Some(sprt) = config.sprt {
+        match sprt.status(&stats) {
+            SprtStatus::AcceptH0 => println!("SPRT: H0 was accepted"),
+            SprtStatus::AcceptH1 => println!("SPRT: H1 was accepted"),
+            SprtStatus::Running => println!("SPRT: no decision"),
+        }
+    }
+    failed.map_or(Ok(()), Err)
+}
+
+fn report(config: &MatchConfig, first: &str, second: &str, stats: &MatchStats) {
+    println!(
+        "Score of {first} vs {second}: {} - {} - {} [{:.3}] {}",
+        stats.wins,
+        stats.losses,
+        stats.draws,
+        stats.score(),
+        stats.games()
+    );
+    let mut line = match stats.elo() {
+        Some(elo) => format!("Elo difference: {:.1} +/- {:.1}", elo.diff, elo.margin),
+        None => String::from("Elo difference: -"),
+    };
+    if let Some(sprt) = config.sprt {
+        let (lower, upper) = sprt.bounds();
+        line.push_str(&format!(
+            ", SPRT: llr {:.2} ({lower:.2}, {upper:.2}), elo0 {}, elo1 {}",
+            sprt.llr(stats),
+            sprt.elo0,
+            sprt.elo1
+        ));
+    }
+    println!("{line}");
+}
+
+type Parsed = (MatchConfig, [EngineConfig; 2], Option<String>);
+
+fn parse(args: Vec<String>) -> Result<Parsed, String> {
+    let mut config = MatchConfig::default();
+    let mut engines = Vec::new();
+    let mut pgnout = None;
+    let mut args = args.into_iter().peekable();
+
+    while let Some(flag) = args.next() {
+        let mut values = Vec::new();
+        while let Some(value) =
+            args.next_if(|arg| !arg.starts_with('-') || arg.parse::<f64>().is_ok())
+        {
+            values.push(value);
+        }
+        let number = |i: usize| -> Result<f64, String> {
+            values
+                .get(i)
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| format!("{flag} needs a number"))
+        };
+        match flag.as_str() {
+            "-engine" => {
+                let mut engine = EngineConfig::default();
+                for value in &values {
+                    match value.split_once('=') {
+                        Some(("cmd", cmd)) => engine.cmd = cmd.to_string(),
+                        Some(("name", name)) => engine.name = Some(name.to_string()),
+                        Some((option, value)) if option.starts_with("option.") => engine
+                            .options
+                            .push((option["option.".len()..].to_string(), value.to_string())),
+                        _ => return Err(format!("Unknown engine setting {value}")),
+                    }
+                }
+                if engine.cmd.is_empty() {
+                    return Err(String::from("-engine needs cmd=<path>"));
+                }
+                engines.push(engine);
+            }
+            "-games" => config.games = number(0)? as usize,
+            "-concurrency" => config.concurrency = number(0)? as usize,
+            "-tc" => {
+                let tc = values.first().ok_or("-tc needs a time control")?;
+                config.time_control = Some(
+                    tc.parse()
+                        .map_err(|_| format!("Invalid time control {tc}"))?,
+                );
+            }
+            "-depth" => config.limits.depth = Some(number(0)? as u32),
+            "-nodes" => config.limits.nodes = Some(number(0)? as u64),
+            "-openings" => {
+                let path = values.first().ok_or("-openings needs a file")?;
+                let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+                let plies = number(1).ok().map(|plies| plies as usize);
+                config.openings = if path.ends_with(".pgn") {
+                    Opening::parse_pgn(&text, plies)
+                } else {
+                    Opening::parse_epd(&text)
+                }
+                .map_err(|e| format!("{path}: {e:?}"))?;
+            }
+            "-pgnout" => pgnout = Some(values.first().ok_or("-pgnout needs a file")?.clone()),
+            "-sprt" => {
+                config.sprt = Some(Sprt {
+                    elo0: number(0)?,
+                    elo1: number(1)?,
+                    alpha: number(2)?,
+                    beta: number(3)?,
+                });
+            }
+            "-resign" => {
+                config.adjudication.resign = Some(ResignRule {
+                    moves: number(0)? as u32,
+                    score: number(1)? as i32,
+                });
+            }
+            "-draw" => {
+                config.adjudication.draw = Some(DrawRule {
+                    move_number: number(0)? as u32,
+                    moves: number(1)? as u32,
+                    score: number(2)? as i32,
+                });
+            }
+            "-maxmoves" => config.adjudication.max_moves = Some(number(0)? as u32),
+            "-tb" => {
+                let dir = values.first().ok_or("-tb needs a directory")?;
+                let tablebase = Tablebase::open(dir).map_err(|e| format!("{dir}: {e}"))?;
+                config.adjudication.tablebase = Some(Arc::new(tablebase));
+            }
+            _ => return Err(format!("Unknown argument {flag}")),
+        }
+    }
+
+    let engines: [EngineConfig; 2] = engines
+        .try_into()
+        .map_err(|_| String::from("Two engines are needed"))?;
+    if config.time_control.is_none() && config.limits == Go::default() {
+        return Err(String::from("Set a time control, a depth or a node count"));
+    }
+    config.event = format!(
+        "{} vs {}",
+        engines[0].name.as_deref().unwrap_or(&engines[0].cmd),
+        engines[1].name.as_deref().unwrap_or(&engines[1].cmd)
+    );
+    Ok((config, engines, pgnout))
+}
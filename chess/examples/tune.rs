@@ -0,0 +1,49 @@
+use chess::eval::{EvalParams, tune::Tuner};
+
+const LEARNING_RATE: f64 = 1.0;
+
+/// Tune the hand-crafted evaluation on a file of `FEN result` lines and write
+/// the weights in the layout of `src/eval/weights.rs`
+///
+/// Usage: `tune <positions> [epochs] [output]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(input) = args.next() else {
+        println!("Usage: tune <positions> [epochs] [output]");
+        return;
+    };
+    let epochs = args.next().and_then(|e| e.parse().ok()).unwrap_or(1000);
+    let output = args.next().unwrap_or_else(|| "weights.rs".to_string());
+
+    let text = match std::fs::read_to_string(&input) {
+        Ok(text) => text,
+        Err(e) => {
+            println!("Failed to read {input}: {e}");
+            return;
+        }
+    };
+    let mut tuner = Tuner::new(EvalParams::default());
+    let skipped = text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !tuner.add_line(line))
+        .count();
+    println!("{} positions, {skipped} lines skipped", tuner.len());
+
+    let k = tuner.fit_scaling();
+    println!("K = {k:.3}, error {:.6}", tuner.error(k));
+    for epoch in 1..=epochs {
+        let error = tuner.epoch(k, LEARNING_RATE);
+        if epoch % 100 == 0 {
+            println!(" epoch {epoch}: error {error:.6}");
+        }
+    }
+    println!("Final error {:.6}", tuner.error(k));
+
+    let weights = format!(
+        "// Evaluation weights, written by the tune example\n{}",
+        tuner.params()
+    );
+    if let Err(e) = std::fs::write(&output, weights) {
+        println!("Failed to write {output}: {e}");
+    }
+}
@@ -0,0 +1,13 @@
+use std::io;
+
+use chess::{eval::HandCrafted, xboard::XBoard};
+
+/// Play through an xboard or WinBoard interface on stdin and stdout
+///
+/// Usage: `xboard`, added to the interface as the engine command
+fn main() {
+    let mut xboard = XBoard::new(HandCrafted::new());
+    if let Err(e) = xboard.run(io::stdin().lock(), io::stdout()) {
+        eprintln!("xboard: {e}");
+    }
+}
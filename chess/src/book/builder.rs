@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::{ChessError, Chessboard, GameResult, Move, Side};
+
+use super::{Book, BookEntry, encode_move, polyglot_key};
+
+/// Collects moves of games into a new book
+///
+/// A move scores 2 points for a win, 1 for a draw or an unknown result and 0 for a loss
+#[derive(Debug, Clone, Default)]
+pub struct BookBuilder {
+    max_ply: Option<usize>,
+    weights: HashMap<(u64, u16), u64>,
+}
+
+impl BookBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the first `max_ply` moves of every game are added
+    #[must_use]
+    pub const fn max_ply(mut self, max_ply: usize) -> Self {
+        self.max_ply = Some(max_ply);
+        self
+    }
+
+    /// Add the game played from the starting position
+    ///
+    /// Fails on the first illegal move, the moves before it are kept
+    pub fn add_game(
+        &mut self,
+        start: Chessboard,
+        moves: &[Move],
+        result: GameResult,
+    ) -> Result<(), ChessError> {
+        let max_ply = self.max_ply.unwrap_or(usize::MAX);
+        let mut board = start;
+
+        for m in moves.iter().take(max_ply) {
+            let key = polyglot_key(&board);
+            let score = match (result, board.side()) {
+                (GameResult::WhiteWin(_), Side::White) | (GameResult::BlackWin(_), Side::Black) => 2,
+                (GameResult::WhiteWin(_), Side::Black) | (GameResult::BlackWin(_), Side::White) => 0,
+                _ => 1,
+            };
+
+            board.try_make_move(*m)?;
+
+            if let Some(mv) = encode_move(*m) {
+                *self.weights.entry((key, mv)).or_default() += score;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the book, weights are scaled down to fit the format
+    pub fn build(&self) -> Book {
+        let max = self.weights.values().copied().max().unwrap_or(0);
+        let scale = |weight: u64| {
+            if max <= u16::MAX as u64 {
+                weight as u16
+            } else {
+                (weight * u16::MAX as u64 / max).max(1) as u16
+            }
+        };
+
+        let entries = self
+            .weights
+            .iter()
+            .filter(|(_, weight)| **weight > 0)
+            .map(|((key, mv), weight)| BookEntry {
+                key: *key,
+                mv: *mv,
+                weight: scale(*weight),
+                learn: 0,
+            })
+            .collect();
+
+        Book::new(entries)
+    }
+}
@@ -0,0 +1,375 @@
+//! Polyglot opening books
+//!
+//! Books are sorted arrays of 16 byte big-endian entries: key, move, weight and learn
+
+mod builder;
+mod random;
+
+use std::io::{Read, Seek, SeekFrom};
+
+pub use builder::BookBuilder;
+use random::RANDOM64;
+
+use crate::{
+    Castling, ChessError, Chessboard, File, Move, Piece, PieceType, Rank, Side, Square,
+    get_pawn_attacks,
+};
+
+const CASTLING_OFFSET: usize = 768;
+const EN_PASSANT_OFFSET: usize = 772;
+const TURN_OFFSET: usize = 780;
+
+/// Size of one entry in the book file
+pub const ENTRY_SIZE: usize = 16;
+
+/// Compute the Polyglot key of the position
+///
+/// En passant file is hashed only when a pawn of the side to move can capture
+pub const fn polyglot_key(cb: &Chessboard) -> u64 {
+    let mut key = 0;
+
+    let mut occupied = cb.occupied();
+    while let Some(sq) = occupied.next_const() {
+        // SAFETY: the square is occupied
+        let piece = unsafe { cb.at(sq).unwrap_unchecked() };
+        key ^= RANDOM64[64 * piece_kind(piece) + sq.as_u8() as usize];
+    }
+
+    let castling = cb.castling();
+    if castling.is_set(Castling::KingSide, Side::White) {
+        key ^= RANDOM64[CASTLING_OFFSET];
+    }
+    if castling.is_set(Castling::QueenSide, Side::White) {
+        key ^= RANDOM64[CASTLING_OFFSET + 1];
+    }
+    if castling.is_set(Castling::KingSide, Side::Black) {
+        key ^= RANDOM64[CASTLING_OFFSET + 2];
+    }
+    if castling.is_set(Castling::QueenSide, Side::Black) {
+        key ^= RANDOM64[CASTLING_OFFSET + 3];
+    }
+
+    if let Some(sq) = cb.en_passant() {
+        let us = cb.side();
+        let pawns = cb.get_pieces(Piece::from_side_and_type(us, PieceType::Pawn));
+        if get_pawn_attacks(us.neg(), sq).overlaps(pawns) {
+            key ^= RANDOM64[EN_PASSANT_OFFSET + sq.file().as_u8() as usize];
+        }
+    }
+
+    if matches!(cb.side(), Side::White) {
+        key ^= RANDOM64[TURN_OFFSET];
+    }
+
+    key
+}
+
+/// Index of the piece in the Polyglot random table
+const fn piece_kind(piece: Piece) -> usize {
+    let kind = match piece.piece_type() {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    match piece.side() {
+        Side::White => kind * 2 + 1,
+        Side::Black => kind * 2,
+    }
+}
+
+/// Entry of the Polyglot book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BookEntry {
+    pub key: u64,
+    /// Move in the Polyglot encoding
+    pub mv: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    pub const fn from_bytes(bytes: [u8; ENTRY_SIZE]) -> Self {
+        let [k0, k1, k2, k3, k4, k5, k6, k7, m0, m1, w0, w1, l0, l1, l2, l3] = bytes;
+        Self {
+            key: u64::from_be_bytes([k0, k1, k2, k3, k4, k5, k6, k7]),
+            mv: u16::from_be_bytes([m0, m1]),
+            weight: u16::from_be_bytes([w0, w1]),
+            learn: u32::from_be_bytes([l0, l1, l2, l3]),
+        }
+    }
+
+    pub const fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let [k0, k1, k2, k3, k4, k5, k6, k7] = self.key.to_be_bytes();
+        let [m0, m1] = self.mv.to_be_bytes();
+        let [w0, w1] = self.weight.to_be_bytes();
+        let [l0, l1, l2, l3] = self.learn.to_be_bytes();
+        [
+            k0, k1, k2, k3, k4, k5, k6, k7, m0, m1, w0, w1, l0, l1, l2, l3,
+        ]
+    }
+
+    /// Decode the move for the position
+    ///
+    /// Returns [`None`] if the move is not legal in the position
+    pub const fn to_move(&self, cb: &Chessboard) -> Option<Move> {
+        decode_move(cb, self.mv)
+    }
+}
+
+/// Decode the Polyglot move for the position
+///
+/// Castling is encoded as the king taking its own rook
+pub const fn decode_move(cb: &Chessboard, mv: u16) -> Option<Move> {
+    let to = Square::at(
+        File::from_u8((mv & 7) as u8),
+        Rank::from_u8(((mv >> 3) & 7) as u8),
+    );
+    let from = Square::at(
+        File::from_u8(((mv >> 6) & 7) as u8),
+        Rank::from_u8(((mv >> 9) & 7) as u8),
+    );
+    let promotion = match (mv >> 12) & 7 {
+        0 => None,
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => return None,
+    };
+
+    let is_king = match cb.at(from) {
+        Some(piece) => matches!(piece.piece_type(), PieceType::King),
+        None => false,
+    };
+    let to = match (from, to) {
+        (Square::E1, Square::H1) if is_king => Square::G1,
+        (Square::E1, Square::A1) if is_king => Square::C1,
+        (Square::E8, Square::H8) if is_king => Square::G8,
+        (Square::E8, Square::A8) if is_king => Square::C8,
+        _ => to,
+    };
+
+    cb.move_from_squares(from, to, promotion)
+}
+
+/// Encode the move in the Polyglot format
+///
+/// Returns [`None`] for moves that can not be stored in a book
+pub const fn encode_move(m: Move) -> Option<u16> {
+    let (from, to, promotion) = match m {
+        Move::Standard { from, to, .. } | Move::EnPassant { from, to } => (from, to, 0),
+        Move::Promotion {
+            from,
+            to,
+            promotion,
+        } => {
+            let promotion = match promotion.piece_type() {
+                PieceType::Knight => 1,
+                PieceType::Bishop => 2,
+                PieceType::Rook => 3,
+                PieceType::Queen => 4,
+                PieceType::Pawn | PieceType::King => return None,
+            };
+            (from, to, promotion)
+        }
+        Move::Castling { castling, from, .. } => {
+            let side = match from {
+                Square::E1 => Side::White,
+                _ => Side::Black,
+            };
+            (from, castling.rook_squares(side).0, 0)
+        }
+        Move::Put { .. } | Move::Remove { .. } | Move::Null => return None,
+    };
+
+    Some(
+        (to.file().as_u8() as u16)
+            | (to.rank().as_u8() as u16) << 3
+            | (from.file().as_u8() as u16) << 6
+            | (from.rank().as_u8() as u16) << 9
+            | promotion << 12,
+    )
+}
+
+/// Opening book loaded into memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    /// Create a book from entries in any order
+    pub fn new(mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)));
+        Self { entries }
+    }
+
+    /// Parse the contents of a book file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChessError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(ChessError::InvalidBook);
+        }
+
+        let mut entries = Vec::with_capacity(bytes.len() / ENTRY_SIZE);
+        for chunk in bytes.chunks_exact(ENTRY_SIZE) {
+            let mut entry = [0; ENTRY_SIZE];
+            entry.copy_from_slice(chunk);
+            entries.push(BookEntry::from_bytes(entry));
+        }
+
+        if !entries.is_sorted_by_key(|e| e.key) {
+            return Err(ChessError::InvalidBook);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries.iter().flat_map(BookEntry::to_bytes).collect()
+    }
+
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    /// Entries for the position key
+    pub fn probe(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = start + self.entries[start..].partition_point(|e| e.key == key);
+        &self.entries[start..end]
+    }
+
+    /// Legal book moves for the position with their weights
+    pub fn moves(&self, cb: &Chessboard) -> Vec<(Move, u16)> {
+        moves_of(cb, self.probe(polyglot_key(cb)))
+    }
+
+    /// The move with the highest weight
+    pub fn best_move(&self, cb: &Chessboard) -> Option<Move> {
+        self.moves(cb)
+            .into_iter()
+            .max_by_key(|(_, weight)| *weight)
+            .map(|(m, _)| m)
+    }
+
+    /// Pick the move using the number in `0..total_weight`
+    pub fn weighted_move(&self, cb: &Chessboard, roll: u32) -> Option<Move> {
+        pick_weighted(&self.moves(cb), roll)
+    }
+
+    /// Pick the random move, each move is chosen proportionally to its weight
+    pub fn random_move(&self, cb: &Chessboard, rng: &mut fastrand::Rng) -> Option<Move> {
+        let moves = self.moves(cb);
+        let total = total_weight(&moves);
+        if total == 0 {
+            return None;
+        }
+        pick_weighted(&moves, rng.u32(0..total))
+    }
+}
+
+/// Opening book that is searched directly in the file
+///
+/// Useful for books too large to be loaded into memory
+#[derive(Debug)]
+pub struct BookFile<R> {
+    reader: R,
+    len: u64,
+}
+
+impl<R: Read + Seek> BookFile<R> {
+    pub fn new(mut reader: R) -> std::io::Result<Self> {
+        let size = reader.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            reader,
+            len: size / ENTRY_SIZE as u64,
+        })
+    }
+
+    /// Number of entries in the book
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn read_entry(&mut self, index: u64) -> std::io::Result<BookEntry> {
+        let mut bytes = [0; ENTRY_SIZE];
+        self.reader
+            .seek(SeekFrom::Start(index * ENTRY_SIZE as u64))?;
+        self.reader.read_exact(&mut bytes)?;
+        Ok(BookEntry::from_bytes(bytes))
+    }
+
+    /// Entries for the position key
+    pub fn probe(&mut self, key: u64) -> std::io::Result<Vec<BookEntry>> {
+        let mut low = 0;
+        let mut high = self.len;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.read_entry(mid)?.key < key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut index = low;
+        while index < self.len {
+            let entry = self.read_entry(index)?;
+            if entry.key != key {
+                break;
+            }
+            entries.push(entry);
+            index += 1;
+        }
+        Ok(entries)
+    }
+
+    /// Legal book moves for the position with their weights
+    pub fn moves(&mut self, cb: &Chessboard) -> std::io::Result<Vec<(Move, u16)>> {
+        let entries = self.probe(polyglot_key(cb))?;
+        Ok(moves_of(cb, &entries))
+    }
+
+    /// Pick the random move, each move is chosen proportionally to its weight
+    pub fn random_move(
+        &mut self,
+        cb: &Chessboard,
+        rng: &mut fastrand::Rng,
+    ) -> std::io::Result<Option<Move>> {
+        let moves = self.moves(cb)?;
+        let total = total_weight(&moves);
+        if total == 0 {
+            return Ok(None);
+        }
+        Ok(pick_weighted(&moves, rng.u32(0..total)))
+    }
+}
+
+fn moves_of(cb: &Chessboard, entries: &[BookEntry]) -> Vec<(Move, u16)> {
+    entries
+        .iter()
+        .filter_map(|e| Some((e.to_move(cb)?, e.weight)))
+        .collect()
+}
+
+fn total_weight(moves: &[(Move, u16)]) -> u32 {
+    moves.iter().map(|(_, weight)| *weight as u32).sum()
+}
+
+fn pick_weighted(moves: &[(Move, u16)], mut roll: u32) -> Option<Move> {
+    for (m, weight) in moves {
+        let weight = *weight as u32;
+        if roll < weight {
+            return Some(*m);
+        }
+        roll -= weight;
+    }
+    None
+}
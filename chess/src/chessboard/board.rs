@@ -1,7 +1,9 @@
-use types::{CastlingInfo, ChessError, GameResult, Move, MoveList, WinType};
+use types::{
+    CastlingInfo, ChessError, DrawType, GameResult, IllegalMoveReason, Move, MoveList, WinType,
+};
 
 use crate::{
-    get_bishop_attacks, get_bishop_pinner, get_connection_direct, get_knight_attacks, get_pawn_attacks, get_rook_attacks, get_rook_pinner, Bitboard, ChessboardRaw, MoveGen, Piece, PieceType, Side, Square, START_POS
+    get_bishop_attacks, get_bishop_pinner, get_connection_axis, get_connection_direct, get_knight_attacks, get_pawn_attacks, get_piece_attacks, get_rook_attacks, get_rook_pinner, Bitboard, ChessboardRaw, MoveGen, Piece, PieceType, Side, Square, START_POS
 };
 
 /// Chessboard representation
@@ -36,6 +38,12 @@ impl Chessboard {
     }
 
     pub const fn from_raw(raw: ChessboardRaw) -> Result<Self, ChessError> {
+        // Move generation needs the king of each side
+        let white_kings = raw.get_pieces(Piece::from_side_and_type(Side::White, PieceType::King));
+        let black_kings = raw.get_pieces(Piece::from_side_and_type(Side::Black, PieceType::King));
+        if white_kings.popcnt() != 1 || black_kings.popcnt() != 1 {
+            return Err(ChessError::InvalidKings);
+        }
         let mut s = Self {
             inner: raw,
             checkers: Bitboard::EMPTY,
@@ -77,6 +85,15 @@ impl Chessboard {
         self.inner.en_passant
     }
 
+    /// Half moves since the last capture or pawn move
+    pub const fn half_moves(&self) -> usize {
+        self.inner.half_moves
+    }
+
+    pub const fn full_moves(&self) -> usize {
+        self.inner.full_moves
+    }
+
     pub const fn occupied(&self) -> Bitboard {
         let white = self.inner.colors[0];
         let black = self.inner.colors[1];
@@ -176,12 +193,153 @@ impl Chessboard {
         }
     }
 
+    /// Our pieces that are the only blocker between our slider and the enemy king
+    ///
+    /// Moving such piece off the line gives a discovered check
+    pub const fn calculate_discoverers(&self) -> Bitboard {
+        let us = self.inner.side;
+        let them = us.neg();
+
+        let Some(enemy_king) = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(them, PieceType::King))
+            .first_square()
+        else {
+            return Bitboard::EMPTY;
+        };
+
+        let our_queens = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(us, PieceType::Queen));
+        let our_rooks = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(us, PieceType::Rook))
+            .or(our_queens);
+        let our_bishops = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(us, PieceType::Bishop))
+            .or(our_queens);
+
+        let color_us = self.colors(us);
+        let rook_pinners = our_rooks.and(get_rook_pinner(enemy_king, self.occupied(), color_us));
+        let bishop_pinners =
+            our_bishops.and(get_bishop_pinner(enemy_king, self.occupied(), color_us));
+
+        let mut pinners = rook_pinners.or(bishop_pinners);
+        let mut discoverers = Bitboard::EMPTY;
+
+        while let Some(pinner_sq) = pinners.next_const() {
+            discoverers =
+                discoverers.or(color_us.and(get_connection_direct(pinner_sq, enemy_king)));
+        }
+
+        discoverers
+    }
+
+    /// Whether the move checks the enemy king
+    ///
+    /// Detects direct and discovered checks without making the move
+    pub const fn gives_check(&self, m: Move) -> bool {
+        let us = self.inner.side;
+        let them = us.neg();
+
+        let Some(enemy_king) = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(them, PieceType::King))
+            .first_square()
+        else {
+            return false;
+        };
+        let king_bb = enemy_king.bitboard();
+        let occupied = self.occupied();
+
+        match m {
+            Move::Standard { piece, from, to } => {
+                let new_occupied = occupied.xor(from.bitboard()).or(to.bitboard());
+                if get_piece_attacks(piece, to, new_occupied).overlaps(king_bb) {
+                    return true;
+                }
+                self.calculate_discoverers().overlaps(from.bitboard())
+                    && !get_connection_axis(enemy_king, from).overlaps(to.bitboard())
+            }
+            Move::Promotion {
+                from,
+                to,
+                promotion,
+            } => {
+                let new_occupied = occupied.xor(from.bitboard()).or(to.bitboard());
+                if get_piece_attacks(promotion, to, new_occupied).overlaps(king_bb) {
+                    return true;
+                }
+                self.calculate_discoverers().overlaps(from.bitboard())
+                    && !get_connection_axis(enemy_king, from).overlaps(to.bitboard())
+            }
+            Move::EnPassant { from, to } => {
+                let captured = match us {
+                    Side::White => to.bitboard().shift_down(),
+                    Side::Black => to.bitboard().shift_up(),
+                };
+                if get_pawn_attacks(us, to).overlaps(king_bb) {
+                    return true;
+                }
+                // Two pawns leave the board at once, so the lines are recomputed
+                let new_occupied = occupied
+                    .xor(from.bitboard())
+                    .xor(captured)
+                    .or(to.bitboard());
+                self.sliders_attack(us, enemy_king, new_occupied)
+            }
+            Move::Castling { castling, from, to } => {
+                let (rook_start, rook_end) = castling.rook_squares(us);
+                let new_occupied = occupied
+                    .xor(from.bitboard())
+                    .xor(rook_start.bitboard())
+                    .or(to.bitboard())
+                    .or(rook_end.bitboard());
+                get_rook_attacks(rook_end, new_occupied).overlaps(king_bb)
+            }
+            Move::Put { piece, to } => {
+                if piece.side().as_u8() != us.as_u8() {
+                    return false;
+                }
+                get_piece_attacks(piece, to, occupied.or(to.bitboard())).overlaps(king_bb)
+            }
+            Move::Remove { from } => {
+                self.sliders_attack(us, enemy_king, occupied.xor(from.bitboard()))
+            }
+            Move::Null => false,
+        }
+    }
+
+    /// Whether sliders of the side attack the square with the given occupancy
+    const fn sliders_attack(&self, side: Side, sq: Square, occupied: Bitboard) -> bool {
+        let queens = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(side, PieceType::Queen));
+        let rooks = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(side, PieceType::Rook))
+            .or(queens)
+            .and(occupied);
+        let bishops = self
+            .inner
+            .get_pieces(Piece::from_side_and_type(side, PieceType::Bishop))
+            .or(queens)
+            .and(occupied);
+
+        get_rook_attacks(sq, occupied).overlaps(rooks)
+            || get_bishop_attacks(sq, occupied).overlaps(bishops)
+    }
+
     pub const fn move_new(&self, m: types::Move) -> Self {
         let mut board = *self;
         board.make_move(m);
         board
     }
 
+    /// Make the move without checking whether it is legal
+    ///
+    /// Use [`Chessboard::try_make_move`] for moves coming from players
     pub const fn make_move(&mut self, m: types::Move) -> bool {
         if !self.inner.make_move(m) {
             return false;
@@ -190,6 +348,35 @@ impl Chessboard {
         true
     }
 
+    /// Take back the move, see [`Chessboard::generate_unmoves`]
+    pub const fn unmake_move(&mut self, um: types::UnMove) {
+        self.inner.unmake_move(um);
+        self.calculate_extra();
+    }
+
+    /// Pass the turn without moving
+    ///
+    /// Returns the en passant square to give back to [`Chessboard::unmake_null_move`].
+    /// Passing is not allowed while in check
+    pub const fn make_null_move(&mut self) -> Result<Option<Square>, ChessError> {
+        if !self.checkers.is_empty() {
+            return Err(ChessError::IllegalMove(IllegalMoveReason::LeavesKingInCheck));
+        }
+
+        let en_passant = self.inner.en_passant;
+        self.inner.en_passant = None;
+        self.inner.toggle_turn();
+        self.calculate_extra();
+        Ok(en_passant)
+    }
+
+    /// Take back the null move made by [`Chessboard::make_null_move`]
+    pub const fn unmake_null_move(&mut self, en_passant: Option<Square>) {
+        self.inner.en_passant = en_passant;
+        self.inner.toggle_turn();
+        self.calculate_extra();
+    }
+
     pub const fn generate_moves(&mut self) -> MoveList {
         if self.moves_cache.is_empty() {
             self.moves_cache = crate::move_gen::generate_moves(self);
@@ -203,7 +390,7 @@ impl Chessboard {
 
         if self.moves_cache.is_empty() {
             if self.checkers.is_empty() {
-                self.result = GameResult::Stalemate;
+                self.result = GameResult::Draw(DrawType::Stalemate);
             } else {
                 match self.inner.side {
                     Side::White => self.result = GameResult::BlackWin(WinType::Checkmate),
@@ -213,38 +400,57 @@ impl Chessboard {
         }
 
         if self.inner.half_moves >= 100 {
-            self.result = GameResult::FiftyMoveRule;
+            self.result = GameResult::Draw(DrawType::FiftyMoveRule);
         }
 
-        let white_count = self.colors(Side::White).popcnt();
-        let black_count = self.colors(Side::Black).popcnt();
-
-        let white_minor = !self.get_pieces(Piece::WHITE_BISHOP).is_empty()
-            || !self.get_pieces(Piece::WHITE_KNIGHT).is_empty();
-        let black_minor = !self.get_pieces(Piece::BLACK_BISHOP).is_empty()
-            || !self.get_pieces(Piece::BLACK_KNIGHT).is_empty();
-
-        match (white_count, black_count) {
-            (1, 1) => self.result = GameResult::InsufficientMaterial,
-            (1, 2) if black_minor => self.result = GameResult::InsufficientMaterial,
-            (2, 1) if white_minor => self.result = GameResult::InsufficientMaterial,
+        if self.insufficient_material() {
+            self.result = GameResult::Draw(DrawType::InsufficientMaterial);
+        }
+    }
 
-            (2, 2) => {
-                let white_bishops = self.get_pieces(Piece::WHITE_BISHOP);
-                let black_bishops = self.get_pieces(Piece::BLACK_BISHOP);
+    /// Whether neither side can mate, whatever the moves
+    ///
+    /// Covers kings with a knight or with bishops on squares of one colour,
+    /// see [`Chessboard::is_dead_position`] for blocked positions
+    pub const fn insufficient_material(&self) -> bool {
+        !self.has_mating_material(Side::White) && !self.has_mating_material(Side::Black)
+    }
 
-                let white_bishop_white_sq = white_bishops.overlaps(Bitboard::WHITE_SQUARES);
-                let white_bishop_black_sq = white_bishops.overlaps(Bitboard::BLACK_SQUARES);
-                let black_bishop_white_sq = black_bishops.overlaps(Bitboard::WHITE_SQUARES);
-                let black_bishop_black_sq = black_bishops.overlaps(Bitboard::BLACK_SQUARES);
+    /// Whether the side could mate by some sequence of legal moves, judging by the pieces
+    ///
+    /// A lone knight needs an enemy piece to block the king, bishops on one
+    /// colour need an enemy piece that can stand on the other one. Used for
+    /// draws by material and for flag-fall rulings, where the game is drawn if
+    /// the opponent cannot mate
+    pub const fn has_mating_material(&self, side: Side) -> bool {
+        let ours = self.colors(side);
+        let king = self.get_pieces(Piece::from_side_and_type(side, PieceType::King));
+        let bishops = self.get_pieces(Piece::from_side_and_type(side, PieceType::Bishop));
+        let knights = self.get_pieces(Piece::from_side_and_type(side, PieceType::Knight));
+        if !ours.xor(king).xor(bishops).xor(knights).is_empty() {
+            return true;
+        }
 
-                if (white_bishop_white_sq && black_bishop_white_sq)
-                    || (white_bishop_black_sq && black_bishop_black_sq)
-                {
-                    self.result = GameResult::InsufficientMaterial;
-                }
+        let them = side.neg();
+        let their_king = self.get_pieces(Piece::from_side_and_type(them, PieceType::King));
+        let their_bishops = self.get_pieces(Piece::from_side_and_type(them, PieceType::Bishop));
+        let theirs = self.colors(them).xor(their_king);
+        match (bishops.popcnt(), knights.popcnt()) {
+            (0, 0) => false,
+            (0, 1) => !theirs.is_empty(),
+            (_, 0) => {
+                let light = bishops.overlaps(Bitboard::WHITE_SQUARES);
+                let dark = bishops.overlaps(Bitboard::BLACK_SQUARES);
+                let other_colour = if light {
+                    Bitboard::BLACK_SQUARES
+                } else {
+                    Bitboard::WHITE_SQUARES
+                };
+                (light && dark)
+                    || !theirs.xor(their_bishops).is_empty()
+                    || their_bishops.overlaps(other_colour)
             }
-            _ => (),
+            _ => true,
         }
     }
 
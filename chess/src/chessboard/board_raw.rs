@@ -1,4 +1,4 @@
-use types::{Castling, ChessError, Directions, File, Rank};
+use types::{Castling, ChessError, Directions, File, Rank, UnMove};
 
 use crate::{
     Bitboard, CastlingInfo, Chessboard, Piece, PieceType, Side, Square,
@@ -36,6 +36,105 @@ impl ChessboardRaw {
         }
     }
 
+    /// Size of the packed position, see [`ChessboardRaw::to_packed`]
+    pub const PACKED_SIZE: usize = 32;
+
+    /// Pack the position into 32 bytes
+    ///
+    /// Layout, integers are little endian:
+    /// - `0..8` occupancy bitboard
+    /// - `8..24` a nibble per occupied square from A1 up, low nibble first, holding the [`Piece`]
+    /// - `24` castling rights in the low bits, the side to move in the top bit
+    /// - `25` en passant square, 255 when there is none
+    /// - `26..28` half moves as u16 and `28..32` full moves as u32, both saturating
+    pub const fn to_packed(&self) -> [u8; Self::PACKED_SIZE] {
+        let mut bytes = [0; Self::PACKED_SIZE];
+        let occupied = self.colors[0].or(self.colors[1]).as_u64();
+        let occupancy = occupied.to_le_bytes();
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = occupancy[i];
+            i += 1;
+        }
+
+        let mut rest = occupied;
+        let mut n = 0;
+        while rest != 0 {
+            let square = Square::from_u8(rest.trailing_zeros() as u8);
+            rest &= rest - 1;
+            if let Some(piece) = self.at(square) {
+                bytes[8 + n / 2] |= piece.as_u8() << (4 * (n % 2));
+            }
+            n += 1;
+        }
+
+        bytes[24] = self.castling.as_u8() | self.side.as_u8() << 7;
+        bytes[25] = match self.en_passant {
+            Some(square) => square.as_u8(),
+            None => u8::MAX,
+        };
+        let half_moves = if self.half_moves > u16::MAX as usize {
+            u16::MAX
+        } else {
+            self.half_moves as u16
+        };
+        let full_moves = if self.full_moves > u32::MAX as usize {
+            u32::MAX
+        } else {
+            self.full_moves as u32
+        };
+        let [h0, h1] = half_moves.to_le_bytes();
+        let [f0, f1, f2, f3] = full_moves.to_le_bytes();
+        bytes[26] = h0;
+        bytes[27] = h1;
+        bytes[28] = f0;
+        bytes[29] = f1;
+        bytes[30] = f2;
+        bytes[31] = f3;
+        bytes
+    }
+
+    /// Unpack a position written by [`ChessboardRaw::to_packed`]
+    pub const fn from_packed(bytes: &[u8; Self::PACKED_SIZE]) -> Result<Self, ChessError> {
+        let mut occupancy = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            occupancy[i] = bytes[i];
+            i += 1;
+        }
+        let occupied = u64::from_le_bytes(occupancy);
+        if occupied.count_ones() > 32 || bytes[24] & 0b0111_0000 != 0 {
+            return Err(ChessError::InvalidPackedBoard);
+        }
+
+        let mut raw = Self::new();
+        let mut rest = occupied;
+        let mut n = 0;
+        while rest != 0 {
+            let square = Square::from_u8(rest.trailing_zeros() as u8);
+            rest &= rest - 1;
+            let nibble = (bytes[8 + n / 2] >> (4 * (n % 2))) & 15;
+            if nibble & 7 >= PieceType::LEN as u8 {
+                return Err(ChessError::InvalidPackedBoard);
+            }
+            let piece =
+                Piece::from_side_and_type(Side::from_u8(nibble >> 3), PieceType::from_u8(nibble));
+            raw.place_raw(square, piece);
+            n += 1;
+        }
+
+        raw.castling = CastlingInfo::from_u8(bytes[24]);
+        raw.side = Side::from_u8(bytes[24] >> 7);
+        raw.en_passant = match bytes[25] {
+            u8::MAX => None,
+            square if square < 64 => Some(Square::from_u8(square)),
+            _ => return Err(ChessError::InvalidPackedBoard),
+        };
+        raw.half_moves = u16::from_le_bytes([bytes[26], bytes[27]]) as usize;
+        raw.full_moves = u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]) as usize;
+        Ok(raw)
+    }
+
     /// Create a new chessboard from a FEN
     pub const fn from_fen(fen: &str) -> Result<Self, ChessError> {
         FenBuilder::build_fen(fen)
@@ -97,6 +196,17 @@ impl ChessboardRaw {
 
         self.en_passant = None;
 
+        // Capturing the rook on its starting square takes the right away
+        if let types::Move::Standard { to, .. } | types::Move::Promotion { to, .. } = m {
+            match to {
+                Square::A1 => self.castling.set(Castling::QueenSide, Side::White, false),
+                Square::H1 => self.castling.set(Castling::KingSide, Side::White, false),
+                Square::A8 => self.castling.set(Castling::QueenSide, Side::Black, false),
+                Square::H8 => self.castling.set(Castling::KingSide, Side::Black, false),
+                _ => (),
+            }
+        }
+
         let mut reset_halfmoves = false;
         match m {
             types::Move::Standard { piece, from, to } => {
@@ -189,12 +299,7 @@ impl ChessboardRaw {
                 reset_halfmoves = true;
             }
             types::Move::Castling { castling, from, to } => {
-                let (rook_start, rook_end) = match (us, castling) {
-                    (Side::White, Castling::KingSide) => (Square::H1, Square::F1),
-                    (Side::White, Castling::QueenSide) => (Square::A1, Square::D1),
-                    (Side::Black, Castling::KingSide) => (Square::H8, Square::F8),
-                    (Side::Black, Castling::QueenSide) => (Square::A8, Square::D8),
-                };
+                let (rook_start, rook_end) = castling.rook_squares(us);
 
                 let rook = Piece::from_side_and_type(us, PieceType::Rook);
                 self.move_piece_raw(rook_start, rook_end, rook);
@@ -211,6 +316,7 @@ impl ChessboardRaw {
                 }
                 self.place_raw(to, piece);
             }
+            types::Move::Null => (),
             types::Move::Remove { from } => {
                 let piece_from = self.at(from);
                 if piece_from.is_some() {
@@ -235,6 +341,66 @@ impl ChessboardRaw {
         true
     }
 
+    /// Take back the move, restoring the predecessor described by the [`UnMove`]
+    ///
+    /// The half-move clock is decremented, its value before a capture or pawn move is unknown
+    pub const fn unmake_move(&mut self, um: UnMove) {
+        self.toggle_turn();
+        let us = self.side;
+
+        match um.m {
+            types::Move::Standard { piece, from, to } => {
+                self.move_piece_raw(to, from, piece);
+            }
+            types::Move::Promotion {
+                from,
+                to,
+                promotion,
+            } => {
+                self.place_raw(to, promotion);
+                self.place_raw(from, Piece::from_side_and_type(us, PieceType::Pawn));
+            }
+            types::Move::EnPassant { from, to } => {
+                self.move_piece_raw(to, from, Piece::from_side_and_type(us, PieceType::Pawn));
+            }
+            types::Move::Castling { castling, from, to } => {
+                let (rook_start, rook_end) = castling.rook_squares(us);
+                let rook = Piece::from_side_and_type(us, PieceType::Rook);
+                self.move_piece_raw(rook_end, rook_start, rook);
+                let king = Piece::from_side_and_type(us, PieceType::King);
+                self.move_piece_raw(to, from, king);
+            }
+            types::Move::Put { piece, to } => {
+                self.place_raw(to, piece);
+            }
+            types::Move::Remove { .. } | types::Move::Null => (),
+        }
+
+        if let Some(captured) = um.captured {
+            let square = match um.m {
+                types::Move::EnPassant { to, .. } => match us {
+                    Side::White => to.shift(Directions::Down),
+                    Side::Black => to.shift(Directions::Up),
+                },
+                types::Move::Standard { to, .. }
+                | types::Move::Promotion { to, .. }
+                | types::Move::Put { to, .. } => Some(to),
+                types::Move::Remove { from } => Some(from),
+                types::Move::Castling { .. } | types::Move::Null => None,
+            };
+            if let Some(square) = square {
+                self.place_raw(square, captured);
+            }
+        }
+
+        self.castling = um.castling;
+        self.en_passant = um.en_passant;
+        self.half_moves = self.half_moves.saturating_sub(1);
+        if us.as_u8() == Side::Black.as_u8() && self.full_moves > 1 {
+            self.full_moves -= 1;
+        }
+    }
+
     pub const fn remove_castling_for(&mut self, side: Side) {
         self.castling.set(Castling::KingSide, side, false);
         self.castling.set(Castling::QueenSide, side, false);
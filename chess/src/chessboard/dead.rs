@@ -0,0 +1,69 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use types::{Move, PieceType};
+
+use crate::{Chessboard, ChessboardRaw, Piece, Side};
+
+/// Positions searched before giving up on proving a dead position
+const MAX_NODES: usize = 10_000;
+
+impl Chessboard {
+    /// Whether no sequence of legal moves leads to a mate
+    ///
+    /// Besides [`Chessboard::insufficient_material`], positions where every
+    /// pawn is blocked by an enemy pawn are searched: when every position the
+    /// pieces can reach without capturing or moving a pawn is free of mates
+    /// and of such moves, the position is dead. Gives up after a bounded
+    /// number of positions, which is reported as not dead
+    pub fn is_dead_position(&self) -> bool {
+        if self.insufficient_material() {
+            return true;
+        }
+        if !self.pawns_locked() {
+            return false;
+        }
+
+        let mut seen = BTreeSet::from([key(self)]);
+        let mut queue = VecDeque::from([*self]);
+        while let Some(cb) = queue.pop_front() {
+            if cb.result.winner().is_some() {
+                return false;
+            }
+            for m in cb.to_vec() {
+                let progress = match m {
+                    Move::Standard { piece, to, .. } => {
+                        piece.piece_type() == PieceType::Pawn || cb.at(to).is_some()
+                    }
+                    Move::Castling { .. } => false,
+                    _ => true,
+                };
+                if progress {
+                    return false;
+                }
+                let child = cb.move_new(m);
+                if seen.insert(key(&child)) {
+                    if seen.len() > MAX_NODES {
+                        return false;
+                    }
+                    queue.push_back(child);
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether there are pawns and each stands in front of an enemy pawn
+    const fn pawns_locked(&self) -> bool {
+        let white = self.get_pieces(Piece::from_side_and_type(Side::White, PieceType::Pawn));
+        let black = self.get_pieces(Piece::from_side_and_type(Side::Black, PieceType::Pawn));
+        !white.is_empty() && white.as_u64() << 8 == black.as_u64()
+    }
+}
+
+/// The raw board without its move counters, which every move of the search changes
+const fn key(cb: &Chessboard) -> ChessboardRaw {
+    let mut raw = cb.into_raw();
+    raw.half_moves = 0;
+    raw.full_moves = 1;
+    raw
+}
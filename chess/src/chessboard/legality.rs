@@ -0,0 +1,353 @@
+use types::{Castling, ChessError, GameResult, IllegalMoveReason, Move, MoveOutcome, Rank};
+
+use crate::{
+    get_connection_axis, get_connection_direct, get_pawn_attacks, get_piece_attacks, is_check,
+    Bitboard, Chessboard, MoveGen, Piece, PieceType, Side, Square,
+};
+
+impl Chessboard {
+    /// Check whether the move can be played in the current position
+    ///
+    /// Returns the reason why the move is illegal
+    pub const fn check_move(&self, m: Move) -> Result<(), IllegalMoveReason> {
+        let us = self.side();
+
+        let from = match m {
+            Move::Standard { from, .. }
+            | Move::Promotion { from, .. }
+            | Move::EnPassant { from, .. }
+            | Move::Castling { from, .. } => from,
+            Move::Put { .. } | Move::Remove { .. } | Move::Null => {
+                return Err(IllegalMoveReason::NotAGameMove);
+            }
+        };
+
+        let Some(piece) = self.at(from) else {
+            return Err(IllegalMoveReason::NoPieceOnOrigin);
+        };
+        if piece.side().as_u8() != us.as_u8() {
+            return Err(IllegalMoveReason::WrongSideToMove);
+        }
+
+        let checked = match m {
+            Move::Standard {
+                piece: moved,
+                from,
+                to,
+            } => {
+                if moved.as_u8() != piece.as_u8() {
+                    return Err(IllegalMoveReason::PieceMismatch);
+                }
+                self.check_standard(piece, from, to)
+            }
+            Move::Promotion {
+                from,
+                to,
+                promotion,
+            } => self.check_promotion(piece, from, to, promotion),
+            Move::EnPassant { from, to } => self.check_en_passant(piece, from, to),
+            Move::Castling { castling, from, to } => self.check_castling(piece, castling, from, to),
+            Move::Put { .. } | Move::Remove { .. } | Move::Null => {
+                Err(IllegalMoveReason::NotAGameMove)
+            }
+        };
+        if let Err(reason) = checked {
+            return Err(reason);
+        }
+
+        if MoveGen::new(self.moves_cache).is_legal(m) {
+            return Ok(());
+        }
+
+        let to = match m {
+            Move::Standard { to, .. }
+            | Move::Promotion { to, .. }
+            | Move::EnPassant { to, .. }
+            | Move::Castling { to, .. }
+            | Move::Put { to, .. } => to,
+            Move::Remove { from } => from,
+            Move::Null => return Err(IllegalMoveReason::NotAGameMove),
+        };
+        if self.pinned.overlaps(from.bitboard())
+            && !get_connection_axis(self.get_king(), from).overlaps(to.bitboard())
+        {
+            return Err(IllegalMoveReason::PinnedPiece);
+        }
+
+        Err(IllegalMoveReason::LeavesKingInCheck)
+    }
+
+    /// Make the move if it is legal
+    ///
+    /// The board stays untouched when an error is returned
+    pub const fn try_make_move(&mut self, m: Move) -> Result<MoveOutcome, ChessError> {
+        if !matches!(self.result, GameResult::None) {
+            return Err(ChessError::GameOver);
+        }
+        if let Err(reason) = self.check_move(m) {
+            return Err(ChessError::IllegalMove(reason));
+        }
+
+        let captured = match m {
+            Move::Standard { to, .. } | Move::Promotion { to, .. } => self.at(to),
+            Move::EnPassant { .. } => Some(Piece::from_side_and_type(
+                self.side().neg(),
+                PieceType::Pawn,
+            )),
+            Move::Castling { .. } | Move::Put { .. } | Move::Remove { .. } | Move::Null => None,
+        };
+
+        self.make_move(m);
+
+        Ok(MoveOutcome {
+            captured,
+            gave_check: !self.checkers.is_empty(),
+            result: self.result,
+        })
+    }
+
+    /// Squares the piece on the square can legally move to
+    pub const fn legal_targets(&self, from: Square) -> Bitboard {
+        let mut i = 0;
+        while i < self.moves_cache.len {
+            let moves = self.moves_cache.moves[i];
+            if moves.from.as_u8() == from.as_u8() {
+                return moves.bitboard;
+            }
+            i += 1;
+        }
+        Bitboard::EMPTY
+    }
+
+    /// Whether the legal move between the squares needs a promotion piece
+    pub const fn needs_promotion_choice(&self, from: Square, to: Square) -> bool {
+        let Some(piece) = self.at(from) else {
+            return false;
+        };
+        matches!(piece.piece_type(), PieceType::Pawn)
+            && to.rank().as_u8() == Self::last_rank(self.side()).as_u8()
+            && self.legal_targets(from).overlaps(to.bitboard())
+    }
+
+    /// Build the legal move between the squares
+    ///
+    /// The promotion piece must be given exactly when
+    /// [`Chessboard::needs_promotion_choice`] is true
+    pub const fn move_from_squares(
+        &self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceType>,
+    ) -> Option<Move> {
+        if !self.legal_targets(from).overlaps(to.bitboard()) {
+            return None;
+        }
+        let Some(piece) = self.at(from) else {
+            return None;
+        };
+        let us = self.side();
+
+        if self.needs_promotion_choice(from, to) {
+            return match promotion {
+                Some(
+                    t @ (PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen),
+                ) => Some(Move::Promotion {
+                    from,
+                    to,
+                    promotion: Piece::from_side_and_type(us, t),
+                }),
+                _ => None,
+            };
+        }
+        if promotion.is_some() {
+            return None;
+        }
+
+        let m = match piece.piece_type() {
+            PieceType::Pawn => match self.en_passant() {
+                Some(sq) if sq.as_u8() == to.as_u8() => Move::EnPassant { from, to },
+                _ => Move::Standard { piece, from, to },
+            },
+            PieceType::King => match (from, to) {
+                (Square::E1, Square::G1) | (Square::E8, Square::G8) => Move::Castling {
+                    castling: Castling::KingSide,
+                    from,
+                    to,
+                },
+                (Square::E1, Square::C1) | (Square::E8, Square::C8) => Move::Castling {
+                    castling: Castling::QueenSide,
+                    from,
+                    to,
+                },
+                _ => Move::Standard { piece, from, to },
+            },
+            _ => Move::Standard { piece, from, to },
+        };
+        Some(m)
+    }
+
+    const fn check_standard(
+        &self,
+        piece: Piece,
+        from: Square,
+        to: Square,
+    ) -> Result<(), IllegalMoveReason> {
+        if self.colors(self.side()).overlaps(to.bitboard()) {
+            return Err(IllegalMoveReason::OwnPieceOnTarget);
+        }
+
+        if piece.piece_type().as_u8() != PieceType::Pawn.as_u8() {
+            if !get_piece_attacks(piece, from, Bitboard::EMPTY).overlaps(to.bitboard()) {
+                return Err(IllegalMoveReason::InvalidMovement);
+            }
+            if !get_piece_attacks(piece, from, self.occupied()).overlaps(to.bitboard()) {
+                return Err(IllegalMoveReason::PathBlocked);
+            }
+            return Ok(());
+        }
+
+        if let Err(reason) = self.check_pawn_path(from, to) {
+            return Err(reason);
+        }
+        if to.rank().as_u8() == Self::last_rank(self.side()).as_u8() {
+            return Err(IllegalMoveReason::MissingPromotion);
+        }
+        Ok(())
+    }
+
+    const fn check_promotion(
+        &self,
+        piece: Piece,
+        from: Square,
+        to: Square,
+        promotion: Piece,
+    ) -> Result<(), IllegalMoveReason> {
+        if piece.piece_type().as_u8() != PieceType::Pawn.as_u8() {
+            return Err(IllegalMoveReason::InvalidMovement);
+        }
+        if self.colors(self.side()).overlaps(to.bitboard()) {
+            return Err(IllegalMoveReason::OwnPieceOnTarget);
+        }
+        if let Err(reason) = self.check_pawn_path(from, to) {
+            return Err(reason);
+        }
+
+        let valid_piece = match promotion.piece_type() {
+            PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen => true,
+            PieceType::Pawn | PieceType::King => false,
+        };
+        if !valid_piece
+            || promotion.side().as_u8() != self.side().as_u8()
+            || to.rank().as_u8() != Self::last_rank(self.side()).as_u8()
+        {
+            return Err(IllegalMoveReason::InvalidPromotion);
+        }
+        Ok(())
+    }
+
+    const fn check_en_passant(
+        &self,
+        piece: Piece,
+        from: Square,
+        to: Square,
+    ) -> Result<(), IllegalMoveReason> {
+        if piece.piece_type().as_u8() != PieceType::Pawn.as_u8() {
+            return Err(IllegalMoveReason::InvalidMovement);
+        }
+        let is_en_passant = match self.en_passant() {
+            Some(sq) => sq.as_u8() == to.as_u8(),
+            None => false,
+        };
+        if !is_en_passant || !get_pawn_attacks(self.side(), from).overlaps(to.bitboard()) {
+            return Err(IllegalMoveReason::InvalidMovement);
+        }
+        Ok(())
+    }
+
+    const fn check_castling(
+        &self,
+        piece: Piece,
+        castling: Castling,
+        from: Square,
+        to: Square,
+    ) -> Result<(), IllegalMoveReason> {
+        let us = self.side();
+        let (start, target) = match (us, castling) {
+            (Side::White, Castling::KingSide) => (Square::E1, Square::G1),
+            (Side::White, Castling::QueenSide) => (Square::E1, Square::C1),
+            (Side::Black, Castling::KingSide) => (Square::E8, Square::G8),
+            (Side::Black, Castling::QueenSide) => (Square::E8, Square::C8),
+        };
+        let is_king = matches!(piece.piece_type(), PieceType::King);
+        if !is_king || from.as_u8() != start.as_u8() || to.as_u8() != target.as_u8() {
+            return Err(IllegalMoveReason::InvalidMovement);
+        }
+
+        let (rook_start, _) = castling.rook_squares(us);
+        let rook = Piece::from_side_and_type(us, PieceType::Rook);
+        if !self.castling().is_set(castling, us)
+            || !self.get_pieces(rook).overlaps(rook_start.bitboard())
+        {
+            return Err(IllegalMoveReason::NoCastlingRight);
+        }
+
+        if get_connection_direct(from, rook_start).overlaps(self.occupied()) {
+            return Err(IllegalMoveReason::PathBlocked);
+        }
+
+        if !self.checkers.is_empty() {
+            return Err(IllegalMoveReason::CastlingOutOfCheck);
+        }
+
+        let mut path = get_connection_direct(from, to).or(to.bitboard());
+        while let Some(sq) = path.next_const() {
+            if is_check(self, self.occupied(), us.neg(), sq) {
+                return Err(IllegalMoveReason::CastlingThroughCheck);
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the pawn movement ignoring the promotion
+    const fn check_pawn_path(&self, from: Square, to: Square) -> Result<(), IllegalMoveReason> {
+        let us = self.side();
+        let occupied = self.occupied();
+        let to_bb = to.bitboard();
+
+        let (single, start) = match us {
+            Side::White => (from.bitboard().shift_up(), Rank::Second),
+            Side::Black => (from.bitboard().shift_down(), Rank::Seventh),
+        };
+        let double = match us {
+            Side::White => single.shift_up(),
+            Side::Black => single.shift_down(),
+        };
+
+        if single.overlaps(to_bb) {
+            if occupied.overlaps(to_bb) {
+                return Err(IllegalMoveReason::PathBlocked);
+            }
+            return Ok(());
+        }
+
+        if double.overlaps(to_bb) && from.rank().as_u8() == start.as_u8() {
+            if occupied.overlaps(single.or(to_bb)) {
+                return Err(IllegalMoveReason::PathBlocked);
+            }
+            return Ok(());
+        }
+
+        if get_pawn_attacks(us, from).overlaps(to_bb) && self.colors(us.neg()).overlaps(to_bb) {
+            return Ok(());
+        }
+
+        Err(IllegalMoveReason::InvalidMovement)
+    }
+
+    const fn last_rank(side: Side) -> Rank {
+        match side {
+            Side::White => Rank::Eighth,
+            Side::Black => Rank::First,
+        }
+    }
+}
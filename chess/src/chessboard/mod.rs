@@ -1,6 +1,13 @@
 mod board;
 mod board_raw;
 mod builder;
+#[cfg(not(feature = "no_std"))]
+mod dead;
+mod legality;
+#[cfg(not(feature = "no_std"))]
+mod san;
+#[cfg(not(feature = "no_std"))]
+mod unmoves;
 
 pub use board::Chessboard;
 pub use board_raw::ChessboardRaw;
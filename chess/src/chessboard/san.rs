@@ -0,0 +1,105 @@
+use types::{Castling, Move, PieceType};
+
+use crate::Chessboard;
+
+impl Chessboard {
+    /// Standard algebraic notation of the legal move, like `Nbd7`, `exd6`, `e8=Q+` or `O-O#`
+    ///
+    /// The null move is written as `--`. Returns `None` for the editor moves
+    pub fn to_san(&self, m: Move) -> Option<String> {
+        let mut san = String::new();
+        let (from, to) = match m {
+            Move::Standard { from, to, .. }
+            | Move::Promotion { from, to, .. }
+            | Move::EnPassant { from, to } => (from, to),
+            Move::Castling { castling, .. } => {
+                san.push_str(match castling {
+                    Castling::KingSide => "O-O",
+                    Castling::QueenSide => "O-O-O",
+                });
+                self.push_check(m, &mut san);
+                return Some(san);
+            }
+            Move::Null => return Some(String::from("--")),
+            Move::Put { .. } | Move::Remove { .. } => return None,
+        };
+
+        let piece_type = self.at(from)?.piece_type();
+        let capture = self.at(to).is_some() || matches!(m, Move::EnPassant { .. });
+
+        if piece_type == PieceType::Pawn {
+            if capture {
+                san.push(from.file().to_char());
+            }
+        } else {
+            san.push(piece_type.to_char().to_ascii_uppercase());
+
+            let others: Vec<_> = self
+                .to_vec()
+                .into_iter()
+                .filter_map(|other| match other {
+                    Move::Standard {
+                        piece,
+                        from: other_from,
+                        to: other_to,
+                    } if piece.piece_type() == piece_type
+                        && other_to == to
+                        && other_from != from =>
+                    {
+                        Some(other_from)
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|sq| sq.file() != from.file()) {
+                    san.push(from.file().to_char());
+                } else if others.iter().all(|sq| sq.rank() != from.rank()) {
+                    san.push(from.rank().to_char());
+                } else {
+                    san.extend(from.to_chars());
+                }
+            }
+        }
+
+        if capture {
+            san.push('x');
+        }
+        san.extend(to.to_chars());
+        if let Move::Promotion { promotion, .. } = m {
+            san.push('=');
+            san.push(promotion.piece_type().to_char().to_ascii_uppercase());
+        }
+
+        self.push_check(m, &mut san);
+        Some(san)
+    }
+
+    /// Legal move written in standard algebraic notation
+    ///
+    /// Check marks, annotations like `!?`, `0-0` castling and promotions
+    /// without `=` are accepted
+    pub fn from_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+        let san = san.replace('=', "");
+        if san.is_empty() {
+            return None;
+        }
+        self.to_vec().into_iter().find(|&m| {
+            self.to_san(m).is_some_and(|candidate| {
+                candidate.trim_end_matches(['+', '#']).replace('=', "") == san
+            })
+        })
+    }
+
+    fn push_check(&self, m: Move, san: &mut String) {
+        let next = self.move_new(m);
+        if !next.checkers.is_empty() {
+            san.push(if next.moves_cache.is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use types::{Castling, Directions, Move, Rank, UnMove};
+
+use crate::{
+    Chessboard, ChessboardRaw, Piece, PieceType, Side, Square, get_pawn_attacks, get_piece_attacks,
+};
+
+const UNCAPTURES: [PieceType; 5] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+];
+
+impl Chessboard {
+    /// Moves that could have led to the position
+    ///
+    /// Each [`UnMove`] holds the move played in the predecessor, the captured piece
+    /// and the castling rights and en passant square of the predecessor.
+    /// Predecessors reached by a double push of the side now to move come
+    /// with and without its en passant square.
+    /// The half-move clock is not taken into account
+    pub fn generate_unmoves(&self) -> Vec<UnMove> {
+        let raw = self.into_raw();
+        let mover = raw.side.neg();
+        let (forward, back) = match mover {
+            Side::White => (Directions::Up, Directions::Down),
+            Side::Black => (Directions::Down, Directions::Up),
+        };
+        let mut unmoves = Vec::new();
+
+        // Only the double push could have set the en passant square
+        if let Some(ep) = raw.en_passant {
+            let pawn = Piece::from_side_and_type(mover, PieceType::Pawn);
+            let to = ep.shift(forward);
+            let from = ep.shift(back);
+            if let (Some(to), Some(from)) = (to, from)
+                && raw.at(to).is_some_and(|p| p.as_u8() == pawn.as_u8())
+                && raw.at(ep).is_none()
+                && raw.at(from).is_none()
+            {
+                push(
+                    &raw,
+                    &mut unmoves,
+                    Move::Standard {
+                        piece: pawn,
+                        from,
+                        to,
+                    },
+                    None,
+                    None,
+                );
+            }
+            unmoves.retain(|um| is_legal_predecessor(&raw, um));
+            return with_en_passant(&raw, unmoves);
+        }
+
+        let occupied = raw.colors[0].or(raw.colors[1]);
+        let empty = occupied.neg();
+        let last_rank = match mover {
+            Side::White => Rank::Eighth,
+            Side::Black => Rank::First,
+        };
+
+        for to in raw.colors[mover.as_u8() as usize] {
+            let Some(piece) = raw.at(to) else {
+                continue;
+            };
+            let piece_type = piece.piece_type();
+            let captures = uncaptures(mover.neg(), to);
+
+            if piece_type == PieceType::Pawn {
+                if let Some(from) = to.shift(back)
+                    && raw.at(from).is_none()
+                    && !is_back_rank(from)
+                {
+                    push(
+                        &raw,
+                        &mut unmoves,
+                        Move::Standard { piece, from, to },
+                        None,
+                        None,
+                    );
+                }
+
+                let origins = get_pawn_attacks(mover.neg(), to).and(empty);
+                for from in origins {
+                    if is_back_rank(from) {
+                        continue;
+                    }
+                    let m = Move::Standard { piece, from, to };
+                    for captured in captures.iter().flatten() {
+                        push(&raw, &mut unmoves, m, Some(*captured), None);
+                    }
+                    if is_en_passant_target(&raw, mover, to) {
+                        let captured = Piece::from_side_and_type(mover.neg(), PieceType::Pawn);
+                        let m = Move::EnPassant { from, to };
+                        push(&raw, &mut unmoves, m, Some(captured), Some(to));
+                    }
+                }
+                continue;
+            }
+
+            if to.rank().as_u8() == last_rank.as_u8() && piece_type != PieceType::King {
+                if let Some(from) = to.shift(back)
+                    && raw.at(from).is_none()
+                {
+                    let m = Move::Promotion {
+                        from,
+                        to,
+                        promotion: piece,
+                    };
+                    push(&raw, &mut unmoves, m, None, None);
+                }
+                for from in get_pawn_attacks(mover.neg(), to).and(empty) {
+                    let m = Move::Promotion {
+                        from,
+                        to,
+                        promotion: piece,
+                    };
+                    for captured in captures.iter().flatten() {
+                        push(&raw, &mut unmoves, m, Some(*captured), None);
+                    }
+                }
+            }
+
+            let origins = get_piece_attacks(piece, to, occupied).and(empty);
+            for from in origins {
+                let m = Move::Standard { piece, from, to };
+                push(&raw, &mut unmoves, m, None, None);
+                for captured in captures.iter().flatten() {
+                    push(&raw, &mut unmoves, m, Some(*captured), None);
+                }
+            }
+
+            if piece_type == PieceType::King {
+                for castling in [Castling::KingSide, Castling::QueenSide] {
+                    if let Some(m) = uncastling(&raw, mover, castling, to) {
+                        push(&raw, &mut unmoves, m, None, None);
+                    }
+                }
+            }
+        }
+
+        unmoves.retain(|um| is_legal_predecessor(&raw, um));
+        with_en_passant(&raw, unmoves)
+    }
+}
+
+const fn is_back_rank(sq: Square) -> bool {
+    matches!(sq.rank(), Rank::First | Rank::Eighth)
+}
+
+/// Pieces of the side that could have been captured on the square
+const fn uncaptures(side: Side, sq: Square) -> [Option<Piece>; 5] {
+    let mut pieces = [None; 5];
+    let mut i = 0;
+    while i < UNCAPTURES.len() {
+        let piece_type = UNCAPTURES[i];
+        if !matches!(piece_type, PieceType::Pawn) || !is_back_rank(sq) {
+            pieces[i] = Some(Piece::from_side_and_type(side, piece_type));
+        }
+        i += 1;
+    }
+    pieces
+}
+
+/// Whether the pawn now standing on the square could have captured en passant
+///
+/// The captured pawn stood behind the square and came from the square in front of it
+const fn is_en_passant_target(raw: &ChessboardRaw, mover: Side, to: Square) -> bool {
+    let (rank, forward, back) = match mover {
+        Side::White => (Rank::Sixth, Directions::Up, Directions::Down),
+        Side::Black => (Rank::Third, Directions::Down, Directions::Up),
+    };
+    if to.rank().as_u8() != rank.as_u8() {
+        return false;
+    }
+    match (to.shift(forward), to.shift(back)) {
+        (Some(start), Some(captured)) => raw.at(start).is_none() && raw.at(captured).is_none(),
+        _ => false,
+    }
+}
+
+/// The unmoves and their copies whose predecessor has the en passant square
+/// of a double push the side to move could have just made
+///
+/// The double push sets the square whether or not a pawn can capture
+fn with_en_passant(raw: &ChessboardRaw, unmoves: Vec<UnMove>) -> Vec<UnMove> {
+    let pusher = raw.side;
+    let (rank, back) = match pusher {
+        Side::White => (Rank::Fourth, Directions::Down),
+        Side::Black => (Rank::Fifth, Directions::Up),
+    };
+    let pawn = Piece::from_side_and_type(pusher, PieceType::Pawn);
+    let king = Piece::from_side_and_type(pusher.neg(), PieceType::King);
+
+    let mut all = Vec::with_capacity(unmoves.len());
+    for um in unmoves {
+        all.push(um);
+        if um.en_passant.is_some() {
+            continue;
+        }
+        let mut parent = *raw;
+        parent.unmake_move(um);
+        for pushed in parent.get_pieces(pawn).and(rank.bitboard()) {
+            let Some(ep) = pushed.shift(back) else {
+                continue;
+            };
+            let Some(start) = ep.shift(back) else {
+                continue;
+            };
+            if parent.at(ep).is_some() || parent.at(start).is_some() {
+                continue;
+            }
+            // The king of the opponent was not in check before the push
+            let mut before = parent;
+            before.move_piece_raw(pushed, start, pawn);
+            if let Some(king) = before.get_pieces(king).first_square()
+                && is_attacked(&before, king, pusher)
+            {
+                continue;
+            }
+            all.push(UnMove {
+                en_passant: Some(ep),
+                ..um
+            });
+        }
+    }
+    all
+}
+
+/// Castling that left the king on the square
+const fn uncastling(
+    raw: &ChessboardRaw,
+    side: Side,
+    castling: Castling,
+    king: Square,
+) -> Option<Move> {
+    let (from, to, between) = match (side, castling) {
+        (Side::White, Castling::KingSide) => (Square::E1, Square::G1, None),
+        (Side::White, Castling::QueenSide) => (Square::E1, Square::C1, Some(Square::B1)),
+        (Side::Black, Castling::KingSide) => (Square::E8, Square::G8, None),
+        (Side::Black, Castling::QueenSide) => (Square::E8, Square::C8, Some(Square::B8)),
+    };
+    if king.as_u8() != to.as_u8() {
+        return None;
+    }
+
+    let (rook_start, rook_end) = castling.rook_squares(side);
+    let rook = Piece::from_side_and_type(side, PieceType::Rook);
+    if !raw.get_pieces(rook).overlaps(rook_end.bitboard())
+        || raw.at(from).is_some()
+        || raw.at(rook_start).is_some()
+    {
+        return None;
+    }
+    if let Some(between) = between
+        && raw.at(between).is_some()
+    {
+        return None;
+    }
+    Some(Move::Castling { castling, from, to })
+}
+
+/// Add the unmove with every castling rights the predecessor could have had
+fn push(
+    raw: &ChessboardRaw,
+    unmoves: &mut Vec<UnMove>,
+    m: Move,
+    captured: Option<Piece>,
+    en_passant: Option<Square>,
+) {
+    let mover = raw.side.neg();
+    let (cleared, castled) = match m {
+        Move::Standard { piece, .. } if matches!(piece.piece_type(), PieceType::King) => {
+            ([true, true], None)
+        }
+        Move::Standard { piece, from, .. } if matches!(piece.piece_type(), PieceType::Rook) => {
+            let king_side = Castling::KingSide.rook_squares(mover).0;
+            let queen_side = Castling::QueenSide.rook_squares(mover).0;
+            (
+                [
+                    from.as_u8() == king_side.as_u8(),
+                    from.as_u8() == queen_side.as_u8(),
+                ],
+                None,
+            )
+        }
+        Move::Castling { castling, .. } => ([true, true], Some(castling)),
+        _ => ([false, false], None),
+    };
+
+    let to = match m {
+        Move::Standard { to, .. }
+        | Move::Promotion { to, .. }
+        | Move::EnPassant { to, .. }
+        | Move::Castling { to, .. } => Some(to),
+        Move::Put { .. } | Move::Remove { .. } | Move::Null => None,
+    };
+    let king_start = match mover {
+        Side::White => Square::E1,
+        Side::Black => Square::E8,
+    };
+
+    let sides = [Castling::KingSide, Castling::QueenSide];
+    let opponent = mover.neg();
+    for castling in sides {
+        // A capture on the rook's starting square would have cleared the right
+        let rook_start = castling.rook_squares(opponent).0;
+        if raw.castling.is_set(castling, opponent)
+            && captured.is_some()
+            && to.is_some_and(|to| to == rook_start)
+        {
+            return;
+        }
+    }
+    for (castling, cleared) in sides.into_iter().zip(cleared) {
+        if !raw.castling.is_set(castling, mover) {
+            continue;
+        }
+        // The move clears the right and nothing could have come to the squares of
+        // the king and the rook that never moved
+        let rook_start = castling.rook_squares(mover).0;
+        let arrived = to.is_some_and(|to| to == king_start || to == rook_start);
+        if cleared || arrived {
+            return;
+        }
+    }
+
+    let mut um = UnMove {
+        m,
+        captured,
+        castling: raw.castling,
+        en_passant,
+    };
+    if let Some(castling) = castled {
+        um.castling.set(castling, mover, true);
+    }
+
+    let mut parent = *raw;
+    parent.unmake_move(um);
+    let first = unmoves.len();
+    unmoves.push(um);
+    for (castling, cleared) in sides.into_iter().zip(cleared) {
+        if !cleared || castled == Some(castling) || !can_castle(&parent, mover, castling) {
+            continue;
+        }
+        for i in first..unmoves.len() {
+            let mut restored = unmoves[i];
+            restored.castling.set(castling, mover, true);
+            unmoves.push(restored);
+        }
+    }
+
+    let rook = Piece::from_side_and_type(opponent, PieceType::Rook);
+    if captured.is_none_or(|captured| captured != rook) {
+        return;
+    }
+    for castling in sides {
+        let rook_start = castling.rook_squares(opponent).0;
+        if to.is_none_or(|to| to != rook_start) || !can_castle(&parent, opponent, castling) {
+            continue;
+        }
+        for i in first..unmoves.len() {
+            let mut restored = unmoves[i];
+            restored.castling.set(castling, opponent, true);
+            unmoves.push(restored);
+        }
+    }
+}
+
+/// Whether the king and the rook stand on their starting squares
+const fn can_castle(raw: &ChessboardRaw, side: Side, castling: Castling) -> bool {
+    let king_start = match side {
+        Side::White => Square::E1,
+        Side::Black => Square::E8,
+    };
+    let king = Piece::from_side_and_type(side, PieceType::King);
+    let rook = Piece::from_side_and_type(side, PieceType::Rook);
+    raw.get_pieces(king).overlaps(king_start.bitboard())
+        && raw
+            .get_pieces(rook)
+            .overlaps(castling.rook_squares(side).0.bitboard())
+}
+
+/// Whether the square is attacked by the pieces of the side
+const fn is_attacked(raw: &ChessboardRaw, sq: Square, by: Side) -> bool {
+    let occupied = raw.colors[0].or(raw.colors[1]);
+    let mut pieces = raw.colors[by.as_u8() as usize];
+    while let Some(from) = pieces.next_const() {
+        // SAFETY: the square is occupied
+        let piece = unsafe { raw.at(from).unwrap_unchecked() };
+        if get_piece_attacks(piece, from, occupied).overlaps(sq.bitboard()) {
+            return true;
+        }
+    }
+    false
+}
+
+/// The side to move in the predecessor can't capture the king and castling
+/// doesn't pass through attacked squares
+fn is_legal_predecessor(raw: &ChessboardRaw, um: &UnMove) -> bool {
+    let mut parent = *raw;
+    parent.unmake_move(*um);
+    let mover = parent.side;
+
+    let king = Piece::from_side_and_type(mover.neg(), PieceType::King);
+    let Some(king) = parent.get_pieces(king).first_square() else {
+        return false;
+    };
+    if is_attacked(&parent, king, mover) {
+        return false;
+    }
+
+    if let Move::Castling { castling, from, .. } = um.m {
+        let path = match castling {
+            Castling::KingSide => [Directions::Right, Directions::Right],
+            Castling::QueenSide => [Directions::Left, Directions::Left],
+        };
+        let mut sq = Some(from);
+        let mut steps = path.into_iter();
+        while let Some(current) = sq {
+            if is_attacked(&parent, current, mover.neg()) {
+                return false;
+            }
+            sq = steps.next().and_then(|dir| current.shift(dir));
+        }
+    }
+    true
+}
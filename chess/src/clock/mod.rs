@@ -0,0 +1,344 @@
+//! Chess clocks
+//!
+//! A [`TimeControl`] is a list of periods, each adding its time to the clock
+//! and lasting a number of moves or the rest of the game. The [`Clock`] reads
+//! the time from a [`TimeSource`], so it can be moved by hand in tests
+
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{ChessError, Side};
+
+/// Monotonic time read by the clock
+pub trait TimeSource: fmt::Debug + Send + Sync {
+    /// Time elapsed since a fixed point
+    fn now(&self) -> Duration;
+}
+
+impl<T: TimeSource + ?Sized> TimeSource for Arc<T> {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// Time of the system
+#[derive(Debug, Clone, Copy)]
+pub struct SystemTime {
+    start: Instant,
+}
+
+impl SystemTime {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time that only moves when advanced, clones share it
+#[derive(Debug, Clone, Default)]
+pub struct ManualTime(Arc<AtomicU64>);
+
+impl ManualTime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Time given back for each move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bonus {
+    #[default]
+    None,
+    /// Fischer increment, added after the move
+    Increment(Duration),
+    /// Time used for the move is given back, up to the delay
+    Bronstein(Duration),
+    /// Simple (US) delay, the clock waits for the delay before running
+    Delay(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    /// Moves to make in the period, `None` for the rest of the game
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+impl Period {
+    /// Period for the rest of the game
+    pub const fn new(time: Duration, bonus: Bonus) -> Self {
+        Self {
+            moves: None,
+            time,
+            bonus,
+        }
+    }
+
+    pub const fn with_moves(moves: u32, time: Duration, bonus: Bonus) -> Self {
+        Self {
+            moves: Some(moves),
+            time,
+            bonus,
+        }
+    }
+}
+
+/// Periods played one after another
+///
+/// The last period repeats when it lasts a number of moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    periods: Vec<Period>,
+}
+
+impl TimeControl {
+    /// `None` without periods
+    pub fn new(periods: Vec<Period>) -> Option<Self> {
+        (!periods.is_empty()).then_some(Self { periods })
+    }
+
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::single(time, Bonus::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::single(time, Bonus::Increment(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        Self::single(time, Bonus::Bronstein(delay))
+    }
+
+    pub fn delay(time: Duration, delay: Duration) -> Self {
+        Self::single(time, Bonus::Delay(delay))
+    }
+
+    fn single(time: Duration, bonus: Bonus) -> Self {
+        Self {
+            periods: vec![Period::new(time, bonus)],
+        }
+    }
+
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+}
+
+/// PGN `TimeControl` tag, periods like `40/5400+30` or `10+0.1` in seconds
+/// separated by `:`
+impl FromStr for TimeControl {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seconds = |s: &str| {
+            s.parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or(ChessError::InvalidTimeControl)
+        };
+        let periods = s
+            .split(':')
+            .map(|period| {
+                let (moves, rest) = match period.split_once('/') {
+                    Some((moves, rest)) => (
+                        Some(moves.parse().map_err(|_| ChessError::InvalidTimeControl)?),
+                        rest,
+                    ),
+                    None => (None, period),
+                };
+                let (time, bonus) = match rest.split_once('+') {
+                    Some((time, increment)) => (time, Bonus::Increment(seconds(increment)?)),
+                    None => (rest, Bonus::None),
+                };
+                Ok(Period {
+                    moves,
+                    time: seconds(time)?,
+                    bonus,
+                })
+            })
+            .collect::<Result<_, ChessError>>()?;
+        Self::new(periods).ok_or(ChessError::InvalidTimeControl)
+    }
+}
+
+/// Written as the PGN `TimeControl` tag, delays are left out
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, period) in self.periods.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = period.moves {
+                write!(f, "{moves}/")?;
+            }
+            write!(f, "{}", period.time.as_secs_f64())?;
+            if let Bonus::Increment(increment) = period.bonus {
+                write!(f, "+{}", increment.as_secs_f64())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Clock of both players
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    source: Arc<dyn TimeSource>,
+    remaining: [Duration; 2],
+    /// Current period and moves made in it
+    period: [(usize, u32); 2],
+    /// Side whose time runs, and since when
+    running: Option<(Side, Duration)>,
+    flagged: Option<Side>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, source: impl TimeSource + 'static) -> Self {
+        let time = control.periods[0].time;
+        Self {
+            control,
+            source: Arc::new(source),
+            remaining: [time; 2],
+            period: [(0, 0); 2],
+            running: None,
+            flagged: None,
+        }
+    }
+
+    pub const fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// Start the time of the side
+    pub fn start(&mut self, side: Side) {
+        if self.flagged.is_none() {
+            self.running = Some((side, self.source.now()));
+        }
+    }
+
+    /// Stop the clock, charging the running side for the time used
+    pub fn stop(&mut self) {
+        if let Some((side, elapsed)) = self.take_running() {
+            let remaining = self.remaining[side as usize];
+            self.remaining[side as usize] = remaining.saturating_sub(self.charged(side, elapsed));
+        }
+    }
+
+    pub const fn running(&self) -> Option<Side> {
+        match self.running {
+            Some((side, _)) => Some(side),
+            None => None,
+        }
+    }
+
+    /// Time left for the side, counting the move in progress
+    pub fn remaining(&self, side: Side) -> Duration {
+        let remaining = self.remaining[side as usize];
+        match self.running {
+            Some((running, since)) if running == side => {
+                let elapsed = self.source.now().saturating_sub(since);
+                remaining.saturating_sub(self.charged(side, elapsed))
+            }
+            _ => remaining,
+        }
+    }
+
+    /// Period the side is playing
+    pub fn period(&self, side: Side) -> &Period {
+        &self.control.periods[self.period[side as usize].0]
+    }
+
+    /// Moves the side has to make before the time of the next period is added
+    pub fn moves_to_go(&self, side: Side) -> Option<u32> {
+        let (_, moves) = self.period[side as usize];
+        self.period(side).moves.map(|period| period - moves)
+    }
+
+    /// Side out of time
+    pub fn flagged(&self) -> Option<Side> {
+        self.flagged.or_else(|| {
+            self.running()
+                .filter(|&side| self.remaining(side).is_zero())
+        })
+    }
+
+    /// End the move of the running side and start the time of the opponent
+    ///
+    /// Returns the time used for the move, `None` when the clock was stopped
+    /// or the flag fell before the move was made
+    pub fn press(&mut self) -> Option<Duration> {
+        let (side, elapsed) = self.take_running()?;
+        let charged = self.charged(side, elapsed);
+        let remaining = &mut self.remaining[side as usize];
+        if charged >= *remaining {
+            *remaining = Duration::ZERO;
+            self.flagged = Some(side);
+            return None;
+        }
+        *remaining -= charged;
+
+        let (index, moves) = &mut self.period[side as usize];
+        let period = self.control.periods[*index];
+        *remaining += match period.bonus {
+            Bonus::Increment(increment) => increment,
+            Bonus::Bronstein(delay) => elapsed.min(delay),
+            Bonus::None | Bonus::Delay(_) => Duration::ZERO,
+        };
+        *moves += 1;
+        if period.moves == Some(*moves) {
+            *index = (*index + 1).min(self.control.periods.len() - 1);
+            *moves = 0;
+            *remaining += self.control.periods[*index].time;
+        }
+
+        self.start(side.neg());
+        Some(elapsed)
+    }
+
+    /// Stop the time, returning the running side and the time of its move
+    fn take_running(&mut self) -> Option<(Side, Duration)> {
+        let (side, since) = self.running.take()?;
+        Some((side, self.source.now().saturating_sub(since)))
+    }
+
+    /// Time taken from the clock for the time used
+    fn charged(&self, side: Side, elapsed: Duration) -> Duration {
+        let (index, _) = self.period[side as usize];
+        match self.control.periods[index].bonus {
+            Bonus::Delay(delay) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Training data from self-play
+//!
+//! Games start from random or book openings and are played by a search with a
+//! fixed depth or node count. Quiet positions are recorded with the score of
+//! the search and the result of the game, each thread writing its own shard
+//! as text lines or as [`Packed`] records
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{
+    ChessError, Chessboard, ChessboardRaw, DrawType, GameResult, Move, START_POS, Side, WinType,
+    book::{Book, polyglot_key},
+    eval::Evaluator,
+    packed::{Packed, PackedWriter},
+    search::{Limit, Search},
+};
+
+/// How games start
+#[derive(Debug, Clone)]
+pub enum Openings {
+    /// Random legal moves from the starting position
+    Random { plies: usize },
+    /// Weighted book moves while the book knows the position, then random moves
+    Book {
+        book: Arc<Book>,
+        random_plies: usize,
+    },
+}
+
+/// How shards are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// `shard-N.txt` with a [`Record`] per line
+    #[default]
+    Text,
+    /// `shard-N.bin` with [`Packed`] records
+    Packed,
+}
+
+impl Format {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Packed => "bin",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DatagenConfig {
+    pub limit: Limit,
+    pub openings: Openings,
+    pub format: Format,
+    /// Games over all threads
+    pub games: usize,
+    pub threads: usize,
+    /// Longer games are adjudicated as draws
+    pub max_plies: usize,
+    /// Seed of the first game, the others add their index
+    pub seed: u64,
+}
+
+impl Default for DatagenConfig {
+    fn default() -> Self {
+        Self {
+            limit: Limit::Depth(6),
+            openings: Openings::Random { plies: 8 },
+            format: Format::Text,
+            games: 100,
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+            max_plies: 400,
+            seed: 0,
+        }
+    }
+}
+
+/// Position seen during a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub cb: Chessboard,
+    /// Score of the search in centipawns for white
+    pub score: i32,
+    /// How the game ended
+    ///
+    /// Games reaching the ply limit are drawn by [`DrawType::Adjudication`]
+    pub result: GameResult,
+}
+
+/// `FEN | score | result`, with the result as `1-0`, `0-1` or `1/2-1/2`
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = match self.result {
+            GameResult::None => "1/2-1/2",
+            result => result.pgn_result(),
+        };
+        write!(f, "{} | {} | {result}", self.cb.get_fen(), self.score)
+    }
+}
+
+/// The packed position, the score as i16 and a byte for the result
+impl Packed for Record {
+    const SIZE: usize = ChessboardRaw::PACKED_SIZE + 3;
+
+    fn pack(&self, out: &mut Vec<u8>) {
+        self.cb.pack(out);
+        let score = self.score.clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+        out.extend_from_slice(&score.to_le_bytes());
+        out.push(result_to_u8(self.result));
+    }
+
+    fn unpack(bytes: &[u8]) -> Result<Self, ChessError> {
+        let (board, rest) = bytes
+            .split_at_checked(ChessboardRaw::PACKED_SIZE)
+            .ok_or(ChessError::InvalidPackedBoard)?;
+        let &[s0, s1, result] = rest else {
+            return Err(ChessError::InvalidPackedBoard);
+        };
+        Ok(Self {
+            cb: Chessboard::unpack(board)?,
+            score: i16::from_le_bytes([s0, s1]).into(),
+            result: result_from_u8(result).ok_or(ChessError::InvalidPackedBoard)?,
+        })
+    }
+}
+
+/// Winner in the high nibble, the reason in the low one
+const fn result_to_u8(result: GameResult) -> u8 {
+    match result {
+        GameResult::None => 0,
+        GameResult::WhiteWin(win_type) => 0x10 | win_type as u8,
+        GameResult::BlackWin(win_type) => 0x20 | win_type as u8,
+        GameResult::Draw(draw_type) => 0x30 | draw_type as u8,
+    }
+}
+
+const fn result_from_u8(byte: u8) -> Option<GameResult> {
+    const WINS: [WinType; 8] = [
+        WinType::Checkmate,
+        WinType::Surrounded,
+        WinType::Resign,
+        WinType::Timeout,
+        WinType::Abandonment,
+        WinType::Forfeit,
+        WinType::Adjudication,
+        WinType::Variant,
+    ];
+    const DRAWS: [DrawType; 10] = [
+        DrawType::Stalemate,
+        DrawType::Agreement,
+        DrawType::Repetition,
+        DrawType::FivefoldRepetition,
+        DrawType::FiftyMoveRule,
+        DrawType::SeventyFiveMoveRule,
+        DrawType::InsufficientMaterial,
+        DrawType::DeadPosition,
+        DrawType::TimeoutVsInsufficientMaterial,
+        DrawType::Adjudication,
+    ];
+    let reason = (byte & 15) as usize;
+    match byte >> 4 {
+        0 if reason == 0 => Some(GameResult::None),
+        1 if reason < WINS.len() => Some(GameResult::WhiteWin(WINS[reason])),
+        2 if reason < WINS.len() => Some(GameResult::BlackWin(WINS[reason])),
+        3 if reason < DRAWS.len() => Some(GameResult::Draw(DRAWS[reason])),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatagenStats {
+    pub games: usize,
+    pub positions: usize,
+}
+
+/// Play the games of the config and write a `shard-N` file per thread to the
+/// directory, with the extension of the [`Format`]
+///
+/// Each thread searches with its own evaluator. Thread `N` plays the games
+/// `N`, `N + threads` and so on, each seeded with the seed plus its index, so
+/// the games do not depend on the scheduling
+pub fn generate<E, F>(
+    config: &DatagenConfig,
+    dir: impl AsRef<Path>,
+    evaluator: F,
+) -> io::Result<DatagenStats>
+where
+    E: Evaluator,
+    F: Fn() -> E + Sync,
+{
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let threads = config.threads.max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|shard| {
+                let evaluator = &evaluator;
+                scope.spawn(move || -> io::Result<DatagenStats> {
+                    let name = format!("shard-{shard}.{}", config.format.extension());
+                    let file = File::create(dir.join(name))?;
+                    let mut out = Shard::new(config.format, file);
+                    let mut search = Search::new(evaluator());
+                    let mut stats = DatagenStats::default();
+                    for game in (shard..config.games).step_by(threads) {
+                        let seed = config.seed.wrapping_add(game as u64);
+                        let mut rng = fastrand::Rng::with_seed(seed);
+                        let records = play_game(config, &mut search, &mut rng);
+                        for record in &records {
+                            out.write(record)?;
+                        }
+                        stats.games += 1;
+                        stats.positions += records.len();
+                    }
+                    out.flush()?;
+                    Ok(stats)
+                })
+            })
+            .collect();
+
+        let mut total = DatagenStats::default();
+        for handle in handles {
+            let stats = handle
+                .join()
+                .map_err(|_| io::Error::other("datagen thread panicked"))??;
+            total.games += stats.games;
+            total.positions += stats.positions;
+        }
+        Ok(total)
+    })
+}
+
+enum Shard {
+    Text(BufWriter<File>),
+    Packed(PackedWriter<File>),
+}
+
+impl Shard {
+    fn new(format: Format, file: File) -> Self {
+        match format {
+            Format::Text => Self::Text(BufWriter::new(file)),
+            Format::Packed => Self::Packed(PackedWriter::new(file)),
+        }
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        match self {
+            Self::Text(out) => writeln!(out, "{record}"),
+            Self::Packed(out) => out.write(record),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Text(out) => out.flush(),
+            Self::Packed(out) => out.flush(),
+        }
+    }
+}
+
+/// Play one game and return its quiet positions
+///
+/// Positions in check and those where the best move captures or promotes
+/// are left out
+pub fn play_game<E: Evaluator>(
+    config: &DatagenConfig,
+    search: &mut Search<E>,
+    rng: &mut fastrand::Rng,
+) -> Vec<Record> {
+    let mut cb = opening(&config.openings, rng);
+    let mut history = Vec::new();
+    let mut records = Vec::new();
+
+    let result = loop {
+        if !matches!(cb.result, GameResult::None) {
+            break cb.result;
+        }
+        let key = polyglot_key(&cb);
+        let repetitions = history.iter().filter(|&&k| k == key).count();
+        if repetitions >= 2 {
+            break GameResult::Draw(DrawType::Repetition);
+        }
+        if history.len() >= config.max_plies {
+            break GameResult::Draw(DrawType::Adjudication);
+        }
+
+        search.set_history(&history);
+        let found = search.search(&cb, config.limit);
+        let Some(m) = found.best_move else {
+            break GameResult::Draw(DrawType::Adjudication);
+        };
+        if cb.checkers.is_empty() && !is_tactical(&cb, m) {
+            let score = match cb.side() {
+                Side::White => found.score,
+                Side::Black => -found.score,
+            };
+            records.push(Record {
+                cb,
+                score,
+                result: GameResult::None,
+            });
+        }
+
+        history.push(key);
+        cb.make_move(m);
+    };
+
+    for record in &mut records {
+        record.result = result;
+    }
+    records
+}
+
+/// Starting position of a game, ongoing after the opening moves
+fn opening(openings: &Openings, rng: &mut fastrand::Rng) -> Chessboard {
+    loop {
+        let Ok(mut cb) = Chessboard::from_fen(START_POS) else {
+            unreachable!()
+        };
+        let mut random_plies = match openings {
+            Openings::Random { plies } => *plies,
+            Openings::Book { random_plies, .. } => *random_plies,
+        };
+
+        while matches!(cb.result, GameResult::None) {
+            let book_move = match openings {
+                Openings::Book { book, .. } => book_move(book, &cb, rng),
+                Openings::Random { .. } => None,
+            };
+            let m = match book_move {
+                Some(m) => m,
+                None if random_plies > 0 => {
+                    random_plies -= 1;
+                    let moves = cb.to_vec();
+                    moves[rng.usize(..moves.len())]
+                }
+                None => return cb,
+            };
+            cb.make_move(m);
+        }
+    }
+}
+
+fn book_move(book: &Book, cb: &Chessboard, rng: &mut fastrand::Rng) -> Option<Move> {
+    let total: u32 = book.moves(cb).iter().map(|&(_, w)| u32::from(w)).sum();
+    if total == 0 {
+        return None;
+    }
+    book.weighted_move(cb, rng.u32(..total))
+}
+
+const fn is_tactical(cb: &Chessboard, m: Move) -> bool {
+    match m {
+        Move::Standard { to, .. } => cb.at(to).is_some(),
+        Move::Promotion { .. } | Move::EnPassant { .. } => true,
+        Move::Castling { .. } | Move::Put { .. } | Move::Remove { .. } | Move::Null => false,
+    }
+}
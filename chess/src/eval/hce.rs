@@ -0,0 +1,202 @@
+use core::{fmt, str::FromStr};
+
+use crate::{ChessError, Chessboard, Piece, PieceType, Side, Square};
+
+use super::Evaluator;
+
+/// Game phase of the starting position, from the minor and major pieces
+pub(super) const MAX_PHASE: i32 = 24;
+
+/// Weights of the hand-crafted evaluation, indexed by [`PieceType`]
+///
+/// Middlegame and endgame scores are blended by the material left on the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub material_mg: [i32; PieceType::LEN],
+    pub material_eg: [i32; PieceType::LEN],
+    /// Piece-square tables from white's point of view, rank 8 first
+    pub psqt_mg: [[i32; 64]; PieceType::LEN],
+    pub psqt_eg: [[i32; 64]; PieceType::LEN],
+}
+
+impl Default for EvalParams {
+    // The weights file can be replaced by the output of the tune example and
+    // stands on its own
+    #[allow(clippy::use_self)]
+    fn default() -> Self {
+        include!("weights.rs")
+    }
+}
+
+impl EvalParams {
+    /// Number of weights
+    pub const LEN: usize = 2 * Self::PHASE_LEN;
+    /// Weights of a game phase, the material and then the tables
+    pub(super) const PHASE_LEN: usize = PieceType::LEN * 65;
+
+    /// All weights, the middlegame ones first
+    pub fn to_vec(&self) -> Vec<i32> {
+        let mut weights = Vec::with_capacity(Self::LEN);
+        for (material, psqt) in [
+            (&self.material_mg, &self.psqt_mg),
+            (&self.material_eg, &self.psqt_eg),
+        ] {
+            weights.extend(material);
+            weights.extend(psqt.iter().flatten());
+        }
+        weights
+    }
+
+    /// Inverse of [`EvalParams::to_vec`]
+    pub fn from_slice(weights: &[i32]) -> Option<Self> {
+        if weights.len() != Self::LEN {
+            return None;
+        }
+        let mut params = Self {
+            material_mg: [0; PieceType::LEN],
+            material_eg: [0; PieceType::LEN],
+            psqt_mg: [[0; 64]; PieceType::LEN],
+            psqt_eg: [[0; 64]; PieceType::LEN],
+        };
+        let (mg, eg) = weights.split_at(Self::PHASE_LEN);
+        for (weights, material, psqt) in [
+            (mg, &mut params.material_mg, &mut params.psqt_mg),
+            (eg, &mut params.material_eg, &mut params.psqt_eg),
+        ] {
+            let (head, tail) = weights.split_at(PieceType::LEN);
+            material.copy_from_slice(head);
+            for (table, weights) in psqt.iter_mut().zip(tail.chunks_exact(64)) {
+                table.copy_from_slice(weights);
+            }
+        }
+        Some(params)
+    }
+}
+
+/// Rust expression in the layout of the weights file
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [&str; PieceType::LEN] = ["Pawn", "Rook", "Knight", "Bishop", "Queen", "King"];
+        let list = |values: &[i32]| {
+            values
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(f, "EvalParams {{")?;
+        writeln!(f, "    material_mg: [{}],", list(&self.material_mg))?;
+        writeln!(f, "    material_eg: [{}],", list(&self.material_eg))?;
+        for (name, psqt) in [("psqt_mg", &self.psqt_mg), ("psqt_eg", &self.psqt_eg)] {
+            writeln!(f, "    {name}: [")?;
+            for (piece, table) in NAMES.iter().zip(psqt) {
+                writeln!(f, "        // {piece}")?;
+                writeln!(f, "        [")?;
+                for rank in table.chunks_exact(8) {
+                    write!(f, "           ")?;
+                    for value in rank {
+                        write!(f, "{value:5},")?;
+                    }
+                    writeln!(f)?;
+                }
+                writeln!(f, "        ],")?;
+            }
+            writeln!(f, "    ],")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// Read the weights file, the numbers are taken in order and comments skipped
+impl FromStr for EvalParams {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Vec::with_capacity(Self::LEN);
+        for line in s.lines() {
+            let code = line.split("//").next().unwrap_or_default();
+            for token in code.split(|c: char| c != '-' && !c.is_ascii_digit()) {
+                if token.is_empty() {
+                    continue;
+                }
+                let weight = token.parse().map_err(|_| ChessError::InvalidEvalParams)?;
+                weights.push(weight);
+            }
+        }
+        if weights.len() != Self::LEN {
+            return Err(ChessError::InvalidEvalParams);
+        }
+        // The file lists both material rows before the tables
+        let (material, psqt) = weights.split_at(2 * PieceType::LEN);
+        let (material_mg, material_eg) = material.split_at(PieceType::LEN);
+        let (psqt_mg, psqt_eg) = psqt.split_at(psqt.len() / 2);
+        let weights = [material_mg, psqt_mg, material_eg, psqt_eg].concat();
+        Self::from_slice(&weights).ok_or(ChessError::InvalidEvalParams)
+    }
+}
+
+/// Tapered material and piece-square evaluation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HandCrafted {
+    pub params: EvalParams,
+}
+
+impl HandCrafted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn with_params(params: EvalParams) -> Self {
+        Self { params }
+    }
+}
+
+impl Evaluator for HandCrafted {
+    fn evaluate(&self, cb: &Chessboard) -> i32 {
+        let mut mg = 0;
+        let mut eg = 0;
+        let mut phase = 0;
+        for side in [Side::White, Side::Black] {
+            let sign = match side {
+                Side::White => 1,
+                Side::Black => -1,
+            };
+            for piece_type in PieceType::ALL {
+                let kind = piece_type as usize;
+                let pieces = cb.get_pieces(Piece::from_side_and_type(side, piece_type));
+                phase += phase_weight(piece_type) * pieces.popcnt() as i32;
+                for sq in pieces {
+                    let index = table_index(side, sq);
+                    mg += sign * (self.params.material_mg[kind] + self.params.psqt_mg[kind][index]);
+                    eg += sign * (self.params.material_eg[kind] + self.params.psqt_eg[kind][index]);
+                }
+            }
+        }
+
+        let phase = phase.min(MAX_PHASE);
+        let score = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE;
+        match cb.side() {
+            Side::White => score,
+            Side::Black => -score,
+        }
+    }
+}
+
+/// Contribution of the piece to the game phase
+pub(super) const fn phase_weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Knight | PieceType::Bishop => 1,
+        PieceType::Rook => 2,
+        PieceType::Queen => 4,
+        PieceType::Pawn | PieceType::King => 0,
+    }
+}
+
+/// Index of the square in the tables, which are written rank 8 first
+pub(super) const fn table_index(side: Side, sq: Square) -> usize {
+    match side {
+        Side::White => (sq.as_u8() ^ 56) as usize,
+        Side::Black => sq.as_u8() as usize,
+    }
+}
@@ -0,0 +1,32 @@
+//! Static evaluation
+//!
+//! The search talks to an [`Evaluator`], so the hand-crafted evaluation and
+//! the network can be swapped without touching it
+
+mod hce;
+pub mod nnue;
+pub mod tune;
+
+pub use hce::{EvalParams, HandCrafted};
+pub use nnue::{Network, Nnue};
+
+use crate::Chessboard;
+
+/// Evaluation used by the search
+///
+/// Evaluators with incremental state follow the search through
+/// [`Evaluator::make_move`] and [`Evaluator::unmake_move`], the others only
+/// implement [`Evaluator::evaluate`]
+pub trait Evaluator {
+    /// Score of the position in centipawns for the side to move
+    fn evaluate(&self, cb: &Chessboard) -> i32;
+
+    /// Start over from the position
+    fn reset(&mut self, _cb: &Chessboard) {}
+
+    /// A move led from `before` to `after`
+    fn make_move(&mut self, _before: &Chessboard, _after: &Chessboard) {}
+
+    /// Take back the last move
+    fn unmake_move(&mut self) {}
+}
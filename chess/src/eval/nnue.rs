@@ -0,0 +1,356 @@
+//! Efficiently updatable neural network
+//!
+//! The network is `(inputs -> hidden) x 2 -> 1`. Both sides see the board from
+//! their own point of view through the same feature transformer, and the two
+//! accumulators, side to move first, are clipped and fed to one output neuron.
+//! Accumulators only change by the rows of the pieces that moved, so the
+//! search updates them with the moves instead of recomputing them
+//!
+//! Network files are little-endian:
+//!
+//! | Field | Type |
+//! |-|-|
+//! | magic `CLNN` | 4 bytes |
+//! | version, 1 | `u32` |
+//! | feature set, 0 for HalfKP and 1 for HalfKA | `u32` |
+//! | hidden size | `u32` |
+//! | output scale | `i32` |
+//! | feature weights, `hidden` for each input | `i16` |
+//! | feature biases | `hidden` × `i16` |
+//! | output weights, side to move first | `2 * hidden` × `i8` |
+//! | output bias | `i32` |
+
+use std::{path::Path, sync::Arc};
+
+use crate::{ChessError, Chessboard, Piece, PieceType, Side, Square};
+
+use super::Evaluator;
+
+const MAGIC: [u8; 4] = *b"CLNN";
+const VERSION: u32 = 1;
+
+/// Quantization of the accumulators, clipped to `0..=QA`
+pub const QA: i32 = 255;
+/// Quantization of the output weights
+pub const QB: i32 = 64;
+
+/// Inputs of a perspective
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeatureSet {
+    /// Own king square, piece other than a king and its square
+    HalfKP,
+    /// Own king square, any piece and its square
+    HalfKA,
+}
+
+impl FeatureSet {
+    /// Number of inputs of a perspective
+    pub const fn inputs(self) -> usize {
+        64 * self.pieces() * 64
+    }
+
+    const fn pieces(self) -> usize {
+        match self {
+            Self::HalfKP => 10,
+            Self::HalfKA => 12,
+        }
+    }
+
+    /// Input of the piece on the square for the perspective with its king on `king`
+    ///
+    /// Black sees the board flipped, so both perspectives share the weights
+    pub const fn index(
+        self,
+        perspective: Side,
+        king: Square,
+        piece: Piece,
+        sq: Square,
+    ) -> Option<usize> {
+        let kinds = self.pieces() / 2;
+        let kind = piece.piece_type() as usize;
+        if kind >= kinds {
+            return None;
+        }
+        let piece = if piece.side().as_u8() == perspective.as_u8() {
+            kind
+        } else {
+            kind + kinds
+        };
+        let flip = match perspective {
+            Side::White => 0,
+            Side::Black => 56,
+        };
+        let king = (king.as_u8() ^ flip) as usize;
+        let sq = (sq.as_u8() ^ flip) as usize;
+        Some((king * self.pieces() + piece) * 64 + sq)
+    }
+}
+
+/// Quantized network weights
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    features: FeatureSet,
+    hidden: usize,
+    /// Centipawns of an output of `QA * QB`
+    scale: i32,
+    feature_weights: Vec<i16>,
+    feature_bias: Vec<i16>,
+    output_weights: Vec<i8>,
+    output_bias: i32,
+}
+
+impl Network {
+    /// Load the network file
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid network"))
+    }
+
+    /// Parse the contents of a network file
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ChessError> {
+        let bytes = &mut bytes;
+        if take::<4>(bytes)? != MAGIC || u32::from_le_bytes(take(bytes)?) != VERSION {
+            return Err(ChessError::InvalidNetwork);
+        }
+        let features = match u32::from_le_bytes(take(bytes)?) {
+            0 => FeatureSet::HalfKP,
+            1 => FeatureSet::HalfKA,
+            _ => return Err(ChessError::InvalidNetwork),
+        };
+        let hidden = u32::from_le_bytes(take(bytes)?) as usize;
+        let scale = i32::from_le_bytes(take(bytes)?);
+
+        let weights = features
+            .inputs()
+            .checked_mul(hidden)
+            .ok_or(ChessError::InvalidNetwork)?;
+        let feature_weights = take_i16(bytes, weights)?;
+        let feature_bias = take_i16(bytes, hidden)?;
+        let (output_weights, rest) = bytes
+            .split_at_checked(2 * hidden)
+            .ok_or(ChessError::InvalidNetwork)?;
+        *bytes = rest;
+        let output_weights = output_weights.iter().map(|&b| b as i8).collect();
+        let output_bias = i32::from_le_bytes(take(bytes)?);
+
+        if hidden == 0 || !bytes.is_empty() {
+            return Err(ChessError::InvalidNetwork);
+        }
+        Ok(Self {
+            features,
+            hidden,
+            scale,
+            feature_weights,
+            feature_bias,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let features: u32 = match self.features {
+            FeatureSet::HalfKP => 0,
+            FeatureSet::HalfKA => 1,
+        };
+        let mut bytes = Vec::new();
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(features.to_le_bytes());
+        bytes.extend((self.hidden as u32).to_le_bytes());
+        bytes.extend(self.scale.to_le_bytes());
+        bytes.extend(self.feature_weights.iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend(self.feature_bias.iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend(self.output_weights.iter().map(|&w| w as u8));
+        bytes.extend(self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    pub const fn features(&self) -> FeatureSet {
+        self.features
+    }
+
+    /// Size of an accumulator
+    pub const fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    /// Score of the position for the side to move, computed from scratch
+    pub fn evaluate(&self, cb: &Chessboard) -> i32 {
+        self.output(&Accumulator::new(self, cb), cb.side())
+    }
+
+    fn row(&self, index: usize) -> &[i16] {
+        &self.feature_weights[index * self.hidden..][..self.hidden]
+    }
+
+    fn output(&self, acc: &Accumulator, side: Side) -> i32 {
+        let (ours, theirs) = self.output_weights.split_at(self.hidden);
+        let sum = activate(&acc.values[side as usize], ours)
+            + activate(&acc.values[side.neg() as usize], theirs)
+            + self.output_bias;
+        (i64::from(sum) * i64::from(self.scale) / i64::from(QA * QB)) as i32
+    }
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], ChessError> {
+    let (value, rest) = bytes
+        .split_first_chunk::<N>()
+        .ok_or(ChessError::InvalidNetwork)?;
+    *bytes = rest;
+    Ok(*value)
+}
+
+fn take_i16(bytes: &mut &[u8], len: usize) -> Result<Vec<i16>, ChessError> {
+    let size = len.checked_mul(2).ok_or(ChessError::InvalidNetwork)?;
+    let (values, rest) = bytes
+        .split_at_checked(size)
+        .ok_or(ChessError::InvalidNetwork)?;
+    *bytes = rest;
+    Ok(values
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect())
+}
+
+/// Clipped ReLU followed by the dot product with the output weights
+fn activate(values: &[i16], weights: &[i8]) -> i32 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(&v, &w)| i32::from(v.clamp(0, QA as i16)) * i32::from(w))
+        .sum()
+}
+
+fn add(values: &mut [i16], row: &[i16]) {
+    for (v, w) in values.iter_mut().zip(row) {
+        *v = v.wrapping_add(*w);
+    }
+}
+
+fn sub(values: &mut [i16], row: &[i16]) {
+    for (v, w) in values.iter_mut().zip(row) {
+        *v = v.wrapping_sub(*w);
+    }
+}
+
+const fn king_square(cb: &Chessboard, side: Side) -> Option<Square> {
+    cb.get_pieces(Piece::from_side_and_type(side, PieceType::King))
+        .first_square()
+}
+
+/// Hidden layer before the activation, for both perspectives
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Accumulator {
+    /// Indexed by [`Side`]
+    values: [Vec<i16>; 2],
+}
+
+impl Accumulator {
+    fn new(network: &Network, cb: &Chessboard) -> Self {
+        let mut acc = Self {
+            values: [Vec::new(), Vec::new()],
+        };
+        acc.refresh(network, cb, Side::White);
+        acc.refresh(network, cb, Side::Black);
+        acc
+    }
+
+    fn refresh(&mut self, network: &Network, cb: &Chessboard, perspective: Side) {
+        let values = &mut self.values[perspective as usize];
+        values.clone_from(&network.feature_bias);
+        let Some(king) = king_square(cb, perspective) else {
+            return;
+        };
+        let mut occupied = cb.occupied();
+        while let Some(sq) = occupied.next_const() {
+            // SAFETY: the square is occupied
+            let piece = unsafe { cb.at(sq).unwrap_unchecked() };
+            if let Some(index) = network.features.index(perspective, king, piece, sq) {
+                add(values, network.row(index));
+            }
+        }
+    }
+
+    /// Apply the pieces that changed between the boards, a perspective whose
+    /// king moved is computed again
+    fn update(&mut self, network: &Network, before: &Chessboard, after: &Chessboard) {
+        for perspective in [Side::White, Side::Black] {
+            let king = king_square(after, perspective);
+            let Some(king) = king.filter(|&k| Some(k) == king_square(before, perspective)) else {
+                self.refresh(network, after, perspective);
+                continue;
+            };
+
+            let values = &mut self.values[perspective as usize];
+            for side in [Side::White, Side::Black] {
+                for piece_type in PieceType::ALL {
+                    let piece = Piece::from_side_and_type(side, piece_type);
+                    let old = before.get_pieces(piece);
+                    let new = after.get_pieces(piece);
+                    for sq in old.and(new.neg()) {
+                        if let Some(index) = network.features.index(perspective, king, piece, sq) {
+                            sub(values, network.row(index));
+                        }
+                    }
+                    for sq in new.and(old.neg()) {
+                        if let Some(index) = network.features.index(perspective, king, piece, sq) {
+                            add(values, network.row(index));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Network evaluation with a stack of accumulators that follows the search
+#[derive(Debug, Clone)]
+pub struct Nnue {
+    network: Arc<Network>,
+    stack: Vec<Accumulator>,
+    depth: usize,
+}
+
+impl Nnue {
+    pub fn new(network: Arc<Network>, cb: &Chessboard) -> Self {
+        let acc = Accumulator::new(&network, cb);
+        Self {
+            network,
+            stack: vec![acc],
+            depth: 0,
+        }
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+}
+
+impl Evaluator for Nnue {
+    fn evaluate(&self, cb: &Chessboard) -> i32 {
+        self.network.output(&self.stack[self.depth], cb.side())
+    }
+
+    fn reset(&mut self, cb: &Chessboard) {
+        self.depth = 0;
+        self.stack[0] = Accumulator::new(&self.network, cb);
+    }
+
+    fn make_move(&mut self, before: &Chessboard, after: &Chessboard) {
+        let depth = self.depth + 1;
+        // Reuse the allocations of the deeper entries
+        if self.stack.len() <= depth {
+            self.stack.push(self.stack[self.depth].clone());
+        } else {
+            let (parents, children) = self.stack.split_at_mut(depth);
+            children[0].clone_from(&parents[self.depth]);
+        }
+        self.stack[depth].update(&self.network, before, after);
+        self.depth = depth;
+    }
+
+    fn unmake_move(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
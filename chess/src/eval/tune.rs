@@ -0,0 +1,228 @@
+//! Texel tuning of the hand-crafted evaluation
+//!
+//! Every position is labelled with the result of its game and resolved to a
+//! quiet position by the capture search of [`Search`]. The weights are then fitted so that
+//! `sigmoid(K * eval)` predicts the results, with `K` fitted to the starting
+//! weights first. The evaluation is linear in its weights, so positions are
+//! kept as the counts of the weights they use
+
+use crate::{Chessboard, GameResult, Piece, PieceType, Side, search::Search};
+
+use super::{
+    EvalParams, HandCrafted,
+    hce::{MAX_PHASE, phase_weight, table_index},
+};
+
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+
+/// Position reduced to the terms of the evaluation
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    /// Result of the game for white
+    result: f64,
+    /// Share of the middlegame weights, the endgame ones get the rest
+    phase: f64,
+    /// Middlegame weights with their count, white pieces minus black ones
+    terms: Vec<(u16, i8)>,
+}
+
+impl Sample {
+    /// Linear evaluation for white
+    fn evaluate(&self, weights: &[f64]) -> f64 {
+        let (mg, eg) = weights.split_at(EvalParams::PHASE_LEN);
+        self.terms
+            .iter()
+            .map(|&(i, count)| {
+                let i = i as usize;
+                f64::from(count) * mg[i].mul_add(self.phase, eg[i] * (1.0 - self.phase))
+            })
+            .sum()
+    }
+}
+
+/// Weights being fitted to labelled positions
+#[derive(Debug, Clone)]
+pub struct Tuner {
+    /// Search with the starting evaluation, used to find the quiet positions
+    search: Search<HandCrafted>,
+    weights: Vec<f64>,
+    samples: Vec<Sample>,
+    /// Adam moment estimates of every weight
+    moments: Vec<(f64, f64)>,
+    epochs: i32,
+}
+
+impl Tuner {
+    pub fn new(params: EvalParams) -> Self {
+        let weights = params.to_vec().into_iter().map(f64::from).collect();
+        Self {
+            search: Search::new(HandCrafted::with_params(params)),
+            weights,
+            samples: Vec::new(),
+            moments: vec![(0.0, 0.0); EvalParams::LEN],
+            epochs: 0,
+        }
+    }
+
+    /// Number of positions
+    pub const fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Add a line with a FEN and the result of the game, see [`parse_line`]
+    pub fn add_line(&mut self, line: &str) -> bool {
+        parse_line(line).is_some_and(|(cb, result)| self.add(&cb, result))
+    }
+
+    /// Add the position with the result of its game for white, from 0 to 1
+    ///
+    /// Positions where the game is over are skipped
+    pub fn add(&mut self, cb: &Chessboard, result: f64) -> bool {
+        if !matches!(cb.result, GameResult::None) {
+            return false;
+        }
+        let (_, line) = self.search.quiesce(cb);
+        let cb = line.into_iter().fold(*cb, |cb, m| cb.move_new(m));
+
+        let mut terms = Vec::new();
+        let mut phase = 0;
+        for (side, sign) in [(Side::White, 1), (Side::Black, -1)] {
+            for piece_type in PieceType::ALL {
+                let kind = piece_type as usize;
+                let pieces = cb.get_pieces(Piece::from_side_and_type(side, piece_type));
+                phase += phase_weight(piece_type) * pieces.popcnt() as i32;
+                for sq in pieces {
+                    let square = PieceType::LEN + kind * 64 + table_index(side, sq);
+                    terms.push((kind as u16, sign));
+                    terms.push((square as u16, sign));
+                }
+            }
+        }
+
+        // Merge the material terms of the pieces of a kind
+        terms.sort_unstable_by_key(|&(i, _)| i);
+        let mut merged: Vec<(u16, i8)> = Vec::with_capacity(terms.len());
+        for (i, count) in terms {
+            match merged.last_mut() {
+                Some(last) if last.0 == i => last.1 += count,
+                _ => merged.push((i, count)),
+            }
+        }
+        merged.retain(|&(_, count)| count != 0);
+
+        self.samples.push(Sample {
+            result,
+            phase: f64::from(phase.min(MAX_PHASE)) / f64::from(MAX_PHASE),
+            terms: merged,
+        });
+        true
+    }
+
+    /// Mean squared error of the predicted results
+    pub fn error(&self, k: f64) -> f64 {
+        let total: f64 = self
+            .samples
+            .iter()
+            .map(|s| (s.result - sigmoid(k, s.evaluate(&self.weights))).powi(2))
+            .sum();
+        total / self.samples.len().max(1) as f64
+    }
+
+    /// The scaling constant with the lowest error for the current weights
+    pub fn fit_scaling(&self) -> f64 {
+        let mut best = 1.0;
+        let mut step = 0.1;
+        for _ in 0..3 {
+            let candidates = (-10..=10)
+                .map(|i| best + step * f64::from(i))
+                .filter(|&k| k > 0.0);
+            best = candidates
+                .map(|k| (k, self.error(k)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(best, |(k, _)| k);
+            step /= 10.0;
+        }
+        best
+    }
+
+    /// One step of gradient descent over all positions, with Adam
+    ///
+    /// Returns the error before the step
+    pub fn epoch(&mut self, k: f64, learning_rate: f64) -> f64 {
+        let mut gradient = vec![0.0; EvalParams::LEN];
+        let mut error = 0.0;
+        for sample in &self.samples {
+            let predicted = sigmoid(k, sample.evaluate(&self.weights));
+            let delta = sample.result - predicted;
+            error = delta.mul_add(delta, error);
+            // Derivative of the squared error by the evaluation
+            let slope =
+                -2.0 * delta * predicted * (1.0 - predicted) * k * core::f64::consts::LN_10 / 400.0;
+            for &(i, count) in &sample.terms {
+                let i = i as usize;
+                let count = f64::from(count);
+                gradient[i] = (slope * count).mul_add(sample.phase, gradient[i]);
+                let eg = i + EvalParams::PHASE_LEN;
+                gradient[eg] = (slope * count).mul_add(1.0 - sample.phase, gradient[eg]);
+            }
+        }
+
+        let n = self.samples.len().max(1) as f64;
+        self.epochs += 1;
+        let correction1 = 1.0 - BETA1.powi(self.epochs);
+        let correction2 = 1.0 - BETA2.powi(self.epochs);
+        for ((weight, moment), gradient) in
+            self.weights.iter_mut().zip(&mut self.moments).zip(gradient)
+        {
+            let gradient = gradient / n;
+            moment.0 = BETA1.mul_add(moment.0, (1.0 - BETA1) * gradient);
+            moment.1 = BETA2.mul_add(moment.1, (1.0 - BETA2) * gradient * gradient);
+            let m = moment.0 / correction1;
+            let v = moment.1 / correction2;
+            *weight -= learning_rate * m / (v.sqrt() + 1e-8);
+        }
+        error / n
+    }
+
+    /// The weights rounded to centipawns
+    pub fn params(&self) -> EvalParams {
+        let weights: Vec<_> = self.weights.iter().map(|w| w.round() as i32).collect();
+        EvalParams::from_slice(&weights).unwrap_or_default()
+    }
+}
+
+/// Parse a line with a FEN and a game result
+///
+/// The result comes last, as `1-0`, `0-1`, `1/2-1/2` or `1.0`, `0.5`, `0.0`,
+/// in brackets or quotes like `[0.5]` or `c9 "1/2-1/2";`, or after a `|` like
+/// the lines of [`crate::datagen`]. FENs without the move counters are accepted
+pub fn parse_line(line: &str) -> Option<(Chessboard, f64)> {
+    let (fen, result) = match line.split_once('|') {
+        // Self-play data, `FEN | score | result`
+        Some((fen, rest)) => (fen, rest.rsplit('|').next()?.trim()),
+        None => line.trim().rsplit_once(char::is_whitespace)?,
+    };
+    let result = match result.trim_matches(['[', ']', '"', ';']) {
+        "1-0" | "1" | "1.0" => 1.0,
+        "0-1" | "0" | "0.0" => 0.0,
+        "1/2-1/2" | "0.5" => 0.5,
+        _ => return None,
+    };
+    let fen = fen.trim();
+    let fen = fen.strip_suffix("c9").unwrap_or(fen).trim();
+    let cb = match fen.split_whitespace().count() {
+        4 => Chessboard::from_fen(&format!("{fen} 0 1")),
+        _ => Chessboard::from_fen(fen),
+    };
+    Some((cb.ok()?, result))
+}
+
+/// Expected result for an evaluation in centipawns
+fn sigmoid(k: f64, eval: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
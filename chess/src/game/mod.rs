@@ -0,0 +1,394 @@
+//! Games played move by move
+//!
+//! A [`Game`] keeps every position of its line, so moves can be taken back,
+//! replayed and browsed. What the board cannot see, like resigning, agreeing
+//! to a draw or repeating a position, ends the game here. The board draws
+//! after fifty moves without a capture or a pawn move, a game goes on until
+//! the draw is claimed or seventy-five moves are reached. With a [`Clock`] the
+//! time of every move is recorded and a fallen flag ends the game
+
+use std::time::Duration;
+
+use crate::{
+    ChessError, Chessboard, DrawType, GameResult, Move, MoveOutcome, START_POS, Side, WinType,
+    book::polyglot_key, clock::Clock,
+};
+
+/// Draws a player can claim instead of waiting for the board to end the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Claim {
+    /// The position occurred three times
+    Threefold,
+    /// No capture or pawn move in the last fifty moves
+    FiftyMoves,
+}
+
+/// Move of the line
+#[derive(Debug, Clone, Copy)]
+struct Ply {
+    m: Move,
+    /// Position the move leads to
+    cb: Chessboard,
+    /// Time used on the clock
+    time: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Chessboard,
+    line: Vec<Ply>,
+    /// Keys of the starting position and of every position of the line
+    keys: Vec<u64>,
+    /// Position shown, `0` is the starting position
+    ply: usize,
+    /// Result of an action at the end of the line
+    ended: Option<GameResult>,
+    /// Side whose draw offer is pending
+    draw_offer: Option<Side>,
+    clock: Option<Clock>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        let Ok(start) = Chessboard::from_fen(START_POS) else {
+            unreachable!()
+        };
+        Self::from_position(start)
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, ChessError> {
+        Chessboard::from_fen(fen).map(Self::from_position)
+    }
+
+    pub fn from_position(start: Chessboard) -> Self {
+        Self {
+            start,
+            line: Vec::new(),
+            keys: vec![polyglot_key(&start)],
+            ply: 0,
+            ended: None,
+            draw_offer: None,
+            clock: None,
+        }
+    }
+
+    pub const fn start(&self) -> &Chessboard {
+        &self.start
+    }
+
+    /// Position at the current ply
+    pub fn position(&self) -> &Chessboard {
+        match self.ply {
+            0 => &self.start,
+            ply => &self.line[ply - 1].cb,
+        }
+    }
+
+    /// Plies played to reach the current position
+    pub const fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Plies of the whole line, including those undone
+    pub const fn len(&self) -> usize {
+        self.line.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.line.is_empty()
+    }
+
+    /// Moves of the whole line, including those undone
+    pub fn moves(&self) -> Vec<Move> {
+        self.line.iter().map(|ply| ply.m).collect()
+    }
+
+    /// Moves that led to the current position
+    pub fn played(&self) -> Vec<Move> {
+        self.line[..self.ply].iter().map(|ply| ply.m).collect()
+    }
+
+    /// Clock time used by each move of the line, `None` for moves made without a clock
+    pub fn move_times(&self) -> Vec<Option<Duration>> {
+        self.line.iter().map(|ply| ply.time).collect()
+    }
+
+    /// Result at the current ply
+    ///
+    /// Resignations, flag-falls and draws agreed, claimed, by fivefold
+    /// repetition or by a dead position end the line, earlier plies show the
+    /// result of their position
+    pub fn result(&self) -> GameResult {
+        match self.ended {
+            Some(result) if self.ply == self.line.len() => result,
+            _ => board_result(self.position()),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result().is_over()
+    }
+
+    /// Play the legal move, the undone moves after the current ply are dropped
+    ///
+    /// Moving declines the draw offer of the opponent. A move made after the
+    /// flag fell is not played and the game ends on time
+    pub fn make_move(&mut self, m: Move) -> Result<MoveOutcome, ChessError> {
+        if self.check_time() || self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        let mut cb = playable(self.position());
+        let outcome = cb.try_make_move(m)?;
+
+        let time = self.press_clock()?;
+        if self.draw_offer == Some(cb.side()) {
+            self.draw_offer = None;
+        }
+        self.push(m, cb, time);
+        if !self.is_over() {
+            if self.repetitions() >= 5 {
+                self.end(GameResult::Draw(DrawType::FivefoldRepetition));
+            } else if self.position().is_dead_position() {
+                self.end(GameResult::Draw(DrawType::DeadPosition));
+            }
+        }
+        if self.is_over()
+            && let Some(clock) = &mut self.clock
+        {
+            clock.stop();
+        }
+        Ok(MoveOutcome {
+            result: self.result(),
+            ..outcome
+        })
+    }
+
+    /// Use the clock for the rest of the game, running for the side to move
+    pub fn set_clock(&mut self, mut clock: Clock) {
+        if !self.is_over() {
+            clock.start(self.position().side());
+        }
+        self.clock = Some(clock);
+    }
+
+    pub const fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// End the game if a flag fell, returning whether it did
+    ///
+    /// The side out of time loses, unless the opponent has no mating material
+    pub fn check_time(&mut self) -> bool {
+        if self.is_over() {
+            return false;
+        }
+        let Some(side) = self.clock.as_ref().and_then(Clock::flagged) else {
+            return false;
+        };
+        let result = if self.position().has_mating_material(side.neg()) {
+            GameResult::win(side.neg(), WinType::Timeout)
+        } else {
+            GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial)
+        };
+        self.end(result);
+        true
+    }
+
+    /// Step back one ply, returning the move taken back
+    pub fn undo(&mut self) -> Option<Move> {
+        let ply = self.ply.checked_sub(1)?;
+        self.go_to(ply);
+        Some(self.line[ply].m)
+    }
+
+    /// Replay the next undone move
+    pub fn redo(&mut self) -> Option<Move> {
+        let m = self.line.get(self.ply)?.m;
+        self.go_to(self.ply + 1);
+        Some(m)
+    }
+
+    /// Show the position after the number of plies, `false` if the line is shorter
+    ///
+    /// A pending draw offer is withdrawn
+    pub const fn go_to(&mut self, ply: usize) -> bool {
+        if ply > self.line.len() {
+            return false;
+        }
+        if ply != self.ply {
+            self.draw_offer = None;
+        }
+        self.ply = ply;
+        true
+    }
+
+    /// Polyglot keys of the positions before the current one
+    pub fn history(&self) -> &[u64] {
+        &self.keys[..self.ply]
+    }
+
+    /// How many times the current position occurred, counting itself
+    pub fn repetitions(&self) -> usize {
+        let key = self.keys[self.ply];
+        self.keys[..=self.ply].iter().filter(|&&k| k == key).count()
+    }
+
+    pub const fn draw_offer(&self) -> Option<Side> {
+        self.draw_offer
+    }
+
+    pub fn resign(&mut self, side: Side) -> Result<(), ChessError> {
+        if self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        self.end(GameResult::win(side.neg(), WinType::Resign));
+        Ok(())
+    }
+
+    /// End the game with a result decided away from the board, like an
+    /// adjudication, a forfeit or an abandonment
+    pub fn adjudicate(&mut self, result: GameResult) -> Result<(), ChessError> {
+        if self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        if result.is_over() {
+            self.end(result);
+        }
+        Ok(())
+    }
+
+    /// Offer a draw, it stands until the opponent answers or moves
+    pub fn offer_draw(&mut self, side: Side) -> Result<(), ChessError> {
+        if self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        self.draw_offer = Some(side);
+        Ok(())
+    }
+
+    pub fn accept_draw(&mut self, side: Side) -> Result<(), ChessError> {
+        if self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        if self.draw_offer != Some(side.neg()) {
+            return Err(ChessError::NoDrawOffer);
+        }
+        self.end(GameResult::Draw(DrawType::Agreement));
+        Ok(())
+    }
+
+    pub fn decline_draw(&mut self, side: Side) -> Result<(), ChessError> {
+        if self.draw_offer != Some(side.neg()) {
+            return Err(ChessError::NoDrawOffer);
+        }
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// Claim a draw, optionally together with the move that makes the claim hold
+    ///
+    /// Nothing changes when the claim is wrong
+    pub fn claim_draw(&mut self, claim: Claim, m: Option<Move>) -> Result<(), ChessError> {
+        if self.check_time() || self.is_over() {
+            return Err(ChessError::GameOver);
+        }
+        let mut cb = playable(self.position());
+        let repetitions = match m {
+            Some(m) => {
+                cb.try_make_move(m)?;
+                let key = polyglot_key(&cb);
+                1 + self.keys[..=self.ply].iter().filter(|&&k| k == key).count()
+            }
+            None => self.repetitions(),
+        };
+
+        let (holds, draw_type) = match claim {
+            Claim::Threefold => (repetitions >= 3, DrawType::Repetition),
+            Claim::FiftyMoves => (cb.half_moves() >= 100, DrawType::FiftyMoveRule),
+        };
+        if !holds {
+            return Err(ChessError::InvalidClaim);
+        }
+
+        if let Some(m) = m {
+            let time = self.press_clock()?;
+            self.push(m, cb, time);
+        }
+        // A mate by the move stands
+        if !self.is_over() {
+            self.end(GameResult::Draw(draw_type));
+        }
+        Ok(())
+    }
+
+    /// Time used for the move being made, ending the game when the flag fell
+    fn press_clock(&mut self) -> Result<Option<Duration>, ChessError> {
+        let Some(clock) = &mut self.clock else {
+            return Ok(None);
+        };
+        match clock.press() {
+            Some(time) => Ok(Some(time)),
+            None if self.check_time() => Err(ChessError::GameOver),
+            // The clock was stopped
+            None => Ok(None),
+        }
+    }
+
+    /// End the game at the current ply, dropping the undone moves
+    fn end(&mut self, result: GameResult) {
+        self.cut();
+        self.ended = Some(result);
+        self.draw_offer = None;
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+    }
+
+    /// Add the move after the current ply, dropping the undone moves
+    fn push(&mut self, m: Move, cb: Chessboard, time: Option<Duration>) {
+        self.cut();
+        self.line.push(Ply { m, cb, time });
+        self.keys.push(polyglot_key(&cb));
+        self.ply += 1;
+    }
+
+    fn cut(&mut self) {
+        self.line.truncate(self.ply);
+        self.keys.truncate(self.ply + 1);
+        self.ended = None;
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of the position in a game, where the fifty-move draw of the board
+/// only ends the game after seventy-five moves
+const fn board_result(cb: &Chessboard) -> GameResult {
+    match cb.result {
+        GameResult::Draw(DrawType::FiftyMoveRule) if cb.moves_cache.is_empty() => {
+            if cb.checkers.is_empty() {
+                GameResult::Draw(DrawType::Stalemate)
+            } else {
+                GameResult::win(cb.side().neg(), WinType::Checkmate)
+            }
+        }
+        GameResult::Draw(DrawType::FiftyMoveRule) if cb.half_moves() < 150 => GameResult::None,
+        GameResult::Draw(DrawType::FiftyMoveRule) => {
+            GameResult::Draw(DrawType::SeventyFiveMoveRule)
+        }
+        result => result,
+    }
+}
+
+/// The position, with the fifty-move draw of the board waiting for a claim
+const fn playable(cb: &Chessboard) -> Chessboard {
+    let mut cb = *cb;
+    if matches!(board_result(&cb), GameResult::None) {
+        cb.result = GameResult::None;
+    }
+    cb
+}
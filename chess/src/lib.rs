@@ -17,8 +17,42 @@
 )]
 #![allow(clippy::indexing_slicing)]
 
+#[cfg(not(feature = "no_std"))]
+pub mod book;
 mod chessboard;
+#[cfg(not(feature = "no_std"))]
+pub mod clock;
+#[cfg(not(feature = "no_std"))]
+pub mod datagen;
+#[cfg(not(feature = "no_std"))]
+pub mod eval;
+#[cfg(not(feature = "no_std"))]
+pub mod game;
+#[cfg(not(feature = "no_std"))]
+pub mod mcts;
 mod move_gen;
+#[cfg(not(feature = "no_std"))]
+pub mod packed;
+#[cfg(not(feature = "no_std"))]
+pub mod pgn;
+#[cfg(not(feature = "no_std"))]
+pub mod problem;
+#[cfg(not(feature = "no_std"))]
+pub mod proof;
+#[cfg(not(feature = "no_std"))]
+pub mod search;
+#[cfg(all(feature = "server", not(feature = "no_std")))]
+pub mod server;
+#[cfg(not(feature = "no_std"))]
+pub mod syzygy;
+#[cfg(not(feature = "no_std"))]
+pub mod tablebase;
+#[cfg(not(feature = "no_std"))]
+pub mod tournament;
+#[cfg(not(feature = "no_std"))]
+pub mod uci;
+#[cfg(not(feature = "no_std"))]
+pub mod xboard;
 
 /// The starting position
 pub static START_POS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
@@ -0,0 +1,310 @@
+//! Monte Carlo tree search
+//!
+//! Needs no evaluation: positions are scored by playing random or lightly
+//! guided games to the end, and the tree grows towards the moves that win
+//! those playouts most often, balanced by UCT exploration
+
+use std::time::{Duration, Instant};
+
+use crate::{Chessboard, GameResult, Move, MoveGen, Side};
+
+/// How long a search runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Iterations(u64),
+    Time(Duration),
+}
+
+/// How playouts choose their moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playout {
+    /// Every legal move is equally likely
+    Random,
+    /// Out of a few random moves, captures and promotions are played first
+    Heuristic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// Weight of the UCT exploration term
+    pub exploration: f64,
+    pub playout: Playout,
+    /// Playouts longer than this are scored as draws
+    pub max_playout_plies: usize,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            exploration: core::f64::consts::SQRT_2,
+            playout: Playout::Heuristic,
+            max_playout_plies: 200,
+        }
+    }
+}
+
+/// Statistics of a move at the root
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveStats {
+    pub m: Move,
+    pub visits: u32,
+    /// Average playout result for the side playing the move, from 0 to 1
+    pub score: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    cb: Chessboard,
+    m: Option<Move>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Legal moves without a child yet, filled on the first visit
+    unexpanded: Option<Vec<Move>>,
+    visits: u32,
+    /// Sum of the results for the side that played the move into the node
+    score: f64,
+}
+
+impl Node {
+    const fn new(cb: Chessboard, m: Option<Move>, parent: Option<usize>) -> Self {
+        Self {
+            cb,
+            m,
+            parent,
+            children: Vec::new(),
+            unexpanded: None,
+            visits: 0,
+            score: 0.0,
+        }
+    }
+
+    const fn is_terminal(&self) -> bool {
+        !matches!(self.cb.result, GameResult::None)
+    }
+}
+
+/// Search tree rooted at the current position
+///
+/// The tree is kept between moves, see [`Mcts::advance`]
+#[derive(Debug)]
+pub struct Mcts {
+    nodes: Vec<Node>,
+    config: MctsConfig,
+    rng: fastrand::Rng,
+}
+
+impl Mcts {
+    pub fn new(cb: Chessboard) -> Self {
+        Self::with_config(cb, MctsConfig::default())
+    }
+
+    pub fn with_config(cb: Chessboard, config: MctsConfig) -> Self {
+        Self {
+            nodes: vec![Node::new(cb, None, None)],
+            config,
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    /// Make the playouts reproducible
+    pub fn seed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+    }
+
+    pub fn position(&self) -> &Chessboard {
+        &self.nodes[0].cb
+    }
+
+    /// Number of nodes in the tree
+    pub const fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Playouts through the root so far, including the reused ones
+    pub fn visits(&self) -> u32 {
+        self.nodes[0].visits
+    }
+
+    /// Grow the tree and return the most visited move
+    pub fn search(&mut self, budget: Budget) -> Option<Move> {
+        let start = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let done = match budget {
+                Budget::Iterations(n) => iterations >= n,
+                Budget::Time(time) => start.elapsed() >= time,
+            };
+            if done || self.nodes[0].is_terminal() {
+                break;
+            }
+            self.iterate();
+            iterations += 1;
+        }
+        self.best_move()
+    }
+
+    /// The most visited move at the root
+    pub fn best_move(&self) -> Option<Move> {
+        self.stats()
+            .into_iter()
+            .max_by(|a, b| a.visits.cmp(&b.visits).then(a.score.total_cmp(&b.score)))
+            .map(|stats| stats.m)
+    }
+
+    /// Statistics of the searched moves at the root
+    pub fn stats(&self) -> Vec<MoveStats> {
+        self.nodes[0]
+            .children
+            .iter()
+            .filter_map(|&i| {
+                let node = &self.nodes[i];
+                Some(MoveStats {
+                    m: node.m?,
+                    visits: node.visits,
+                    score: node.score / f64::from(node.visits.max(1)),
+                })
+            })
+            .collect()
+    }
+
+    /// Play the move on the root, keeping its subtree when it was searched
+    pub fn advance(&mut self, m: Move) {
+        let child = self.nodes[0]
+            .children
+            .iter()
+            .copied()
+            .find(|&i| self.nodes[i].m == Some(m));
+        let Some(child) = child else {
+            let cb = self.nodes[0].cb.move_new(m);
+            self.nodes = vec![Node::new(cb, None, None)];
+            return;
+        };
+
+        // Copy the subtree in order, parents come before their children
+        let mut nodes = Vec::new();
+        let mut stack = vec![(child, None)];
+        while let Some((old, parent)) = stack.pop() {
+            let index = nodes.len();
+            let mut node = self.nodes[old].clone();
+            node.parent = parent;
+            let children = core::mem::take(&mut node.children);
+            if let Some(parent) = parent {
+                let parent: &mut Node = &mut nodes[parent];
+                parent.children.push(index);
+            }
+            nodes.push(node);
+            stack.extend(children.into_iter().rev().map(|c| (c, Some(index))));
+        }
+        nodes[0].m = None;
+        self.nodes = nodes;
+    }
+
+    fn iterate(&mut self) {
+        let mut index = 0;
+        // Selection
+        loop {
+            let node = &self.nodes[index];
+            if node.is_terminal() || node.unexpanded.as_ref().is_none_or(|m| !m.is_empty()) {
+                break;
+            }
+            index = self.select(index);
+        }
+
+        // Expansion
+        if !self.nodes[index].is_terminal() {
+            let node = &mut self.nodes[index];
+            let cb = node.cb;
+            let unexpanded = node.unexpanded.get_or_insert_with(|| cb.to_vec());
+            let m = unexpanded.swap_remove(self.rng.usize(..unexpanded.len()));
+            let cb = cb.move_new(m);
+            let child = self.nodes.len();
+            self.nodes.push(Node::new(cb, Some(m), Some(index)));
+            self.nodes[index].children.push(child);
+            index = child;
+        }
+
+        let result = self.playout(self.nodes[index].cb);
+
+        // Backpropagation
+        let mut current = Some(index);
+        while let Some(i) = current {
+            let node = &mut self.nodes[i];
+            node.visits += 1;
+            // The move into the node was played by the side not to move
+            node.score += match node.cb.side() {
+                Side::White => 1.0 - result,
+                Side::Black => result,
+            };
+            current = node.parent;
+        }
+    }
+
+    /// The child with the best UCT value
+    fn select(&self, index: usize) -> usize {
+        let node = &self.nodes[index];
+        let log = f64::from(node.visits).ln();
+        let uct = |i: usize| {
+            let child = &self.nodes[i];
+            let visits = f64::from(child.visits);
+            self.config
+                .exploration
+                .mul_add((log / visits).sqrt(), child.score / visits)
+        };
+        node.children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .unwrap_or(index)
+    }
+
+    /// Play the game out, the result is 1 for a white win and 0 for a black win
+    fn playout(&mut self, mut cb: Chessboard) -> f64 {
+        for _ in 0..self.config.max_playout_plies {
+            match cb.result {
+                GameResult::None => (),
+                result => return score(result),
+            }
+            let Some(m) = self.playout_move(&cb) else {
+                break;
+            };
+            cb.make_move(m);
+        }
+        score(cb.result)
+    }
+
+    fn playout_move(&mut self, cb: &Chessboard) -> Option<Move> {
+        let count = cb.moves_cache.count();
+        if count == 0 {
+            return None;
+        }
+        let move_gen = MoveGen::new(cb.moves_cache);
+        let tries = match self.config.playout {
+            Playout::Random => 1,
+            Playout::Heuristic => 4,
+        };
+
+        let mut fallback = None;
+        for _ in 0..tries {
+            let m = move_gen.nth(self.rng.usize(..count))?;
+            let forcing = match m {
+                Move::Standard { to, .. } => cb.at(to).is_some(),
+                Move::Promotion { .. } | Move::EnPassant { .. } => true,
+                _ => false,
+            };
+            if forcing {
+                return Some(m);
+            }
+            fallback.get_or_insert(m);
+        }
+        fallback
+    }
+}
+
+/// Result of the finished game for white, unfinished games are draws
+const fn score(result: GameResult) -> f64 {
+    match result {
+        GameResult::WhiteWin(_) => 1.0,
+        GameResult::BlackWin(_) => 0.0,
+        _ => 0.5,
+    }
+}
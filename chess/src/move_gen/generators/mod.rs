@@ -89,7 +89,7 @@ pub const fn generate_moves(cb: &mut Chessboard) -> MoveList {
     moves
 }
 
-const fn is_check(
+pub const fn is_check(
     cb: &Chessboard,
     new_occupanicy: Bitboard,
     them: types::Side,
@@ -2,10 +2,10 @@ mod generators;
 mod tables;
 
 pub use generators::{Bishop, King, Knight, Pawn, Queen, Rook};
-pub use generators::{generate_moves, perft};
+pub use generators::{generate_moves, is_check, perft};
 pub use tables::*;
 
-use crate::{Castling, Chessboard, Move, MoveList, Piece, PieceType, Rank, Side, Square};
+use crate::{Bitboard, Castling, Chessboard, Move, MoveList, Piece, PieceType, Rank, Side, Square};
 
 #[derive(Debug)]
 pub struct MoveGen {
@@ -27,7 +27,7 @@ impl MoveGen {
             | Move::Promotion { from, to, .. }
             | Move::EnPassant { from, to, .. }
             | Move::Castling { from, to, .. } => (from, to.bitboard()),
-            Move::Remove { .. } | Move::Put { .. } => return false,
+            Move::Remove { .. } | Move::Put { .. } | Move::Null => return false,
         };
 
         let mut i = 0;
@@ -44,9 +44,15 @@ impl MoveGen {
         }
     }
 
+    /// Parse the move in the long algebraic notation (like `e2e4` or `e7e8q`)
+    ///
+    /// `0000` is parsed as [`Move::Null`]
     pub const fn from_str(&mut self, cb: &Chessboard, m: &str) -> Option<Move> {
         let bytes = m.as_bytes();
-        if bytes.len() != 4 {
+        if let [b'0', b'0', b'0', b'0'] = bytes {
+            return Some(Move::Null);
+        }
+        if bytes.len() != 4 && bytes.len() != 5 {
             return None;
         }
 
@@ -57,14 +63,76 @@ impl MoveGen {
             return None;
         };
 
-        self.from_squares(cb, start_sq, end_sq)
+        if bytes.len() == 4 {
+            return self.from_squares(cb, start_sq, end_sq);
+        }
+
+        let promotion = match Piece::from_char(bytes[4] as char) {
+            Some(piece) => piece.piece_type(),
+            None => return None,
+        };
+        cb.move_from_squares(start_sq, end_sq, Some(promotion))
     }
 
+    /// Build the legal move between the squares
+    ///
+    /// Promotions need a piece, see [`Chessboard::move_from_squares`]
     pub const fn from_squares(&mut self, cb: &Chessboard, start_sq: Square, end_sq: Square) -> Option<Move> {
-        let Some(start_piece) = cb.at(start_sq) else {
-            return None;
+        cb.move_from_squares(start_sq, end_sq, None)
+    }
+
+    /// The move at the index in `0..MoveList::count()`, without walking the moves before it
+    ///
+    /// Each promotion counts as four moves, from knight to queen
+    pub const fn nth(&self, mut index: usize) -> Option<Move> {
+        let end_rank = match self.cache.side {
+            Side::White => Rank::Eighth,
+            Side::Black => Rank::First,
         };
-        Some(self.get_move_unchecked(start_sq, end_sq, start_piece))
+
+        let mut i = 0;
+        while i < self.cache.len {
+            let moves = self.cache.moves[i];
+            let is_pawn = moves.piece.piece_type().as_u8() == PieceType::Pawn.as_u8();
+            let promotions = if is_pawn {
+                moves.bitboard.and(end_rank.bitboard())
+            } else {
+                Bitboard::EMPTY
+            };
+            let count = (moves.bitboard.popcnt() + promotions.popcnt() * 3) as usize;
+            if index >= count {
+                index -= count;
+                i += 1;
+                continue;
+            }
+
+            let mut bitboard = moves.bitboard;
+            while let Some(to) = bitboard.next_const() {
+                if !promotions.overlaps(to.bitboard()) {
+                    if index == 0 {
+                        let mut move_gen = Self::new(self.cache);
+                        return Some(move_gen.get_move_unchecked(moves.from, to, moves.piece));
+                    }
+                    index -= 1;
+                } else if index < 4 {
+                    let promotion = match index {
+                        0 => PieceType::Knight,
+                        1 => PieceType::Bishop,
+                        2 => PieceType::Rook,
+                        _ => PieceType::Queen,
+                    };
+                    return Some(Move::Promotion {
+                        from: moves.from,
+                        to,
+                        promotion: Piece::from_side_and_type(self.cache.side, promotion),
+                    });
+                } else {
+                    index -= 4;
+                }
+            }
+            return None;
+        }
+        None
     }
 
     pub const fn next_const(&mut self) -> Option<Move> {
@@ -1,4 +1,4 @@
-use types::{Bitboard, Magic, Side, Square};
+use types::{Bitboard, Magic, Piece, PieceType, Side, Square};
 
 include!("../../generated.rs");
 
@@ -55,3 +55,15 @@ pub const fn get_connection_axis(start: Square, end: Square) -> Bitboard {
 pub const fn get_connection_direct(start: Square, end: Square) -> Bitboard {
     DIRECT_CONNECTIONS[start.as_u8() as usize][end.as_u8() as usize]
 }
+
+/// Squares attacked by the piece standing on the square
+pub const fn get_piece_attacks(piece: Piece, square: Square, occupied: Bitboard) -> Bitboard {
+    match piece.piece_type() {
+        PieceType::Pawn => get_pawn_attacks(piece.side(), square),
+        PieceType::Knight => get_knight_attacks(square),
+        PieceType::Bishop => get_bishop_attacks(square, occupied),
+        PieceType::Rook => get_rook_attacks(square, occupied),
+        PieceType::Queen => get_rook_attacks(square, occupied).or(get_bishop_attacks(square, occupied)),
+        PieceType::King => get_king_attacks(square),
+    }
+}
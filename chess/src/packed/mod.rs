@@ -0,0 +1,130 @@
+//! Files of fixed-size binary records
+//!
+//! Positions are packed with [`ChessboardRaw::to_packed`], 32 bytes instead of
+//! a FEN of around 60 that has to be parsed. Records are stored back to back
+//! without a header, so files can be concatenated and split freely
+
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+
+use crate::{ChessError, Chessboard, ChessboardRaw};
+
+/// Value with a fixed-size binary form
+pub trait Packed: Sized {
+    /// Bytes of a record
+    const SIZE: usize;
+
+    /// Append the record to the buffer
+    fn pack(&self, out: &mut Vec<u8>);
+
+    /// Read the record from exactly [`Packed::SIZE`] bytes
+    fn unpack(bytes: &[u8]) -> Result<Self, ChessError>;
+}
+
+impl Packed for ChessboardRaw {
+    const SIZE: usize = Self::PACKED_SIZE;
+
+    fn pack(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_packed());
+    }
+
+    fn unpack(bytes: &[u8]) -> Result<Self, ChessError> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| ChessError::InvalidPackedBoard)?;
+        Self::from_packed(bytes)
+    }
+}
+
+impl Packed for Chessboard {
+    const SIZE: usize = ChessboardRaw::PACKED_SIZE;
+
+    fn pack(&self, out: &mut Vec<u8>) {
+        self.into_raw().pack(out);
+    }
+
+    fn unpack(bytes: &[u8]) -> Result<Self, ChessError> {
+        Self::from_raw(ChessboardRaw::unpack(bytes)?)
+    }
+}
+
+/// Buffered writer of records
+#[derive(Debug)]
+pub struct PackedWriter<W: Write> {
+    writer: BufWriter<W>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> PackedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn write<T: Packed>(&mut self, record: &T) -> io::Result<()> {
+        self.buffer.clear();
+        record.pack(&mut self.buffer);
+        debug_assert_eq!(self.buffer.len(), T::SIZE);
+        self.writer.write_all(&self.buffer)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush and return the inner writer
+    pub fn into_inner(self) -> io::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)
+    }
+}
+
+/// Buffered reader yielding the records of a file in order
+///
+/// Corrupted records and a truncated last record are [`ErrorKind::InvalidData`]
+#[derive(Debug)]
+pub struct PackedReader<R: Read, T> {
+    reader: BufReader<R>,
+    buffer: Vec<u8>,
+    _record: core::marker::PhantomData<T>,
+}
+
+impl<R: Read, T: Packed> PackedReader<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            buffer: vec![0; T::SIZE],
+            _record: core::marker::PhantomData,
+        }
+    }
+
+    /// Next record, `None` at the end of the file
+    pub fn read(&mut self) -> io::Result<Option<T>> {
+        let mut filled = 0;
+        while filled < T::SIZE {
+            match self.reader.read(&mut self.buffer[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        match filled {
+            0 => Ok(None),
+            n if n < T::SIZE => Err(io::Error::new(ErrorKind::InvalidData, "truncated record")),
+            _ => T::unpack(&self.buffer)
+                .map(Some)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, format!("{e:?}"))),
+        }
+    }
+}
+
+impl<R: Read, T: Packed> Iterator for PackedReader<R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
@@ -0,0 +1,326 @@
+//! Portable Game Notation
+//!
+//! A [`Pgn`] holds the tags, the moves and the move comments of a game.
+//! Reading skips variations and annotation glyphs, writing puts the moves in
+//! standard algebraic notation on lines of at most 80 characters
+
+use std::fmt;
+
+use crate::{ChessError, Chessboard, Move, START_POS, game::Game};
+
+/// Tags every game has, in the order they are written
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+const LINE_WIDTH: usize = 80;
+
+#[derive(Debug, Clone)]
+pub struct Pgn {
+    /// Tags in the order they are written
+    pub tags: Vec<(String, String)>,
+    pub start: Chessboard,
+    pub moves: Vec<Move>,
+    /// Comment after the move with the same index
+    pub comments: Vec<Option<String>>,
+}
+
+impl Pgn {
+    /// Game without moves from the starting position, the tags of the
+    /// seven tag roster are unknown
+    pub fn new() -> Self {
+        let Ok(start) = Chessboard::from_fen(START_POS) else {
+            unreachable!()
+        };
+        Self::from_position(start)
+    }
+
+    pub fn from_position(start: Chessboard) -> Self {
+        let mut pgn = Self {
+            tags: SEVEN_TAG_ROSTER
+                .iter()
+                .map(|&name| (name.to_string(), String::from("?")))
+                .collect(),
+            start,
+            moves: Vec::new(),
+            comments: Vec::new(),
+        };
+        pgn.set_tag("Result", "*");
+        if start.get_fen() != START_POS {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", start.get_fen());
+        }
+        pgn
+    }
+
+    /// Moves played up to the current ply of the game, with its result and
+    /// termination
+    pub fn from_game(game: &Game) -> Self {
+        let mut pgn = Self::from_position(*game.start());
+        pgn.moves = game.played();
+        let result = game.result();
+        pgn.set_tag("Result", result.pgn_result());
+        if result.is_over() {
+            pgn.set_tag("Termination", result.termination().as_str());
+        }
+        pgn
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of the tag, new tags are added last
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    /// Value of the `Result` tag, `*` when missing
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+
+    /// Position after all the moves
+    pub fn position(&self) -> Chessboard {
+        let mut cb = self.start;
+        for &m in &self.moves {
+            cb.make_move(m);
+        }
+        cb
+    }
+
+    /// First game of the text
+    pub fn parse(text: &str) -> Result<Self, ChessError> {
+        Self::parse_all(text)?
+            .into_iter()
+            .next()
+            .ok_or(ChessError::InvalidPgn)
+    }
+
+    /// All games of the text, like the content of a `.pgn` file
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, ChessError> {
+        let mut games = Vec::new();
+        let mut reading = Reading::default();
+        for token in tokens(text)? {
+            match token {
+                Token::Tag(name, value) => {
+                    if reading.started() {
+                        games.push(std::mem::take(&mut reading).finish()?);
+                    }
+                    reading.tags.push((name, value));
+                }
+                Token::Comment(comment) => {
+                    if let Some(last) = reading.comments.last_mut() {
+                        *last = Some(match last.take() {
+                            Some(previous) => format!("{previous} {comment}"),
+                            None => comment,
+                        });
+                    }
+                }
+                Token::Move(san) => {
+                    let cb = reading.position()?;
+                    let m = cb.from_san(&san).ok_or(ChessError::InvalidPgn)?;
+                    cb.make_move(m);
+                    reading.moves.push(m);
+                    reading.comments.push(None);
+                }
+                Token::Result(result) => {
+                    reading.result = Some(result);
+                    games.push(std::mem::take(&mut reading).finish()?);
+                }
+            }
+        }
+        if reading.started() {
+            games.push(reading.finish()?);
+        }
+        Ok(games)
+    }
+}
+
+impl Default for Pgn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        writeln!(f)?;
+
+        let mut words = Vec::new();
+        let mut cb = self.start;
+        for (i, &m) in self.moves.iter().enumerate() {
+            if cb.side() == crate::Side::White {
+                words.push(format!("{}.", cb.full_moves()));
+            } else if i == 0 || matches!(self.comments.get(i - 1), Some(Some(_))) {
+                words.push(format!("{}...", cb.full_moves()));
+            }
+            words.push(cb.to_san(m).ok_or(fmt::Error)?);
+            if let Some(Some(comment)) = self.comments.get(i) {
+                words.push(format!("{{{comment}}}"));
+            }
+            cb.make_move(m);
+        }
+        words.push(self.result().to_string());
+
+        let mut line = 0;
+        for word in words {
+            if line > 0 && line + 1 + word.len() > LINE_WIDTH {
+                writeln!(f)?;
+                line = 0;
+            }
+            if line > 0 {
+                write!(f, " ")?;
+                line += 1;
+            }
+            write!(f, "{word}")?;
+            line += word.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// Game being read
+#[derive(Debug, Default)]
+struct Reading {
+    tags: Vec<(String, String)>,
+    position: Option<Chessboard>,
+    moves: Vec<Move>,
+    comments: Vec<Option<String>>,
+    result: Option<String>,
+}
+
+impl Reading {
+    const fn started(&self) -> bool {
+        !self.moves.is_empty() || self.result.is_some()
+    }
+
+    /// Position after the moves read, set up from the `FEN` tag
+    fn position(&mut self) -> Result<&mut Chessboard, ChessError> {
+        if self.position.is_none() {
+            self.position = Some(self.start()?);
+        }
+        self.position.as_mut().ok_or(ChessError::InvalidPgn)
+    }
+
+    fn start(&self) -> Result<Chessboard, ChessError> {
+        let fen = self
+            .tags
+            .iter()
+            .find(|(name, _)| name == "FEN")
+            .map_or(START_POS, |(_, fen)| fen.as_str());
+        Chessboard::from_fen(fen)
+    }
+
+    fn finish(self) -> Result<Pgn, ChessError> {
+        let start = self.start()?;
+        let mut tags = self.tags;
+        if let Some(result) = self.result {
+            match tags.iter_mut().find(|(name, _)| name == "Result") {
+                Some((_, value)) => *value = result,
+                None => tags.push((String::from("Result"), result)),
+            }
+        }
+        Ok(Pgn {
+            tags,
+            start,
+            moves: self.moves,
+            comments: self.comments,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    /// Move in standard algebraic notation, without its number
+    Move(String),
+    Result(String),
+}
+
+fn tokens(text: &str) -> Result<Vec<Token>, ChessError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut variations = 0usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' if variations == 0 => {
+                let name: String = chars
+                    .by_ref()
+                    .skip_while(|c| c.is_whitespace())
+                    .take_while(|c| !c.is_whitespace())
+                    .collect();
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next() != Some('"') {
+                    return Err(ChessError::InvalidPgn);
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next().ok_or(ChessError::InvalidPgn)? {
+                        '\\' => value.extend(chars.next()),
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+                while chars.next_if(|&c| c != ']').is_some() {}
+                chars.next();
+                tokens.push(Token::Tag(name, value));
+            }
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if variations == 0 {
+                    tokens.push(Token::Comment(
+                        comment.split_whitespace().collect::<Vec<_>>().join(" "),
+                    ));
+                }
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '\n').collect();
+                if variations == 0 {
+                    tokens.push(Token::Comment(comment.trim().to_string()));
+                }
+            }
+            '(' => variations += 1,
+            ')' => variations = variations.checked_sub(1).ok_or(ChessError::InvalidPgn)?,
+            '$' => while chars.next_if(char::is_ascii_digit).is_some() {},
+            c if c.is_whitespace() => (),
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"{}()[];$".contains(*c))
+                {
+                    word.push(c);
+                }
+                if variations > 0 {
+                    continue;
+                }
+                match word.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => tokens.push(Token::Result(word)),
+                    _ => {
+                        // Move numbers like `12.` or `12...`, castling can be `0-0`
+                        let san = match word.find(|c: char| !c.is_ascii_digit()) {
+                            Some(i) if word[i..].starts_with('.') => {
+                                word[i..].trim_start_matches('.')
+                            }
+                            _ => &word,
+                        };
+                        if !san.is_empty() {
+                            tokens.push(Token::Move(san.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
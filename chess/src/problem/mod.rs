@@ -0,0 +1,151 @@
+//! Chess problem solver
+//!
+//! Solves direct mates, selfmates, helpmates and stalemate problems by
+//! enumerating every solution, so cooks and duals of a composition show up
+
+mod solver;
+mod stipulation;
+
+pub use stipulation::Stipulation;
+
+use solver::Solver;
+
+use crate::{Chessboard, Move, Side};
+
+/// A move of the solution tree with the continuations that keep solving
+///
+/// Moves of the defending side list every legal reply, moves of the solving side
+/// only the successful ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub m: Move,
+    pub san: String,
+    pub replies: Vec<Self>,
+}
+
+/// All solutions of a problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub stipulation: Stipulation,
+    /// Every first move that solves the problem
+    pub keys: Vec<Line>,
+    side: Side,
+    full_moves: usize,
+}
+
+/// Solve the problem in the position, the side to move plays first
+pub fn solve(cb: &Chessboard, stipulation: Stipulation) -> Solution {
+    let keys = Solver::new(stipulation).keys(cb);
+    Solution {
+        stipulation,
+        keys,
+        side: cb.side(),
+        full_moves: cb.full_moves(),
+    }
+}
+
+impl Solution {
+    pub const fn is_solvable(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// More than one key move solves the problem
+    pub const fn is_cooked(&self) -> bool {
+        self.keys.len() > 1
+    }
+
+    /// One key and no duals
+    pub fn is_sound(&self) -> bool {
+        self.keys.len() == 1 && self.duals().is_empty()
+    }
+
+    /// The key moves in SAN
+    pub fn key_moves(&self) -> Vec<&str> {
+        self.keys.iter().map(|line| line.san.as_str()).collect()
+    }
+
+    /// Every line of the solution tree, like `1. Qh5 Kd8 2. Qe8#`
+    pub fn variations(&self) -> Vec<String> {
+        let mut variations = Vec::new();
+        let mut path = Vec::new();
+        for key in &self.keys {
+            self.collect_variations(key, &mut path, &mut variations);
+        }
+        variations
+    }
+
+    /// Places after the key where the solving side has several ways to go on,
+    /// like `1. Qh5 Kd8 2. Qe8#/Qf7#`
+    ///
+    /// In help play both sides solve, so every branch is reported
+    pub fn duals(&self) -> Vec<String> {
+        let mut duals = Vec::new();
+        let mut path = Vec::new();
+        for key in &self.keys {
+            path.push(key.san.as_str());
+            self.collect_duals(&key.replies, &mut path, &mut duals);
+            path.pop();
+        }
+        duals
+    }
+
+    fn collect_variations<'a>(
+        &self,
+        line: &'a Line,
+        path: &mut Vec<&'a str>,
+        variations: &mut Vec<String>,
+    ) {
+        path.push(&line.san);
+        if line.replies.is_empty() {
+            variations.push(self.movetext(path));
+        }
+        for reply in &line.replies {
+            self.collect_variations(reply, path, variations);
+        }
+        path.pop();
+    }
+
+    fn collect_duals<'a>(
+        &self,
+        lines: &'a [Line],
+        path: &mut Vec<&'a str>,
+        duals: &mut Vec<String>,
+    ) {
+        // Only the moves of the solving side may not branch in direct play
+        let solving = self.stipulation.is_help() || path.len().is_multiple_of(2);
+        if solving && lines.len() > 1 {
+            let moves: Vec<_> = lines.iter().map(|line| line.san.as_str()).collect();
+            let alternatives = moves.join("/");
+            let mut dual = path.clone();
+            dual.push(&alternatives);
+            duals.push(self.movetext(&dual));
+        }
+        for line in lines {
+            path.push(&line.san);
+            self.collect_duals(&line.replies, path, duals);
+            path.pop();
+        }
+    }
+
+    /// Numbered moves starting from the problem position
+    fn movetext(&self, moves: &[&str]) -> String {
+        let mut text = String::new();
+        let mut number = self.full_moves;
+        let mut side = self.side;
+        for (i, san) in moves.iter().enumerate() {
+            match side {
+                Side::White => text.push_str(&format!("{number}. ")),
+                Side::Black if i == 0 => text.push_str(&format!("{number}... ")),
+                Side::Black => (),
+            }
+            text.push_str(san);
+            text.push(' ');
+            if side == Side::Black {
+                number += 1;
+            }
+            side = side.neg();
+        }
+        text.pop();
+        text
+    }
+}
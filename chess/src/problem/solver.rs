@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use super::{Line, Stipulation};
+use crate::{Chessboard, DrawType, GameResult, Move, WinType, book::polyglot_key};
+
+/// Searches the solutions of one stipulation, remembering the solved positions
+pub(super) struct Solver {
+    stipulation: Stipulation,
+    cache: HashMap<(u64, u8), bool>,
+}
+
+impl Solver {
+    pub(super) fn new(stipulation: Stipulation) -> Self {
+        Self {
+            stipulation,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Lines starting with every move that solves the problem
+    pub(super) fn keys(&mut self, cb: &Chessboard) -> Vec<Line> {
+        let moves = self.stipulation.moves();
+        match self.stipulation {
+            Stipulation::HelpMate(_) | Stipulation::HelpStalemate(_) => {
+                self.help_lines(cb, moves * 2)
+            }
+            _ => self.attack_lines(cb, moves),
+        }
+    }
+
+    /// Whether the side to move has reached the goal of the stipulation
+    fn is_goal(&self, cb: &Chessboard) -> bool {
+        let mated = matches!(
+            cb.result,
+            GameResult::WhiteWin(WinType::Checkmate) | GameResult::BlackWin(WinType::Checkmate)
+        );
+        // The fifty-move and material draws override the result of the last move
+        let no_moves = cb.moves_cache.is_empty();
+        match self.stipulation {
+            Stipulation::Stalemate(_) | Stipulation::HelpStalemate(_) => {
+                cb.result == GameResult::Draw(DrawType::Stalemate) || (no_moves && cb.checkers.is_empty())
+            }
+            _ => mated || (no_moves && !cb.checkers.is_empty()),
+        }
+    }
+
+    fn cached(
+        &mut self,
+        cb: &Chessboard,
+        moves: u8,
+        f: fn(&mut Self, &Chessboard, u8) -> bool,
+    ) -> bool {
+        let key = (polyglot_key(cb), moves);
+        if let Some(&solved) = self.cache.get(&key) {
+            return solved;
+        }
+        let solved = f(self, cb, moves);
+        self.cache.insert(key, solved);
+        solved
+    }
+
+    /// The solving side has a move that wins within the number of its moves
+    fn attack(&mut self, cb: &Chessboard, moves: u8) -> bool {
+        self.cached(cb, moves, |solver, cb, moves| {
+            cb.to_vec()
+                .into_iter()
+                .any(|m| solver.defend(&cb.move_new(m), moves))
+        })
+    }
+
+    /// Every move of the defending side loses, the solving side has moves left
+    /// after the one just played
+    fn defend(&mut self, cb: &Chessboard, moves: u8) -> bool {
+        if let Stipulation::SelfMate(_) = self.stipulation {
+            let replies = cb.to_vec();
+            return !replies.is_empty()
+                && replies.into_iter().all(|m| {
+                    let next = cb.move_new(m);
+                    self.is_goal(&next) || (moves > 1 && self.attack(&next, moves - 1))
+                });
+        }
+
+        if self.is_goal(cb) {
+            return true;
+        }
+        let replies = cb.to_vec();
+        moves > 1
+            && !replies.is_empty()
+            && replies
+                .into_iter()
+                .all(|m| self.attack(&cb.move_new(m), moves - 1))
+    }
+
+    /// Both sides cooperate so that the goal is reached exactly after the plies
+    fn help(&mut self, cb: &Chessboard, plies: u8) -> bool {
+        if plies == 0 {
+            return self.is_goal(cb);
+        }
+        self.cached(cb, plies, |solver, cb, plies| {
+            cb.to_vec()
+                .into_iter()
+                .any(|m| solver.help(&cb.move_new(m), plies - 1))
+        })
+    }
+
+    fn attack_lines(&mut self, cb: &Chessboard, moves: u8) -> Vec<Line> {
+        let mut lines = Vec::new();
+        for m in cb.to_vec() {
+            let next = cb.move_new(m);
+            if self.defend(&next, moves) {
+                let replies = self.defense_lines(&next, moves);
+                lines.push(line(cb, m, replies));
+            }
+        }
+        lines
+    }
+
+    /// Every reply of the defending side with the moves that still win against it
+    fn defense_lines(&mut self, cb: &Chessboard, moves: u8) -> Vec<Line> {
+        let self_mate = matches!(self.stipulation, Stipulation::SelfMate(_));
+        if !self_mate && self.is_goal(cb) {
+            return Vec::new();
+        }
+
+        let mut lines = Vec::new();
+        for m in cb.to_vec() {
+            let next = cb.move_new(m);
+            let replies = if self_mate && self.is_goal(&next) {
+                Vec::new()
+            } else {
+                self.attack_lines(&next, moves - 1)
+            };
+            lines.push(line(cb, m, replies));
+        }
+        lines
+    }
+
+    fn help_lines(&mut self, cb: &Chessboard, plies: u8) -> Vec<Line> {
+        let mut lines = Vec::new();
+        if plies == 0 {
+            return lines;
+        }
+        for m in cb.to_vec() {
+            let next = cb.move_new(m);
+            if self.help(&next, plies - 1) {
+                let replies = self.help_lines(&next, plies - 1);
+                lines.push(line(cb, m, replies));
+            }
+        }
+        lines
+    }
+}
+
+fn line(cb: &Chessboard, m: Move, replies: Vec<Line>) -> Line {
+    Line {
+        m,
+        san: cb.to_san(m).unwrap_or_default(),
+        replies,
+    }
+}
@@ -0,0 +1,70 @@
+use crate::ChessError;
+
+/// What the problem asks for, in the number of moves of the solving side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stipulation {
+    /// `#N`, the side to move forces mate
+    Mate(u8),
+    /// `=N`, the side to move forces stalemate of the other side
+    Stalemate(u8),
+    /// `h#N`, the side to move starts and both sides cooperate to mate it
+    HelpMate(u8),
+    /// `h=N`, the side to move starts and both sides cooperate to stalemate it
+    HelpStalemate(u8),
+    /// `s#N`, the side to move forces the other side to mate it
+    SelfMate(u8),
+}
+
+impl Stipulation {
+    pub const fn moves(&self) -> u8 {
+        match self {
+            Self::Mate(n)
+            | Self::Stalemate(n)
+            | Self::HelpMate(n)
+            | Self::HelpStalemate(n)
+            | Self::SelfMate(n) => *n,
+        }
+    }
+
+    /// Whether both sides play for the goal
+    pub const fn is_help(&self) -> bool {
+        matches!(self, Self::HelpMate(_) | Self::HelpStalemate(_))
+    }
+}
+
+impl core::str::FromStr for Stipulation {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, moves) = s
+            .find(|c: char| c.is_ascii_digit())
+            .map(|i| s.split_at(i))
+            .ok_or(ChessError::InvalidStipulation)?;
+        let moves: u8 = moves.parse().map_err(|_| ChessError::InvalidStipulation)?;
+        if moves == 0 {
+            return Err(ChessError::InvalidStipulation);
+        }
+
+        match kind {
+            "#" => Ok(Self::Mate(moves)),
+            "=" => Ok(Self::Stalemate(moves)),
+            "h#" => Ok(Self::HelpMate(moves)),
+            "h=" => Ok(Self::HelpStalemate(moves)),
+            "s#" => Ok(Self::SelfMate(moves)),
+            _ => Err(ChessError::InvalidStipulation),
+        }
+    }
+}
+
+impl core::fmt::Display for Stipulation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let kind = match self {
+            Self::Mate(_) => "#",
+            Self::Stalemate(_) => "=",
+            Self::HelpMate(_) => "h#",
+            Self::HelpStalemate(_) => "h=",
+            Self::SelfMate(_) => "s#",
+        };
+        write!(f, "{kind}{}", self.moves())
+    }
+}
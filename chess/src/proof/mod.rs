@@ -0,0 +1,267 @@
+//! Depth-first proof-number search
+//!
+//! Proves or disproves that the side to move can force mate, without a depth
+//! bound. Nodes where the attacker moves are OR nodes, the defender's are AND
+//! nodes, and the proof and disproof numbers of every visited position are kept
+//! in a transposition table
+
+use std::collections::HashMap;
+
+use crate::{Chessboard, Move, Side, book::polyglot_key};
+
+const INFINITY: u32 = u32::MAX / 2;
+
+/// Outcome of the search for the side to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Proof {
+    /// Forced mate, in plies along the proof which is not always the shortest
+    Mate(u16),
+    /// The defender can avoid mate
+    NoMate,
+    /// The node limit was hit first
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    pn: u32,
+    dn: u32,
+    /// Plies to mate of a proven position
+    distance: u16,
+}
+
+impl Entry {
+    const PROVEN: Self = Self {
+        pn: 0,
+        dn: INFINITY,
+        distance: 0,
+    };
+    const DISPROVEN: Self = Self {
+        pn: INFINITY,
+        dn: 0,
+        distance: 0,
+    };
+}
+
+/// Df-pn search over the positions reachable from the root
+///
+/// Repeating a position of the current line counts as a draw. As the table
+/// doesn't remember the line, such disproofs may be reused elsewhere, so a
+/// `NoMate` found in positions full of repetitions is not always exact
+#[derive(Debug, Clone)]
+pub struct ProofSearch {
+    table: HashMap<u64, Entry>,
+    path: Vec<u64>,
+    attacker: Side,
+    nodes: u64,
+    max_nodes: u64,
+}
+
+impl ProofSearch {
+    pub fn new() -> Self {
+        Self::with_limit(u64::MAX)
+    }
+
+    /// Search that gives up after expanding the number of nodes
+    pub fn with_limit(max_nodes: u64) -> Self {
+        Self {
+            table: HashMap::new(),
+            path: Vec::new(),
+            attacker: Side::White,
+            nodes: 0,
+            max_nodes,
+        }
+    }
+
+    /// Nodes expanded by the last search
+    pub const fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Whether the side to move forces mate
+    pub fn prove(&mut self, cb: &Chessboard) -> Proof {
+        self.table.clear();
+        self.path.clear();
+        self.attacker = cb.side();
+        self.nodes = 0;
+
+        let mut root = self.evaluate(cb);
+        while root.pn != 0 && root.dn != 0 && self.nodes < self.max_nodes {
+            root = self.mid(cb, INFINITY, INFINITY);
+        }
+        match root {
+            Entry {
+                pn: 0, distance, ..
+            } => Proof::Mate(distance),
+            Entry { dn: 0, .. } => Proof::NoMate,
+            _ => Proof::Unknown,
+        }
+    }
+
+    /// The fastest mate against the longest defence, after a proven search
+    pub fn principal_variation(&self, cb: &Chessboard) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut cb = *cb;
+        loop {
+            let attacking = cb.side() == self.attacker;
+            let children = cb.to_vec().into_iter().filter_map(|m| {
+                let child = cb.move_new(m);
+                let entry = self.evaluate(&child);
+                (entry.pn == 0).then_some((m, child, entry.distance))
+            });
+            let best = if attacking {
+                children.min_by_key(|(_, _, distance)| *distance)
+            } else {
+                children.max_by_key(|(_, _, distance)| *distance)
+            };
+            let Some((m, child, _)) = best else {
+                return line;
+            };
+            line.push(m);
+            cb = child;
+        }
+    }
+
+    /// Proof and disproof numbers of a terminal or already searched position
+    fn evaluate(&self, cb: &Chessboard) -> Entry {
+        if cb.moves_cache.is_empty() {
+            // Checked by the result of the move, draws may override the mate
+            return if !cb.checkers.is_empty() && cb.side() != self.attacker {
+                Entry::PROVEN
+            } else {
+                Entry::DISPROVEN
+            };
+        }
+        if cb.result.is_draw() {
+            return Entry::DISPROVEN;
+        }
+
+        let key = polyglot_key(cb);
+        if self.path.contains(&key) {
+            return Entry::DISPROVEN;
+        }
+        // Unseen positions start from the mobility, fewer moves are easier to refute
+        let moves = cb.moves_cache.count() as u32;
+        let unknown = if cb.side() == self.attacker {
+            Entry {
+                pn: 1,
+                dn: moves,
+                distance: 0,
+            }
+        } else {
+            Entry {
+                pn: moves,
+                dn: 1,
+                distance: 0,
+            }
+        };
+        self.table.get(&key).copied().unwrap_or(unknown)
+    }
+
+    /// Multiple iterative deepening: search below the node until one of its
+    /// numbers reaches the threshold
+    fn mid(&mut self, cb: &Chessboard, pn_threshold: u32, dn_threshold: u32) -> Entry {
+        let key = polyglot_key(cb);
+        let attacking = cb.side() == self.attacker;
+        let children: Vec<_> = cb.to_vec().into_iter().map(|m| cb.move_new(m)).collect();
+        self.nodes += 1;
+        self.path.push(key);
+
+        let mut entry;
+        loop {
+            let entries: Vec<_> = children.iter().map(|child| self.evaluate(child)).collect();
+            entry = combine(&entries, attacking);
+            if entry.pn >= pn_threshold || entry.dn >= dn_threshold || self.nodes >= self.max_nodes
+            {
+                break;
+            }
+
+            // The most proving child and the runner-up, in the numbers of this node
+            let delta = |e: &Entry| if attacking { e.pn } else { e.dn };
+            let mut best = 0;
+            let mut second = INFINITY;
+            for (i, e) in entries.iter().enumerate().skip(1) {
+                if delta(e) < delta(&entries[best]) {
+                    second = delta(&entries[best]);
+                    best = i;
+                } else if delta(e) < second {
+                    second = delta(e);
+                }
+            }
+
+            let child = entries[best];
+            let (pn, dn) = if attacking {
+                (
+                    pn_threshold.min(grow(second)),
+                    (dn_threshold - entry.dn)
+                        .saturating_add(child.dn)
+                        .min(INFINITY),
+                )
+            } else {
+                (
+                    (pn_threshold - entry.pn)
+                        .saturating_add(child.pn)
+                        .min(INFINITY),
+                    dn_threshold.min(grow(second)),
+                )
+            };
+            self.mid(&children[best], pn, dn);
+        }
+
+        self.path.pop();
+        self.table.insert(key, entry);
+        entry
+    }
+}
+
+impl Default for ProofSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Numbers of the node from its children, OR nodes need one proof and AND nodes all
+///
+/// The numbers that would be summed are weak, the largest one plus the count of
+/// the other unsolved children, as positions reached by several paths would
+/// otherwise be counted again and again
+fn combine(children: &[Entry], attacking: bool) -> Entry {
+    let weak = |f: fn(&Entry) -> u32| {
+        let max = children.iter().map(f).max().unwrap_or(0);
+        let unsolved = children.iter().filter(|e| f(e) != 0).count() as u32;
+        if max >= INFINITY {
+            INFINITY
+        } else {
+            (max + unsolved.saturating_sub(1)).min(INFINITY - 1)
+        }
+    };
+    let min = |f: fn(&Entry) -> u32| children.iter().map(f).min().unwrap_or(INFINITY);
+    let proven = children.iter().filter(|e| e.pn == 0).map(|e| e.distance);
+
+    if attacking {
+        let pn = min(|e| e.pn);
+        Entry {
+            pn,
+            dn: weak(|e| e.dn),
+            distance: proven.min().map_or(0, |d| d + 1),
+        }
+    } else {
+        let pn = weak(|e| e.pn);
+        Entry {
+            pn,
+            dn: min(|e| e.dn),
+            distance: if pn == 0 {
+                proven.max().map_or(0, |d| d + 1)
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// Threshold for the best child, a bit above the runner-up so the search
+/// doesn't switch between two close children at every step
+const fn grow(second: u32) -> u32 {
+    let grown = second.saturating_add(second / 4).saturating_add(1);
+    if grown < INFINITY { grown } else { INFINITY }
+}
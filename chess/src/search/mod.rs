@@ -0,0 +1,328 @@
+//! Alpha-beta search
+//!
+//! Iterative deepening negamax over an [`Evaluator`], with a capture search at
+//! the leaves. Moves of the previous principal variation are tried first, then
+//! captures by the most valuable victim
+
+use core::cmp::Reverse;
+use std::time::Instant;
+
+use crate::{Chessboard, GameResult, Move, PieceType, book::polyglot_key, eval::Evaluator};
+
+/// Score of a mate at the root, mates further away score less
+pub const MATE: i32 = 30_000;
+/// Deepest ply the search reaches, including the capture search
+const MAX_PLY: usize = 128;
+/// Nodes searched between looks at the time
+const TIME_CHECK: u64 = 1024;
+
+/// When the search stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Complete iterations up to the depth
+    Depth(u32),
+    /// Stop after the number of nodes, keeping the last complete iteration
+    Nodes(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// Centipawns for the side to move, see [`MATE`]
+    pub score: i32,
+    /// Depth of the last complete iteration
+    pub depth: u32,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+}
+
+/// Whether the score is a forced mate for either side
+pub const fn is_mate(score: i32) -> bool {
+    score.abs() >= MATE - MAX_PLY as i32
+}
+
+#[derive(Debug, Clone)]
+pub struct Search<E> {
+    eval: E,
+    /// Keys of the game before the root and of the current line
+    history: Vec<u64>,
+    root_history: usize,
+    pv: Vec<Move>,
+    nodes: u64,
+    max_nodes: u64,
+    deadline: Option<Instant>,
+    stopped: bool,
+}
+
+impl<E: Evaluator> Search<E> {
+    pub const fn new(eval: E) -> Self {
+        Self {
+            eval,
+            history: Vec::new(),
+            root_history: 0,
+            pv: Vec::new(),
+            nodes: 0,
+            max_nodes: u64::MAX,
+            deadline: None,
+            stopped: false,
+        }
+    }
+
+    pub const fn evaluator(&self) -> &E {
+        &self.eval
+    }
+
+    /// Polyglot keys of the positions played before the root, for repetitions
+    pub fn set_history(&mut self, keys: &[u64]) {
+        self.history.clear();
+        self.history.extend_from_slice(keys);
+        self.root_history = keys.len();
+    }
+
+    /// Time at which searches stop whatever the limit, keeping the last
+    /// complete iteration
+    pub const fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    pub fn search(&mut self, cb: &Chessboard, limit: Limit) -> SearchResult {
+        let (max_depth, max_nodes) = match limit {
+            Limit::Depth(depth) => (depth.max(1), u64::MAX),
+            Limit::Nodes(nodes) => (MAX_PLY as u32 / 2, nodes),
+        };
+        self.history.truncate(self.root_history);
+        self.pv.clear();
+        self.nodes = 0;
+        self.max_nodes = max_nodes;
+        self.stopped = false;
+        self.eval.reset(cb);
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        };
+        if !matches!(cb.result, GameResult::None) {
+            return result;
+        }
+
+        for depth in 1..=max_depth {
+            let mut pv = Vec::new();
+            let score = self.negamax(cb, depth, -MATE, MATE, true, &mut pv);
+            if self.stopped {
+                // A partial iteration is only used when none was completed
+                if result.best_move.is_none() && !pv.is_empty() {
+                    result.best_move = pv.first().copied();
+                    result.score = score;
+                    result.pv = pv;
+                }
+                break;
+            }
+            result = SearchResult {
+                best_move: pv.first().copied(),
+                score,
+                depth,
+                nodes: self.nodes,
+                pv: pv.clone(),
+            };
+            self.pv = pv;
+            if is_mate(score) {
+                break;
+            }
+        }
+        if result.best_move.is_none() {
+            result.best_move = ordered_moves(cb).first().map(|&(_, m)| m);
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Score of the capture search and its best line, which ends in a quiet
+    /// position
+    pub fn quiesce(&mut self, cb: &Chessboard) -> (i32, Vec<Move>) {
+        self.history.truncate(self.root_history);
+        self.nodes = 0;
+        self.max_nodes = u64::MAX;
+        self.stopped = false;
+        self.eval.reset(cb);
+
+        let mut pv = Vec::new();
+        let score = self.qsearch(cb, 0, -MATE, MATE, &mut pv);
+        (score, pv)
+    }
+
+    fn negamax(
+        &mut self,
+        cb: &Chessboard,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        on_pv: bool,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        let ply = self.history.len() - self.root_history;
+        if let Some(score) = terminal(cb, ply) {
+            return score;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.qsearch(cb, ply, alpha, beta, pv);
+        }
+        if self.out_of_budget() {
+            self.stopped = true;
+            return 0;
+        }
+        self.nodes += 1;
+
+        let key = polyglot_key(cb);
+        if ply > 0 && self.history.contains(&key) {
+            return 0;
+        }
+        self.history.push(key);
+
+        let mut best = -MATE;
+        let mut child_pv = Vec::new();
+        for m in self.ordered(cb, ply, on_pv) {
+            let child = cb.move_new(m);
+            self.eval.make_move(cb, &child);
+            child_pv.clear();
+            let child_on_pv = on_pv && self.pv.get(ply) == Some(&m);
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha, child_on_pv, &mut child_pv);
+            self.eval.unmake_move();
+            if self.stopped {
+                break;
+            }
+
+            if score > best {
+                best = score;
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(m);
+                    pv.append(&mut child_pv);
+                }
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        self.history.pop();
+        best
+    }
+
+    /// Captures until the position is quiet, or every evasion when in check
+    fn qsearch(
+        &mut self,
+        cb: &Chessboard,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        if let Some(score) = terminal(cb, ply) {
+            return score;
+        }
+        if self.out_of_budget() {
+            self.stopped = true;
+            return 0;
+        }
+        self.nodes += 1;
+
+        let in_check = !cb.checkers.is_empty();
+        let mut best = -MATE;
+        if !in_check || ply >= MAX_PLY {
+            best = self.eval.evaluate(cb);
+            if best >= beta || ply >= MAX_PLY {
+                return best;
+            }
+            alpha = alpha.max(best);
+        }
+
+        let mut child_pv = Vec::new();
+        for (value, m) in ordered_moves(cb) {
+            if value.is_none() && !in_check {
+                break;
+            }
+            let child = cb.move_new(m);
+            self.eval.make_move(cb, &child);
+            child_pv.clear();
+            let score = -self.qsearch(&child, ply + 1, -beta, -alpha, &mut child_pv);
+            self.eval.unmake_move();
+            if self.stopped {
+                break;
+            }
+
+            best = best.max(score);
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(m);
+                pv.append(&mut child_pv);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    fn out_of_budget(&self) -> bool {
+        self.nodes >= self.max_nodes
+            || (self.nodes.is_multiple_of(TIME_CHECK)
+                && self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline))
+    }
+
+    /// Moves in search order, the move of the last principal variation first
+    fn ordered(&self, cb: &Chessboard, ply: usize, on_pv: bool) -> Vec<Move> {
+        let mut moves: Vec<_> = ordered_moves(cb).into_iter().map(|(_, m)| m).collect();
+        if on_pv
+            && let Some(&pv_move) = self.pv.get(ply)
+            && let Some(i) = moves.iter().position(|&m| m == pv_move)
+        {
+            moves[..=i].rotate_right(1);
+        }
+        moves
+    }
+}
+
+/// Score of a finished game, the side to move is the one mated
+const fn terminal(cb: &Chessboard, ply: usize) -> Option<i32> {
+    match cb.result {
+        GameResult::None => None,
+        GameResult::WhiteWin(_) | GameResult::BlackWin(_) => Some(-MATE + ply as i32),
+        _ => Some(0),
+    }
+}
+
+/// Legal moves with the value of the captured piece, captures first by the
+/// most valuable victim and then the least valuable attacker
+fn ordered_moves(cb: &Chessboard) -> Vec<(Option<i32>, Move)> {
+    const VALUES: [i32; PieceType::LEN] = [1, 5, 3, 3, 9, 0];
+    let value = |piece_type: PieceType| VALUES[piece_type as usize];
+    let mut moves: Vec<_> = cb
+        .to_vec()
+        .into_iter()
+        .map(|m| {
+            let gain = match m {
+                Move::Standard { piece, to, .. } => cb
+                    .at(to)
+                    .map(|victim| 10 * value(victim.piece_type()) - value(piece.piece_type())),
+                Move::EnPassant { .. } => {
+                    Some(10 * value(PieceType::Pawn) - value(PieceType::Pawn))
+                }
+                Move::Promotion { to, promotion, .. } => Some(
+                    10 * (value(promotion.piece_type())
+                        + cb.at(to).map_or(0, |victim| value(victim.piece_type()))),
+                ),
+                Move::Castling { .. } | Move::Put { .. } | Move::Remove { .. } | Move::Null => None,
+            };
+            (gain, m)
+        })
+        .collect();
+    moves.sort_by_key(|&(gain, _)| Reverse(gain));
+    moves
+}
@@ -0,0 +1,500 @@
+//! Local HTTP server answering JSON
+//!
+//! Every endpoint takes a JSON body by `POST`, with the position as a `fen`
+//! (the starting position by default) and `moves` played from it in UCI or
+//! SAN:
+//!
+//! - `/validate`: whether the FEN is valid, with the side to move and the result
+//! - `/moves`: legal moves of the position
+//! - `/play`: position after the moves, with their outcomes
+//! - `/result`: result of the game after the moves
+//! - `/perft`: leaf nodes at the `depth`, by first move
+//! - `/search`: best move found in `time_ms` milliseconds, or to the `depth`
+//!
+//! Failures answer an `error` message, with the [`IllegalMoveReason`] as
+//! `reason` when a move cannot be played
+//!
+//! Requests are read by a small HTTP/1.1 reader, each connection on its own
+//! thread and closed after the answer. It is meant for localhost during
+//! development, not for the open internet
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ChessError, Chessboard, GameResult, IllegalMoveReason, Move, MoveGen, MoveOutcome, Piece,
+    START_POS, Side, Square,
+    eval::HandCrafted,
+    game::Game,
+    perft,
+    search::{Limit, MATE, Search, is_mate},
+    uci::format_move,
+};
+
+/// Largest request body read
+const MAX_BODY: usize = 64 * 1024;
+/// Time a client gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deepest perft computed
+const MAX_PERFT_DEPTH: u32 = 6;
+/// Search time without `time_ms` and `depth`
+const DEFAULT_SEARCH_TIME: Duration = Duration::from_secs(1);
+/// Longest search
+const MAX_SEARCH_TIME: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    /// Listen on the address, like `127.0.0.1:8080`
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        TcpListener::bind(addr).map(|listener| Self { listener })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answer connections for as long as the process runs
+    pub fn run(&self) {
+        for stream in self.listener.incoming().flatten() {
+            thread::spawn(move || {
+                let _ = serve(stream);
+            });
+        }
+    }
+}
+
+/// Fields of the request bodies, each endpoint reading its own
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Request {
+    fen: Option<String>,
+    moves: Vec<String>,
+    depth: Option<u32>,
+    time_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct MoveJson {
+    uci: String,
+    san: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Validation {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fen: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    side: Option<Side>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<GameResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct Moves {
+    fen: String,
+    moves: Vec<MoveJson>,
+    result: GameResult,
+}
+
+#[derive(Debug, Serialize)]
+struct PlayedMove {
+    uci: String,
+    san: String,
+    outcome: MoveOutcome,
+}
+
+#[derive(Debug, Serialize)]
+struct Played {
+    fen: String,
+    moves: Vec<PlayedMove>,
+    result: GameResult,
+}
+
+#[derive(Debug, Serialize)]
+struct Outcome {
+    result: GameResult,
+    over: bool,
+    /// Value of the PGN `Result` tag
+    pgn: &'static str,
+    termination: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Divided {
+    uci: String,
+    nodes: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Perft {
+    depth: u32,
+    nodes: usize,
+    moves: Vec<Divided>,
+}
+
+#[derive(Debug, Serialize)]
+struct Searched {
+    best_move: Option<MoveJson>,
+    /// Centipawns for the side to move
+    score: i32,
+    /// Moves to mate, negative when the side to move is mated
+    mate: Option<i32>,
+    depth: u32,
+    nodes: u64,
+    pv: Vec<MoveJson>,
+    time_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+struct Failure {
+    error: String,
+    /// Why a move of the request cannot be played
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<IllegalMoveReason>,
+}
+
+impl From<String> for Failure {
+    fn from(error: String) -> Self {
+        Self {
+            error,
+            reason: None,
+        }
+    }
+}
+
+impl From<&str> for Failure {
+    fn from(error: &str) -> Self {
+        Self::from(error.to_string())
+    }
+}
+
+/// Read the request and answer it, closing the connection
+fn serve(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let (status, body) = match read_request(&mut reader) {
+        Ok((method, path, body)) => respond(&method, &path, &body),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => (413, failure("Body is too large")),
+        Err(_) => (400, failure("Malformed request")),
+    };
+
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Payload Too Large",
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Method, path without the query and body of the request
+fn read_request(reader: &mut impl BufRead) -> io::Result<(String, String, String)> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    };
+    let path = target.split('?').next().unwrap_or(target);
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value
+                .trim()
+                .parse()
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        }
+    }
+    if length > MAX_BODY {
+        return Err(io::Error::from(io::ErrorKind::InvalidData));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    Ok((method, path, body))
+}
+
+/// Status and JSON body answering the request
+fn respond(method: &str, path: &str, body: &str) -> (u16, String) {
+    let route = match path {
+        "/validate" => validate,
+        "/moves" => moves,
+        "/play" => play,
+        "/result" => result,
+        "/perft" => perft_divide,
+        "/search" => search,
+        _ => return (404, failure(&format!("No endpoint {path}"))),
+    };
+    match method {
+        "OPTIONS" => return (204, String::new()),
+        "POST" => (),
+        _ => return (405, failure(&format!("{path} takes POST"))),
+    }
+    let body = if body.trim().is_empty() { "{}" } else { body };
+    let answer = serde_json::from_str(body)
+        .map_err(|e| Failure::from(format!("Invalid request: {e}")))
+        .and_then(|request| route(&request));
+    match answer {
+        Ok(json) => (200, json),
+        Err(failure) => (400, json(&failure)),
+    }
+}
+
+fn failure(error: &str) -> String {
+    json(&Failure::from(error))
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn validate(request: &Request) -> Result<String, Failure> {
+    let validation = match Chessboard::from_fen(request.fen.as_deref().unwrap_or(START_POS)) {
+        Ok(cb) => Validation {
+            valid: true,
+            error: None,
+            fen: Some(cb.get_fen()),
+            side: Some(cb.side()),
+            check: Some(!cb.checkers.is_empty()),
+            result: Some(cb.result),
+        },
+        Err(e) => Validation {
+            valid: false,
+            error: Some(format!("{e:?}")),
+            fen: None,
+            side: None,
+            check: None,
+            result: None,
+        },
+    };
+    Ok(json(&validation))
+}
+
+fn moves(request: &Request) -> Result<String, Failure> {
+    let game = game(request)?;
+    let mut cb = *game.position();
+    let moves = if game.is_over() {
+        Vec::new()
+    } else {
+        let mut move_gen = MoveGen::new(cb.generate_moves());
+        let mut moves = Vec::new();
+        while let Some(m) = move_gen.next_const() {
+            moves.extend(move_json(&cb, m));
+        }
+        moves
+    };
+    Ok(json(&Moves {
+        fen: cb.get_fen(),
+        moves,
+        result: game.result(),
+    }))
+}
+
+fn play(request: &Request) -> Result<String, Failure> {
+    let mut game = start(request)?;
+    let mut moves = Vec::new();
+    for text in &request.moves {
+        let cb = *game.position();
+        let m = parse_move(&cb, text)?;
+        let outcome = game.make_move(m).map_err(|e| cannot_play(text, e))?;
+        let Some(MoveJson { uci, san }) = move_json(&cb, m) else {
+            return Err(format!("Cannot play {text}").into());
+        };
+        moves.push(PlayedMove { uci, san, outcome });
+    }
+    Ok(json(&Played {
+        fen: game.position().get_fen(),
+        moves,
+        result: game.result(),
+    }))
+}
+
+fn result(request: &Request) -> Result<String, Failure> {
+    let result = game(request)?.result();
+    Ok(json(&Outcome {
+        result,
+        over: result.is_over(),
+        pgn: result.pgn_result(),
+        termination: result.termination().as_str(),
+    }))
+}
+
+fn perft_divide(request: &Request) -> Result<String, Failure> {
+    let depth = request.depth.ok_or("perft needs a depth")?;
+    if !(1..=MAX_PERFT_DEPTH).contains(&depth) {
+        return Err(format!("Depth has to be from 1 to {MAX_PERFT_DEPTH}").into());
+    }
+    let mut cb = *game(request)?.position();
+    let mut moves = Vec::new();
+    if !cb.result.is_over() {
+        let mut move_gen = MoveGen::new(cb.generate_moves());
+        while let Some(m) = move_gen.next_const() {
+            let nodes = match depth {
+                1 => 1,
+                _ => perft(&mut cb.move_new(m), depth - 1),
+            };
+            moves.push(Divided {
+                uci: format_move(m).unwrap_or_default(),
+                nodes,
+            });
+        }
+    }
+    Ok(json(&Perft {
+        depth,
+        nodes: moves.iter().map(|m| m.nodes).sum(),
+        moves,
+    }))
+}
+
+fn search(request: &Request) -> Result<String, Failure> {
+    let game = game(request)?;
+    let cb = *game.position();
+    let time = match (request.time_ms, request.depth) {
+        (Some(ms), _) => Duration::from_millis(ms).min(MAX_SEARCH_TIME),
+        (None, Some(_)) => MAX_SEARCH_TIME,
+        (None, None) => DEFAULT_SEARCH_TIME,
+    };
+    let limit = request.depth.map_or(Limit::Nodes(u64::MAX), Limit::Depth);
+
+    let start = Instant::now();
+    let mut search = Search::new(HandCrafted::new());
+    search.set_history(game.history());
+    search.set_deadline(Some(start + time));
+    let result = search.search(&cb, limit);
+
+    let mut position = cb;
+    let pv = result
+        .pv
+        .iter()
+        .filter_map(|&m| {
+            let json = move_json(&position, m);
+            position.make_move(m);
+            json
+        })
+        .collect();
+    let mate = is_mate(result.score).then(|| {
+        let moves = (MATE - result.score.abs() + 1) / 2;
+        moves * result.score.signum()
+    });
+    Ok(json(&Searched {
+        best_move: result.best_move.and_then(|m| move_json(&cb, m)),
+        score: result.score,
+        mate,
+        depth: result.depth,
+        nodes: result.nodes,
+        pv,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
+
+/// Game from the FEN, without the moves
+fn start(request: &Request) -> Result<Game, Failure> {
+    Game::from_fen(request.fen.as_deref().unwrap_or(START_POS))
+        .map_err(|e| format!("Invalid FEN: {e:?}").into())
+}
+
+/// Game from the FEN, after the moves
+fn game(request: &Request) -> Result<Game, Failure> {
+    let mut game = start(request)?;
+    for text in &request.moves {
+        let m = parse_move(game.position(), text)?;
+        game.make_move(m).map_err(|e| cannot_play(text, e))?;
+    }
+    Ok(game)
+}
+
+/// Legal move in UCI or SAN
+fn parse_move(cb: &Chessboard, text: &str) -> Result<Move, Failure> {
+    MoveGen::new(cb.moves_cache)
+        .from_str(cb, text)
+        .filter(|&m| m != Move::Null)
+        .or_else(|| cb.from_san(text))
+        .ok_or_else(|| Failure {
+            error: format!("Illegal move {text}"),
+            reason: Some(illegal_reason(cb, text)),
+        })
+}
+
+/// Why the text is no legal move, told by the squares of UCI text and
+/// [`IllegalMoveReason::InvalidMovement`] for anything else
+fn illegal_reason(cb: &Chessboard, text: &str) -> IllegalMoveReason {
+    let square = |range| text.get(range).and_then(|sq| Square::try_from(sq).ok());
+    let (Some(from), Some(to)) = (square(0..2), square(2..4)) else {
+        return IllegalMoveReason::InvalidMovement;
+    };
+    let Some(piece) = cb.at(from) else {
+        return IllegalMoveReason::NoPieceOnOrigin;
+    };
+    let promotion = text[4..].chars().next().and_then(Piece::from_char);
+    let m = promotion.map_or(Move::Standard { piece, from, to }, |promotion| {
+        Move::Promotion {
+            from,
+            to,
+            promotion: Piece::from_side_and_type(piece.side(), promotion.piece_type()),
+        }
+    });
+    cb.check_move(m)
+        .err()
+        .unwrap_or(IllegalMoveReason::InvalidMovement)
+}
+
+/// Failure of a parsed move, with the reason when it is illegal
+fn cannot_play(text: &str, error: ChessError) -> Failure {
+    match error {
+        ChessError::IllegalMove(reason) => Failure {
+            error: format!("Cannot play {text}: {reason}"),
+            reason: Some(reason),
+        },
+        ChessError::GameOver => format!("Cannot play {text}: the game is over").into(),
+        _ => format!("Cannot play {text}").into(),
+    }
+}
+
+fn move_json(cb: &Chessboard, m: Move) -> Option<MoveJson> {
+    Some(MoveJson {
+        uci: format_move(m)?,
+        san: cb.to_san(m)?,
+    })
+}
@@ -0,0 +1,140 @@
+//! Index tables shared by all Syzygy tables
+
+/// Rank minus file of the square, zero on the a1-h8 diagonal
+pub(super) const fn off_diagonal(sq: usize) -> i32 {
+    (sq >> 3) as i32 - (sq & 7) as i32
+}
+
+const fn kings_touch(a: usize, b: usize) -> bool {
+    (a & 7).abs_diff(b & 7) <= 1 && (a >> 3).abs_diff(b >> 3) <= 1
+}
+
+/// Squares below the a1-h8 diagonal mapped to `0..28`
+pub(super) const MAP_B1H1H7: [usize; 64] = {
+    let mut map = [0; 64];
+    let mut code = 0;
+    let mut sq = 0;
+    while sq < 64 {
+        if off_diagonal(sq) < 0 {
+            map[sq] = code;
+            code += 1;
+        }
+        sq += 1;
+    }
+    map
+};
+
+/// Squares of the a1-d1-d4 triangle mapped to `0..10`, diagonal squares last
+pub(super) const MAP_A1D1D4: [usize; 64] = {
+    let mut map = [0; 64];
+    let mut code = 0;
+    let mut sq = 0;
+    while sq <= 27 {
+        if off_diagonal(sq) < 0 && sq & 7 <= 3 {
+            map[sq] = code;
+            code += 1;
+        }
+        sq += 1;
+    }
+    sq = 0;
+    while sq <= 27 {
+        if off_diagonal(sq) == 0 && sq & 7 <= 3 {
+            map[sq] = code;
+            code += 1;
+        }
+        sq += 1;
+    }
+    map
+};
+
+/// The 462 legal placements of two kings with the first one in the a1-d1-d4 triangle
+pub(super) const MAP_KK: [[usize; 64]; 10] = {
+    let mut map = [[0; 64]; 10];
+    let mut code = 0;
+
+    // Placements with both kings on the diagonal are encoded last
+    let mut pass = 0;
+    while pass < 2 {
+        let mut idx = 0;
+        while idx < 10 {
+            let mut s1 = 0;
+            while s1 <= 27 {
+                if MAP_A1D1D4[s1] == idx && (idx != 0 || s1 == 1) {
+                    let mut s2 = 0;
+                    while s2 < 64 {
+                        let on_diagonal = off_diagonal(s1) == 0;
+                        let both = on_diagonal && off_diagonal(s2) == 0;
+                        let legal = !kings_touch(s1, s2) && !(on_diagonal && off_diagonal(s2) > 0);
+                        if legal && both == (pass == 1) {
+                            map[idx][s2] = code;
+                            code += 1;
+                        }
+                        s2 += 1;
+                    }
+                }
+                s1 += 1;
+            }
+            idx += 1;
+        }
+        pass += 1;
+    }
+    map
+};
+
+/// `BINOMIAL[k][n]` ways to choose `k` squares out of `n`
+pub(super) const BINOMIAL: [[u64; 64]; 7] = {
+    let mut table = [[0; 64]; 7];
+    table[0][0] = 1;
+    let mut n = 1;
+    while n < 64 {
+        let mut k = 0;
+        while k < 7 && k <= n {
+            let with = if k > 0 { table[k - 1][n - 1] } else { 0 };
+            let without = if k < n { table[k][n - 1] } else { 0 };
+            table[k][n] = with + without;
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+type PawnTables = ([usize; 64], [[u64; 64]; 6], [[u64; 4]; 6]);
+
+const PAWN_TABLES: PawnTables = {
+    let mut map_pawns = [0; 64];
+    let mut lead_idx = [[0; 64]; 6];
+    let mut lead_size = [[0; 4]; 6];
+
+    let mut available: usize = 47;
+    let mut count = 1;
+    while count <= 5 {
+        let mut file = 0;
+        while file < 4 {
+            let mut idx = 0;
+            let mut rank = 1;
+            while rank <= 6 {
+                let sq = file + 8 * rank;
+                if count == 1 {
+                    map_pawns[sq] = available;
+                    map_pawns[sq ^ 7] = available - 1;
+                    available = available.saturating_sub(2);
+                }
+                lead_idx[count][sq] = idx;
+                idx += BINOMIAL[count - 1][map_pawns[sq]];
+                rank += 1;
+            }
+            lead_size[count][file] = idx;
+            file += 1;
+        }
+        count += 1;
+    }
+    (map_pawns, lead_idx, lead_size)
+};
+
+/// Pawn squares mapped to `0..48`, the leading pawn has the highest value
+pub(super) const MAP_PAWNS: [usize; 64] = PAWN_TABLES.0;
+/// Index of the leading pawn group by the number of pawns and the leading square
+pub(super) const LEAD_PAWN_IDX: [[u64; 64]; 6] = PAWN_TABLES.1;
+/// Number of leading pawn placements by the number of pawns and the file
+pub(super) const LEAD_PAWNS_SIZE: [[u64; 4]; 6] = PAWN_TABLES.2;
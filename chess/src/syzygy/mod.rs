@@ -0,0 +1,477 @@
+//! Syzygy endgame tablebase probing
+//!
+//! WDL (`.rtbw`) and DTZ (`.rtbz`) files are read from local directories and
+//! loaded lazily the first time a table is probed
+
+mod encoding;
+mod table;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use table::{MAX_PIECES, Table};
+
+use crate::{Chessboard, Move, Piece, PieceType, Side};
+
+const MAX_DTZ: i32 = 1 << 18;
+
+/// Outcome of the position for the side to move
+///
+/// Cursed wins and blessed losses are draws under the fifty-move rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    const fn from_i8(value: i8) -> Self {
+        match value {
+            ..=-2 => Self::Loss,
+            -1 => Self::BlessedLoss,
+            0 => Self::Draw,
+            1 => Self::CursedWin,
+            2.. => Self::Win,
+        }
+    }
+
+    pub const fn as_i8(&self) -> i8 {
+        match self {
+            Self::Loss => -2,
+            Self::BlessedLoss => -1,
+            Self::Draw => 0,
+            Self::CursedWin => 1,
+            Self::Win => 2,
+        }
+    }
+
+    /// Outcome from the other side's point of view
+    pub const fn neg(&self) -> Self {
+        Self::from_i8(-self.as_i8())
+    }
+
+    /// Outcome of the DTZ value with the half-move clock already spent
+    pub const fn from_dtz(dtz: i32, half_moves: usize) -> Self {
+        let half_moves = half_moves as i32;
+        if dtz > 0 {
+            if dtz + half_moves <= 100 {
+                Self::Win
+            } else {
+                Self::CursedWin
+            }
+        } else if dtz < 0 {
+            if -dtz + half_moves <= 100 {
+                Self::Loss
+            } else {
+                Self::BlessedLoss
+            }
+        } else {
+            Self::Draw
+        }
+    }
+
+    const fn signum(&self) -> i32 {
+        self.as_i8().signum() as i32
+    }
+
+    /// DTZ of the position where the best move zeroes the half-move clock
+    const fn dtz_before_zeroing(&self) -> i32 {
+        match self {
+            Self::Loss => -1,
+            Self::BlessedLoss => -101,
+            Self::Draw => 0,
+            Self::CursedWin => 101,
+            Self::Win => 1,
+        }
+    }
+}
+
+/// Errors of the tablebase probing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProbeError {
+    /// No table for the material of the position
+    MissingTable,
+    /// Tables do not cover positions with castling rights
+    CastlingRights,
+    /// The table file is corrupted
+    InvalidTable,
+}
+
+/// Legal root move ranked by the tablebase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootMove {
+    pub m: Move,
+    /// Distance to zeroing in plies after the move, counted from the root
+    pub dtz: i32,
+    /// Higher is better, moves with the same rank preserve the same outcome
+    pub rank: i32,
+}
+
+/// Count of pieces by side and type in the order of the table names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Material {
+    /// Pawn, knight, bishop, rook, queen and king of each side
+    counts: [[u8; 6]; 2],
+}
+
+impl Material {
+    const NAME_ORDER: [(char, usize); 6] =
+        [('K', 5), ('Q', 4), ('R', 3), ('B', 2), ('N', 1), ('P', 0)];
+
+    fn from_board(cb: &Chessboard) -> Self {
+        let mut counts = [[0; 6]; 2];
+        for side in [Side::White, Side::Black] {
+            for (kind, piece_type) in [
+                (0, PieceType::Pawn),
+                (1, PieceType::Knight),
+                (2, PieceType::Bishop),
+                (3, PieceType::Rook),
+                (4, PieceType::Queen),
+                (5, PieceType::King),
+            ] {
+                let piece = Piece::from_side_and_type(side, piece_type);
+                counts[side.as_u8() as usize][kind] = cb.get_pieces(piece).popcnt() as u8;
+            }
+        }
+        Self { counts }
+    }
+
+    /// Parse the name like `KRvKP`
+    fn from_name(name: &str) -> Option<Self> {
+        let sides: [&str; 2] = name.split_once('v')?.into();
+        let mut counts = [[0; 6]; 2];
+        for (side, pieces) in sides.into_iter().enumerate() {
+            if !pieces.starts_with('K') {
+                return None;
+            }
+            for c in pieces.chars() {
+                let (_, kind) = Self::NAME_ORDER.iter().find(|(name, _)| *name == c)?;
+                counts[side][*kind] += 1;
+            }
+        }
+
+        let material = Self { counts };
+        if material.counts[0][5] != 1 || material.counts[1][5] != 1 {
+            return None;
+        }
+        Some(material)
+    }
+
+    fn name(&self) -> String {
+        let mut name = String::new();
+        for (side, counts) in self.counts.iter().enumerate() {
+            if side == 1 {
+                name.push('v');
+            }
+            for (c, kind) in Self::NAME_ORDER {
+                for _ in 0..counts[kind] {
+                    name.push(c);
+                }
+            }
+        }
+        name
+    }
+
+    const fn flipped(&self) -> Self {
+        Self {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    const fn side(&self, side: Side) -> [u8; 6] {
+        self.counts[side.as_u8() as usize]
+    }
+
+    fn count(&self) -> usize {
+        self.counts.iter().flatten().map(|&c| c as usize).sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct TableFiles {
+    material: Option<Material>,
+    wdl: Option<PathBuf>,
+    dtz: Option<PathBuf>,
+    wdl_table: OnceLock<Result<Table, ProbeError>>,
+    dtz_table: OnceLock<Result<Table, ProbeError>>,
+}
+
+/// Collection of Syzygy tables
+#[derive(Debug, Default)]
+pub struct Tablebase {
+    tables: HashMap<String, TableFiles>,
+    max_pieces: usize,
+}
+
+impl Tablebase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the tablebase from the tables in the directory
+    pub fn open(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut tablebase = Self::new();
+        tablebase.add_directory(dir)?;
+        Ok(tablebase)
+    }
+
+    /// Register the tables found in the directory
+    ///
+    /// Returns the number of table files added
+    pub fn add_directory(&mut self, dir: impl AsRef<Path>) -> std::io::Result<usize> {
+        let mut added = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let (Some(stem), Some(extension)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            let Some(material) = Material::from_name(stem) else {
+                continue;
+            };
+            if material.count() > MAX_PIECES {
+                continue;
+            }
+
+            let files = self.tables.entry(material.name()).or_default();
+            match extension {
+                "rtbw" => files.wdl = Some(path.clone()),
+                "rtbz" => files.dtz = Some(path.clone()),
+                _ => continue,
+            }
+            files.material = Some(material);
+            self.max_pieces = self.max_pieces.max(material.count());
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Largest number of pieces covered by the tables
+    pub const fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Outcome of the position ignoring the fifty-move rule
+    ///
+    /// Captures, including en passant, are resolved by a search
+    pub fn probe_wdl(&self, cb: &Chessboard) -> Result<Wdl, ProbeError> {
+        Self::check_castling(cb)?;
+        Ok(self.search(cb, false)?.0)
+    }
+
+    /// Distance in plies to the next capture or pawn move of the best play
+    ///
+    /// Positive for a win and negative for a loss, values beyond 100 are
+    /// cursed wins and blessed losses. Zero for a draw
+    pub fn probe_dtz(&self, cb: &Chessboard) -> Result<i32, ProbeError> {
+        Self::check_castling(cb)?;
+        self.dtz(cb)
+    }
+
+    /// Outcome of the position with the half-move clock of the board
+    ///
+    /// A win that needs more than the remaining half moves is a cursed win
+    pub fn probe_outcome(&self, cb: &Chessboard) -> Result<Wdl, ProbeError> {
+        let wdl = self.probe_wdl(cb)?;
+        if matches!(wdl, Wdl::Draw) {
+            return Ok(wdl);
+        }
+        let dtz = self.probe_dtz(cb)?;
+        Ok(Wdl::from_dtz(dtz, cb.half_moves()))
+    }
+
+    /// Rank all legal moves, best first
+    pub fn root_moves(&self, cb: &Chessboard) -> Result<Vec<RootMove>, ProbeError> {
+        Self::check_castling(cb)?;
+        let half_moves = cb.half_moves() as i32;
+
+        let mut moves = Vec::new();
+        for m in cb.to_vec() {
+            let next = cb.move_new(m);
+            let mut dtz = if next.half_moves() == 0 {
+                self.search(&next, false)?.0.neg().dtz_before_zeroing()
+            } else if next.half_moves() >= 100 && !is_checkmate(&next) {
+                // The fifty-move rule draws the game
+                0
+            } else {
+                let dtz = -self.dtz(&next)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && is_checkmate(&next) {
+                dtz = 1;
+            }
+
+            let rank = if dtz > 0 {
+                if dtz + half_moves <= 99 {
+                    MAX_DTZ
+                } else {
+                    MAX_DTZ / 2 - (dtz + half_moves)
+                }
+            } else if dtz < 0 {
+                if -dtz * 2 + half_moves < 100 {
+                    -MAX_DTZ
+                } else {
+                    -MAX_DTZ / 2 + (-dtz + half_moves)
+                }
+            } else {
+                0
+            };
+            moves.push(RootMove { m, dtz, rank });
+        }
+
+        moves.sort_by_key(|root| std::cmp::Reverse(root.rank));
+        Ok(moves)
+    }
+
+    /// Moves that keep the best outcome reachable under the fifty-move rule
+    pub fn filter_root_moves(&self, cb: &Chessboard) -> Result<Vec<Move>, ProbeError> {
+        let moves = self.root_moves(cb)?;
+        let Some(best) = moves.first().map(|root| root.rank) else {
+            return Ok(Vec::new());
+        };
+        Ok(moves
+            .into_iter()
+            .filter(|root| root.rank == best)
+            .map(|root| root.m)
+            .collect())
+    }
+
+    const fn check_castling(cb: &Chessboard) -> Result<(), ProbeError> {
+        if cb.castling().as_u8() != 0 {
+            return Err(ProbeError::CastlingRights);
+        }
+        Ok(())
+    }
+
+    /// Resolve captures, and pawn moves for DTZ, before looking into the table
+    ///
+    /// Returns the outcome and whether the best move zeroes the half-move clock
+    fn search(&self, cb: &Chessboard, pawn_moves: bool) -> Result<(Wdl, bool), ProbeError> {
+        let moves = cb.to_vec();
+        let mut searched = 0;
+        let mut best = Wdl::Loss;
+
+        for &m in &moves {
+            let next = cb.move_new(m);
+            let capture = next.occupied().popcnt() < cb.occupied().popcnt();
+            if !capture && !(pawn_moves && next.half_moves() == 0) {
+                continue;
+            }
+            searched += 1;
+
+            let value = self.search(&next, false)?.0.neg();
+            if value > best {
+                best = value;
+                if matches!(value, Wdl::Win) {
+                    return Ok((value, true));
+                }
+            }
+        }
+
+        // Tables may store anything for positions won by a capture
+        let all_searched = searched > 0 && searched == moves.len();
+        let value = if all_searched {
+            best
+        } else {
+            self.probe_wdl_table(cb)?
+        };
+
+        if best >= value {
+            return Ok((best, best > Wdl::Draw || all_searched));
+        }
+        Ok((value, false))
+    }
+
+    fn dtz(&self, cb: &Chessboard) -> Result<i32, ProbeError> {
+        let (wdl, zeroing) = self.search(cb, true)?;
+        if matches!(wdl, Wdl::Draw) {
+            return Ok(0);
+        }
+        if zeroing {
+            return Ok(wdl.dtz_before_zeroing());
+        }
+
+        if let Some(dtz) = self.probe_dtz_table(cb, wdl)? {
+            let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+            return Ok((dtz + 100 * cursed as i32) * wdl.signum());
+        }
+
+        // The table stores the other side to move, look one ply ahead
+        let mut min_dtz = i32::MAX;
+        for m in cb.to_vec() {
+            let next = cb.move_new(m);
+            let zeroing = next.half_moves() == 0;
+
+            let mut dtz = if zeroing {
+                -self.search(&next, false)?.0.dtz_before_zeroing()
+            } else {
+                -self.dtz(&next)?
+            };
+            if dtz == 1 && is_checkmate(&next) {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+
+        Ok(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    fn probe_wdl_table(&self, cb: &Chessboard) -> Result<Wdl, ProbeError> {
+        if cb.occupied().popcnt() == 2 {
+            return Ok(Wdl::Draw);
+        }
+        let (files, flipped) = self.find(cb)?;
+        let table = Self::load(&files.wdl_table, files.wdl.as_deref(), files, false)?;
+        table.wdl(cb, flipped)
+    }
+
+    fn probe_dtz_table(&self, cb: &Chessboard, wdl: Wdl) -> Result<Option<i32>, ProbeError> {
+        let (files, flipped) = self.find(cb)?;
+        let table = Self::load(&files.dtz_table, files.dtz.as_deref(), files, true)?;
+        table.dtz(cb, flipped, wdl)
+    }
+
+    /// Files of the material, flipped when black has the material listed first
+    fn find(&self, cb: &Chessboard) -> Result<(&TableFiles, bool), ProbeError> {
+        let material = Material::from_board(cb);
+        if let Some(files) = self.tables.get(&material.name()) {
+            return Ok((files, false));
+        }
+        self.tables
+            .get(&material.flipped().name())
+            .map(|files| (files, true))
+            .ok_or(ProbeError::MissingTable)
+    }
+
+    fn load<'a>(
+        cell: &'a OnceLock<Result<Table, ProbeError>>,
+        path: Option<&Path>,
+        files: &TableFiles,
+        dtz: bool,
+    ) -> Result<&'a Table, ProbeError> {
+        cell.get_or_init(|| {
+            let (Some(path), Some(material)) = (path, files.material) else {
+                return Err(ProbeError::MissingTable);
+            };
+            let bytes = std::fs::read(path).map_err(|_| ProbeError::MissingTable)?;
+            Table::new(bytes, &material, dtz)
+        })
+        .as_ref()
+        .map_err(|e| *e)
+    }
+}
+
+const fn is_checkmate(cb: &Chessboard) -> bool {
+    !cb.checkers.is_empty() && cb.moves_cache.is_empty()
+}
@@ -0,0 +1,708 @@
+//! Parsing and decompression of a single Syzygy table file
+
+use super::encoding::{
+    BINOMIAL, LEAD_PAWN_IDX, LEAD_PAWNS_SIZE, MAP_A1D1D4, MAP_B1H1H7, MAP_KK, MAP_PAWNS,
+    off_diagonal,
+};
+use super::{Material, ProbeError, Wdl};
+use crate::{Bitboard, Chessboard, Piece, PieceType, Side};
+
+pub(super) const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+pub(super) const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+pub(super) const MAX_PIECES: usize = 7;
+
+// Flags of the pairs data, all but the last one are used by DTZ tables only
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+const HEADER_HAS_PAWNS: u8 = 2;
+
+/// Huffman decoding data of one side and leading file
+///
+/// Positions are offsets into the table file
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    min_sym_len: u8,
+    block_size: usize,
+    span: u64,
+    num_blocks: usize,
+    block_length_size: usize,
+    sparse_index_size: usize,
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u8>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+}
+
+/// WDL or DTZ table loaded into memory
+#[derive(Debug)]
+pub(super) struct Table {
+    bytes: Vec<u8>,
+    dtz: bool,
+    symmetric: bool,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    piece_count: usize,
+    pawn_count: [usize; 2],
+    pairs: [[PairsData; 4]; 2],
+    map: usize,
+}
+
+impl Table {
+    /// Parse the table for the material named in the file
+    pub(super) fn new(bytes: Vec<u8>, material: &Material, dtz: bool) -> Result<Self, ProbeError> {
+        let magic = if dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if bytes.len() % 64 != 16 || !bytes.starts_with(&magic) {
+            return Err(ProbeError::InvalidTable);
+        }
+
+        let white = material.side(Side::White);
+        let black = material.side(Side::Black);
+        let white_pawns = white[0] as usize;
+        let black_pawns = black[0] as usize;
+        // The side with fewer pawns leads, it compresses better
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+
+        let mut table = Self {
+            bytes,
+            dtz,
+            symmetric: white == black,
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces: white[..5].contains(&1) || black[..5].contains(&1),
+            piece_count: material.count(),
+            pawn_count: if white_leads {
+                [white_pawns, black_pawns]
+            } else {
+                [black_pawns, white_pawns]
+            },
+            pairs: Default::default(),
+            map: 0,
+        };
+        table.init()?;
+        Ok(table)
+    }
+
+    fn init(&mut self) -> Result<(), ProbeError> {
+        let mut pos = 4;
+        let header = self.u8_at(pos)?;
+        if (header & HEADER_HAS_PAWNS != 0) != self.has_pawns {
+            return Err(ProbeError::InvalidTable);
+        }
+        pos += 1;
+
+        let sides = self.sides();
+        let files = self.files();
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+
+        for file in 0..files {
+            let first = self.u8_at(pos)?;
+            let second = if pp { self.u8_at(pos + 1)? } else { 0xFF };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            pos += 1 + pp as usize;
+
+            for k in 0..self.piece_count {
+                let byte = self.u8_at(pos)?;
+                self.pairs[0][file].pieces[k] = byte & 0xF;
+                self.pairs[1][file].pieces[k] = byte >> 4;
+                pos += 1;
+            }
+
+            for (side, order) in order.iter().enumerate().take(sides) {
+                self.set_groups(side, file, *order)?;
+            }
+        }
+        pos += pos & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                pos = self.set_sizes(side, file, pos)?;
+            }
+        }
+
+        if self.dtz {
+            pos = self.set_dtz_map(pos)?;
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let d = &mut self.pairs[side][file];
+                d.sparse_index = pos;
+                pos += d.sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let d = &mut self.pairs[side][file];
+                d.block_length = pos;
+                pos += d.block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                pos = (pos + 0x3F) & !0x3F;
+                let d = &mut self.pairs[side][file];
+                d.data = pos;
+                pos += d.num_blocks * d.block_size;
+            }
+        }
+
+        if pos > self.bytes.len() {
+            return Err(ProbeError::InvalidTable);
+        }
+        Ok(())
+    }
+
+    /// WDL tables store both sides to move unless the material is symmetric
+    const fn sides(&self) -> usize {
+        if !self.dtz && !self.symmetric { 2 } else { 1 }
+    }
+
+    /// Tables with pawns are split by the file of the leading pawn
+    const fn files(&self) -> usize {
+        if self.has_pawns { 4 } else { 1 }
+    }
+
+    /// Split the pieces into groups and compute the index multiplier of each group
+    fn set_groups(&mut self, side: usize, file: usize, order: [u8; 2]) -> Result<(), ProbeError> {
+        let has_pawns = self.has_pawns;
+        let pp = has_pawns && self.pawn_count[1] > 0;
+        let unique = self.has_unique_pieces;
+        let piece_count = self.piece_count;
+        let d = &mut self.pairs[side][file];
+
+        let mut n = 0;
+        let mut first_len: i32 = if has_pawns {
+            0
+        } else if unique {
+            3
+        } else {
+            2
+        };
+        d.group_len[0] = 1;
+        for i in 1..piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let mut next = if pp { 2 } else { 1 };
+        let mut free = 64 - d.group_len[0] - if pp { d.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if has_pawns {
+                    LEAD_PAWNS_SIZE[d.group_len[0]][file]
+                } else if unique {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= BINOMIAL[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                if next > MAX_PIECES {
+                    return Err(ProbeError::InvalidTable);
+                }
+                d.group_idx[next] = idx;
+                idx *= BINOMIAL[d.group_len[next]][free];
+                free -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+        Ok(())
+    }
+
+    /// Read the block layout and the Huffman code of the pairs data
+    fn set_sizes(&mut self, side: usize, file: usize, mut pos: usize) -> Result<usize, ProbeError> {
+        let flags = self.u8_at(pos)?;
+        pos += 1;
+        self.pairs[side][file].flags = flags;
+
+        if flags & FLAG_SINGLE_VALUE != 0 {
+            // The single stored value
+            self.pairs[side][file].min_sym_len = self.u8_at(pos)?;
+            return Ok(pos + 1);
+        }
+
+        let block_size = 1 << self.u8_at(pos)?;
+        let span = 1 << self.u8_at(pos + 1)?;
+        let padding = self.u8_at(pos + 2)? as usize;
+        let num_blocks = self.u32_le(pos + 3)? as usize;
+        let max_sym_len = self.u8_at(pos + 7)?;
+        let min_sym_len = self.u8_at(pos + 8)?;
+        pos += 9;
+        if span == 0 || min_sym_len == 0 || max_sym_len < min_sym_len {
+            return Err(ProbeError::InvalidTable);
+        }
+
+        let lowest_sym = pos;
+        let lengths = (max_sym_len - min_sym_len) as usize + 1;
+        let mut base64 = vec![0u64; lengths];
+        // Canonical Huffman: longer codes have lower values
+        for i in (0..lengths - 1).rev() {
+            let lowest = self.u16_le(lowest_sym + 2 * i)? as u64;
+            let next_lowest = self.u16_le(lowest_sym + 2 * (i + 1))? as u64;
+            base64[i] = base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (i, base) in base64.iter_mut().enumerate() {
+            *base = base
+                .checked_shl((64 - i - min_sym_len as usize) as u32)
+                .unwrap_or(0);
+        }
+        pos += lengths * 2;
+
+        let symbols = self.u16_le(pos)? as usize;
+        pos += 2;
+        let btree = pos;
+
+        let d = &mut self.pairs[side][file];
+        let size = d.group_idx[d.group_len.iter().position(|&len| len == 0).unwrap_or(0)];
+        d.block_size = block_size;
+        d.span = span;
+        d.sparse_index_size = size.div_ceil(span) as usize;
+        d.num_blocks = num_blocks;
+        d.block_length_size = num_blocks + padding;
+        d.min_sym_len = min_sym_len;
+        d.lowest_sym = lowest_sym;
+        d.base64 = base64;
+        d.btree = btree;
+
+        let mut symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for sym in 0..symbols {
+            if !visited[sym] {
+                symlen[sym] = self.set_symlen(btree, sym, &mut symlen, &mut visited)?;
+            }
+        }
+        self.pairs[side][file].symlen = symlen;
+
+        Ok(btree + symbols * 3 + (symbols & 1))
+    }
+
+    /// Number of values minus one the symbol expands into
+    fn set_symlen(
+        &self,
+        btree: usize,
+        sym: usize,
+        symlen: &mut [u8],
+        visited: &mut [bool],
+    ) -> Result<u8, ProbeError> {
+        visited[sym] = true;
+        let (left, right) = self.pair_at(btree, sym)?;
+        if right == 0xFFF {
+            return Ok(0);
+        }
+        if left >= symlen.len() || right >= symlen.len() {
+            return Err(ProbeError::InvalidTable);
+        }
+
+        if !visited[left] {
+            symlen[left] = self.set_symlen(btree, left, symlen, visited)?;
+        }
+        if !visited[right] {
+            symlen[right] = self.set_symlen(btree, right, symlen, visited)?;
+        }
+        Ok(symlen[left].wrapping_add(symlen[right]).wrapping_add(1))
+    }
+
+    /// Read the maps from stored values to DTZ for every WDL outcome
+    fn set_dtz_map(&mut self, mut pos: usize) -> Result<usize, ProbeError> {
+        self.map = pos;
+        for file in 0..self.files() {
+            let flags = self.pairs[0][file].flags;
+            if flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+            if flags & FLAG_WIDE != 0 {
+                pos += pos & 1;
+                for i in 0..4 {
+                    self.pairs[0][file].map_idx[i] = (pos - self.map) / 2 + 1;
+                    pos += 2 * self.u16_le(pos)? as usize + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    self.pairs[0][file].map_idx[i] = pos - self.map + 1;
+                    pos += self.u8_at(pos)? as usize + 1;
+                }
+            }
+        }
+        Ok(pos + (pos & 1))
+    }
+
+    /// Value stored for the position as WDL
+    pub(super) fn wdl(&self, cb: &Chessboard, flipped: bool) -> Result<Wdl, ProbeError> {
+        let (value, _) = self.lookup(cb, flipped)?.ok_or(ProbeError::InvalidTable)?;
+        Ok(Wdl::from_i8(value as i8 - 2))
+    }
+
+    /// Distance to zeroing in plies for the known outcome
+    ///
+    /// Returns [`None`] when the table stores the other side to move
+    pub(super) fn dtz(
+        &self,
+        cb: &Chessboard,
+        flipped: bool,
+        wdl: Wdl,
+    ) -> Result<Option<i32>, ProbeError> {
+        let Some((value, file)) = self.lookup(cb, flipped)? else {
+            return Ok(None);
+        };
+
+        let d = &self.pairs[0][file];
+        let mut value = value as usize;
+        if d.flags & FLAG_MAPPED != 0 {
+            let idx = d.map_idx[match wdl {
+                Wdl::Loss => 1,
+                Wdl::BlessedLoss => 3,
+                Wdl::CursedWin => 2,
+                Wdl::Draw | Wdl::Win => 0,
+            }];
+            value = if d.flags & FLAG_WIDE != 0 {
+                self.u16_le(self.map + 2 * (idx + value))? as usize
+            } else {
+                self.u8_at(self.map + idx + value)? as usize
+            };
+        }
+
+        let in_moves = match wdl {
+            Wdl::Win => d.flags & FLAG_WIN_PLIES == 0,
+            Wdl::Loss => d.flags & FLAG_LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        if in_moves {
+            value *= 2;
+        }
+        Ok(Some(value as i32 + 1))
+    }
+
+    /// Encode the position and decompress its value
+    ///
+    /// `flipped` is set when black has the material listed first in the table name.
+    /// Returns the value with the file of the leading pawn
+    fn lookup(&self, cb: &Chessboard, flipped: bool) -> Result<Option<(u32, usize)>, ProbeError> {
+        // Symmetric tables only store white to move
+        let flip = flipped || (self.symmetric && matches!(cb.side(), Side::Black));
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ cb.side().as_u8() as usize;
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_count = 0;
+        let mut file = 0;
+
+        let lead_pawns = if self.has_pawns {
+            let side = match self.pairs[0][0].pieces[0] ^ flip_color {
+                p if p & 8 == 0 => Side::White,
+                _ => Side::Black,
+            };
+            cb.get_pieces(Piece::from_side_and_type(side, PieceType::Pawn))
+        } else {
+            Bitboard::EMPTY
+        };
+
+        if self.has_pawns {
+            let mut pawns = lead_pawns;
+            while let Some(sq) = pawns.next_const() {
+                if size >= MAX_PIECES {
+                    return Err(ProbeError::MissingTable);
+                }
+                squares[size] = sq.as_u8() as usize ^ flip_squares;
+                size += 1;
+            }
+            lead_count = size;
+
+            let mut lead = 0;
+            for i in 1..lead_count {
+                if MAP_PAWNS[squares[i]] > MAP_PAWNS[squares[lead]] {
+                    lead = i;
+                }
+            }
+            squares.swap(0, lead);
+            file = (squares[0] & 7).min(7 - (squares[0] & 7));
+        }
+
+        if self.dtz
+            && (self.pairs[0][file].flags & FLAG_STM) as usize != stm
+            && !(self.symmetric && !self.has_pawns)
+        {
+            return Ok(None);
+        }
+
+        let mut rest = cb.occupied().xor(lead_pawns);
+        while let Some(sq) = rest.next_const() {
+            if size >= MAX_PIECES {
+                return Err(ProbeError::MissingTable);
+            }
+            let Some(piece) = cb.at(sq) else {
+                continue;
+            };
+            squares[size] = sq.as_u8() as usize ^ flip_squares;
+            pieces[size] = piece_code(piece) ^ flip_color;
+            size += 1;
+        }
+
+        let d = &self.pairs[if self.dtz { 0 } else { stm }][file];
+
+        // Order the pieces the way the table stores them
+        for i in lead_count..size.saturating_sub(1) {
+            for j in i + 1..size {
+                if d.pieces[i] == pieces[j] {
+                    pieces.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        // The leading piece goes to the a-d files
+        if squares[0] & 7 > 3 {
+            for sq in &mut squares[..size] {
+                *sq ^= 7;
+            }
+        }
+
+        let mut idx = if self.has_pawns {
+            let mut idx = LEAD_PAWN_IDX[lead_count][squares[0]];
+            squares[1..lead_count].sort_by_key(|&sq| MAP_PAWNS[sq]);
+            for i in 1..lead_count {
+                idx += BINOMIAL[i][MAP_PAWNS[squares[i]]];
+            }
+            idx
+        } else {
+            self.encode_leading_pieces(d, &mut squares[..size])
+        };
+
+        idx *= d.group_idx[0];
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while next <= MAX_PIECES && d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            let end = (start + len).min(size);
+            squares[start..end].sort_unstable();
+
+            let mut n = 0;
+            for i in start..end {
+                let sq = squares[i];
+                let adjust = squares[..start].iter().filter(|&&s| sq > s).count();
+                let below = if remaining_pawns { 8 } else { 0 };
+                n += BINOMIAL[i - start + 1][sq.saturating_sub(adjust + below)];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start = end;
+            next += 1;
+        }
+
+        Ok(Some((self.decompress(d, idx)?, file)))
+    }
+
+    /// Index of the leading group of a table without pawns
+    fn encode_leading_pieces(&self, d: &PairsData, squares: &mut [usize]) -> u64 {
+        // The leading piece goes to the first four ranks
+        if squares[0] >> 3 > 3 {
+            for sq in squares.iter_mut() {
+                *sq ^= 56;
+            }
+        }
+
+        // The first leading piece off the diagonal goes below it
+        for i in 0..d.group_len[0].min(squares.len()) {
+            let off = off_diagonal(squares[i]);
+            if off == 0 {
+                continue;
+            }
+            if off > 0 {
+                for sq in &mut squares[i..] {
+                    *sq = ((*sq >> 3) | (*sq << 3)) & 63;
+                }
+            }
+            break;
+        }
+
+        if !self.has_unique_pieces {
+            return MAP_KK[MAP_A1D1D4[squares[0]]][squares[1]] as u64;
+        }
+
+        let (s0, s1, s2) = (squares[0], squares[1], squares[2]);
+        let adjust1 = (s1 > s0) as usize;
+        let adjust2 = (s2 > s0) as usize + (s2 > s1) as usize;
+        let idx = if off_diagonal(s0) != 0 {
+            (MAP_A1D1D4[s0] * 63 + (s1 - adjust1)) * 62 + s2 - adjust2
+        } else if off_diagonal(s1) != 0 {
+            (6 * 63 + (s0 >> 3) * 28 + MAP_B1H1H7[s1]) * 62 + s2 - adjust2
+        } else if off_diagonal(s2) != 0 {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + (s0 >> 3) * 7 * 28
+                + ((s1 >> 3) - adjust1) * 28
+                + MAP_B1H1H7[s2]
+        } else {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + 4 * 7 * 28
+                + (s0 >> 3) * 7 * 6
+                + ((s1 >> 3) - adjust1) * 6
+                + ((s2 >> 3) - adjust2)
+        };
+        idx as u64
+    }
+
+    /// Decode the value at the index
+    fn decompress(&self, d: &PairsData, idx: u64) -> Result<u32, ProbeError> {
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            return Ok(d.min_sym_len as u32);
+        }
+
+        // The sparse index points into the block holding the value near the index
+        let k = (idx / d.span) as usize;
+        if k >= d.sparse_index_size {
+            return Err(ProbeError::InvalidTable);
+        }
+        let entry = d.sparse_index + 6 * k;
+        let mut block = self.u32_le(entry)? as usize;
+        let mut offset = self.u16_le(entry + 4)? as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+
+        while offset < 0 {
+            block = block.checked_sub(1).ok_or(ProbeError::InvalidTable)?;
+            offset += self.block_length(d, block)? + 1;
+        }
+        while offset > self.block_length(d, block)? {
+            offset -= self.block_length(d, block)? + 1;
+            block += 1;
+        }
+
+        let min_len = d.min_sym_len as usize;
+        let mut ptr = d.data + block * d.block_size;
+        let mut buf = self.u64_be(ptr)?;
+        ptr += 8;
+        let mut buf_size = 64;
+
+        let mut sym = loop {
+            let mut len = 0;
+            while buf < *d.base64.get(len).ok_or(ProbeError::InvalidTable)? {
+                len += 1;
+            }
+            let sym = ((buf - d.base64[len]) >> (64 - len - min_len)) as usize
+                + self.u16_le(d.lowest_sym + 2 * len)? as usize;
+            let count = *d.symlen.get(sym).ok_or(ProbeError::InvalidTable)? as i64 + 1;
+            if offset < count {
+                break sym;
+            }
+
+            offset -= count;
+            let len = len + min_len;
+            buf = buf.checked_shl(len as u32).unwrap_or(0);
+            buf_size -= len as i64;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= (self.u32_be(ptr)? as u64) << (64 - buf_size);
+                ptr += 4;
+            }
+        };
+
+        // Walk down the pairs to the value
+        while d.symlen[sym] != 0 {
+            let (left, right) = self.pair_at(d.btree, sym)?;
+            let count = *d.symlen.get(left).ok_or(ProbeError::InvalidTable)? as i64 + 1;
+            if offset < count {
+                sym = left;
+            } else {
+                offset -= count;
+                sym = right;
+            }
+            if sym >= d.symlen.len() {
+                return Err(ProbeError::InvalidTable);
+            }
+        }
+        Ok(self.pair_at(d.btree, sym)?.0 as u32)
+    }
+
+    fn block_length(&self, d: &PairsData, block: usize) -> Result<i64, ProbeError> {
+        if block >= d.block_length_size {
+            return Err(ProbeError::InvalidTable);
+        }
+        Ok(self.u16_le(d.block_length + 2 * block)? as i64)
+    }
+
+    /// Left and right symbols of the pair, a leaf stores its value on the left
+    fn pair_at(&self, btree: usize, sym: usize) -> Result<(usize, usize), ProbeError> {
+        let pos = btree + 3 * sym;
+        let [a, b, c] = self.array_at(pos)?;
+        let left = ((b as usize & 0xF) << 8) | a as usize;
+        let right = ((c as usize) << 4) | (b as usize >> 4);
+        Ok((left, right))
+    }
+
+    fn array_at<const N: usize>(&self, pos: usize) -> Result<[u8; N], ProbeError> {
+        self.bytes
+            .get(pos..pos + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProbeError::InvalidTable)
+    }
+
+    fn u8_at(&self, pos: usize) -> Result<u8, ProbeError> {
+        self.bytes.get(pos).copied().ok_or(ProbeError::InvalidTable)
+    }
+
+    fn u16_le(&self, pos: usize) -> Result<u16, ProbeError> {
+        Ok(u16::from_le_bytes(self.array_at(pos)?))
+    }
+
+    fn u32_le(&self, pos: usize) -> Result<u32, ProbeError> {
+        Ok(u32::from_le_bytes(self.array_at(pos)?))
+    }
+
+    fn u32_be(&self, pos: usize) -> Result<u32, ProbeError> {
+        Ok(u32::from_be_bytes(self.array_at(pos)?))
+    }
+
+    fn u64_be(&self, pos: usize) -> Result<u64, ProbeError> {
+        Ok(u64::from_be_bytes(self.array_at(pos)?))
+    }
+}
+
+/// Piece as stored in the table files
+const fn piece_code(piece: Piece) -> u8 {
+    let kind = match piece.piece_type() {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match piece.side() {
+        Side::White => kind,
+        Side::Black => kind | 8,
+    }
+}
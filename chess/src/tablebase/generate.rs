@@ -0,0 +1,263 @@
+//! Retrograde analysis of one material
+//!
+//! Positions are resolved level by level, a level being the distance to mate.
+//! Mates start the search, then every resolved position schedules its
+//! predecessors for the next level. Moves that change the material are looked
+//! up in the tables already computed and schedule the position at init
+
+use std::collections::HashMap;
+
+use crate::{
+    Bitboard, Chessboard, ChessboardRaw, Directions, Move, PieceType, Side, Square,
+    get_piece_attacks,
+};
+
+use super::{DRAW, Dtm, EndgameTable, LOSS, TablebaseError, UNUSED, index::Material};
+
+/// Longest distance to mate the values can store
+const MAX_DEPTH: usize = LOSS as usize - 2;
+
+struct Builder<'a> {
+    material: &'a Material,
+    tables: &'a HashMap<String, EndgameTable>,
+    values: Vec<u8>,
+    /// Last level the position was scheduled for
+    queued: Vec<u8>,
+    levels: Vec<Vec<u32>>,
+}
+
+/// Compute the table, the tables of the captures and promotions must be present
+pub(super) fn build(
+    material: &Material,
+    tables: &HashMap<String, EndgameTable>,
+) -> Result<EndgameTable, TablebaseError> {
+    let size = material.size();
+    let mut builder = Builder {
+        material,
+        tables,
+        values: vec![UNUSED; size],
+        queued: vec![0; size],
+        levels: vec![Vec::new(); MAX_DEPTH + 1],
+    };
+    builder.init()?;
+    builder.resolve()?;
+    Ok(EndgameTable {
+        material: material.clone(),
+        values: builder.values,
+    })
+}
+
+impl Builder<'_> {
+    fn init(&mut self) -> Result<(), TablebaseError> {
+        let mut mates = Vec::new();
+        for index in 0..self.values.len() {
+            let Some(cb) = self.material.position(index) else {
+                continue;
+            };
+            self.values[index] = DRAW;
+
+            let moves = cb.to_vec();
+            if moves.is_empty() {
+                if !cb.checkers.is_empty() {
+                    self.values[index] = Dtm::Loss(0).as_byte();
+                    mates.push(cb);
+                }
+                continue;
+            }
+
+            for m in moves {
+                let known = if is_capture(&cb, m) {
+                    Some(self.lookup(&cb, m)?)
+                } else {
+                    let mut raw = cb.into_raw();
+                    raw.make_move(m);
+                    self.en_passant(&raw)?.map(|(dtm, _)| dtm)
+                };
+                if let Some(Dtm::Win(plies) | Dtm::Loss(plies)) = known {
+                    self.schedule(index, plies as usize + 1)?;
+                }
+            }
+        }
+
+        for cb in mates {
+            self.retract(&cb.into_raw(), 1)?;
+        }
+        Ok(())
+    }
+
+    fn resolve(&mut self) -> Result<(), TablebaseError> {
+        for level in 1..=MAX_DEPTH {
+            let indices = std::mem::take(&mut self.levels[level]);
+            for index in indices {
+                let index = index as usize;
+                if self.values[index] != DRAW {
+                    continue;
+                }
+                let Some(cb) = self.material.position(index) else {
+                    continue;
+                };
+                if let Some(dtm) = self.evaluate(&cb, level)? {
+                    self.values[index] = dtm.as_byte();
+                    self.retract(&cb.into_raw(), level + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn schedule(&mut self, index: usize, level: usize) -> Result<(), TablebaseError> {
+        if level > MAX_DEPTH {
+            return Err(TablebaseError::TooDeep);
+        }
+        if self.queued[index] as usize != level {
+            self.queued[index] = level as u8;
+            self.levels[level].push(index as u32);
+        }
+        Ok(())
+    }
+
+    /// Schedule the positions one quiet move before the position
+    fn retract(&mut self, raw: &ChessboardRaw, level: usize) -> Result<(), TablebaseError> {
+        let mover = raw.side.neg();
+        let occupied = raw.colors[0].or(raw.colors[1]);
+        for to in raw.colors[mover.as_u8() as usize] {
+            let Some(piece) = raw.at(to) else {
+                continue;
+            };
+            let origins = if piece.piece_type() == PieceType::Pawn {
+                pawn_origins(mover, to, occupied)
+            } else {
+                get_piece_attacks(piece, to, occupied).and(occupied.neg())
+            };
+            for from in origins {
+                let mut parent = *raw;
+                parent.move_piece_raw(to, from, piece);
+                parent.side = mover;
+                parent.en_passant = None;
+                self.schedule(self.material.index(&parent), level)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the position if its distance to mate is at most the level
+    fn evaluate(&self, cb: &Chessboard, level: usize) -> Result<Option<Dtm>, TablebaseError> {
+        let moves = cb.to_vec();
+        if moves.is_empty() {
+            return Ok(None);
+        }
+
+        let mut win: Option<u8> = None;
+        let mut longest = 0;
+        let mut all_lost = true;
+        for m in moves {
+            match self.child(cb, m, level)? {
+                Dtm::Loss(plies) => win = Some(win.map_or(plies + 1, |w| w.min(plies + 1))),
+                Dtm::Win(plies) => longest = longest.max(plies + 1),
+                Dtm::Draw => all_lost = false,
+            }
+        }
+
+        let dtm = match win {
+            Some(plies) if plies as usize <= level => Some(Dtm::Win(plies)),
+            None if all_lost && longest as usize <= level => Some(Dtm::Loss(longest)),
+            _ => None,
+        };
+        Ok(dtm)
+    }
+
+    /// Value of the position after the move, unresolved positions are draws
+    fn child(&self, cb: &Chessboard, m: Move, level: usize) -> Result<Dtm, TablebaseError> {
+        if is_capture(cb, m) {
+            return self.lookup(cb, m);
+        }
+
+        let mut raw = cb.into_raw();
+        raw.make_move(m);
+        let value = self.values[self.material.index(&raw)];
+        let stored = Dtm::from_byte(value).unwrap_or(Dtm::Draw);
+        let Some((capture, only)) = self.en_passant(&raw)? else {
+            return Ok(stored);
+        };
+
+        // The stored value is the position without the en passant capture
+        let dtm = if only {
+            capture
+        } else if value != DRAW {
+            stored.max(capture)
+        } else if matches!(capture, Dtm::Win(plies) if (plies as usize) < level) {
+            capture
+        } else {
+            Dtm::Draw
+        };
+        Ok(dtm)
+    }
+
+    /// Value of the position after the capture or promotion
+    fn lookup(&self, cb: &Chessboard, m: Move) -> Result<Dtm, TablebaseError> {
+        let mut raw = cb.into_raw();
+        raw.make_move(m);
+        let material = Material::from_raw(&raw).canonical();
+        let table = self
+            .tables
+            .get(&material.name())
+            .ok_or(TablebaseError::MissingTable)?;
+        table.value(&raw).ok_or(TablebaseError::InvalidData)
+    }
+
+    /// Best en passant capture of the position and whether it is the only
+    /// kind of legal move
+    fn en_passant(&self, raw: &ChessboardRaw) -> Result<Option<(Dtm, bool)>, TablebaseError> {
+        if raw.en_passant.is_none() {
+            return Ok(None);
+        }
+        let Ok(cb) = Chessboard::from_raw(*raw) else {
+            return Ok(None);
+        };
+
+        let moves = cb.to_vec();
+        let mut best = None;
+        let mut only = true;
+        for m in moves {
+            if matches!(m, Move::EnPassant { .. }) {
+                best = best.max(Some(self.lookup(&cb, m)?.before_move()));
+            } else {
+                only = false;
+            }
+        }
+        Ok(best.map(|dtm| (dtm, only)))
+    }
+}
+
+const fn is_capture(cb: &Chessboard, m: Move) -> bool {
+    match m {
+        Move::Standard { to, .. } => cb.at(to).is_some(),
+        Move::Promotion { .. } | Move::EnPassant { .. } => true,
+        _ => false,
+    }
+}
+
+/// Squares the pawn standing on the square could have come from
+fn pawn_origins(side: Side, to: Square, occupied: Bitboard) -> Bitboard {
+    let (back, start) = match side {
+        Side::White => (Directions::Down, 1),
+        Side::Black => (Directions::Up, 6),
+    };
+    let mut origins = Bitboard::EMPTY;
+    let Some(one) = to
+        .shift(back)
+        .filter(|sq| !occupied.overlaps(sq.bitboard()))
+    else {
+        return origins;
+    };
+    if matches!(one.rank().as_u8(), 1..=6) {
+        origins = origins.or(one.bitboard());
+    }
+    if let Some(two) = one.shift(back)
+        && two.rank().as_u8() == start
+        && !occupied.overlaps(two.bitboard())
+    {
+        origins = origins.or(two.bitboard());
+    }
+    origins
+}
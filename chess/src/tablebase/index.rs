@@ -0,0 +1,347 @@
+//! Material description and symmetric position indexing
+
+use crate::{
+    Bitboard, Chessboard, ChessboardRaw, Piece, PieceType, Side, Square, get_piece_attacks,
+};
+
+use super::{MAX_PIECES, TablebaseError};
+
+/// Squares of the a1-d1-d4 triangle
+const TRIANGLE: [u8; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+/// Code of the white king square, `u8::MAX` outside the canonical region
+const KING_CODES: [[u8; 64]; 2] = {
+    let mut codes = [[u8::MAX; 64]; 2];
+    let mut i = 0;
+    while i < TRIANGLE.len() {
+        codes[0][TRIANGLE[i] as usize] = i as u8;
+        i += 1;
+    }
+    let mut sq = 0;
+    let mut code = 0;
+    while sq < 64 {
+        if sq & 7 <= 3 {
+            codes[1][sq] = code;
+            code += 1;
+        }
+        sq += 1;
+    }
+    codes
+};
+
+/// Apply one of the eight board symmetries to the square
+///
+/// Bit 0 mirrors the files, bit 1 mirrors the ranks and bit 2 swaps them
+const fn transform(sq: usize, t: usize) -> usize {
+    let mut sq = sq;
+    if t & 4 != 0 {
+        sq = (sq >> 3) | ((sq & 7) << 3);
+    }
+    if t & 1 != 0 {
+        sq ^= 7;
+    }
+    if t & 2 != 0 {
+        sq ^= 56;
+    }
+    sq
+}
+
+const fn piece_value(piece_type: PieceType) -> u32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King => 0,
+    }
+}
+
+/// Pieces besides the kings, white first and identical pieces adjacent
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct Material {
+    pieces: Vec<Piece>,
+}
+
+impl Material {
+    const NAME_ORDER: [(char, PieceType); 5] = [
+        ('Q', PieceType::Queen),
+        ('R', PieceType::Rook),
+        ('B', PieceType::Bishop),
+        ('N', PieceType::Knight),
+        ('P', PieceType::Pawn),
+    ];
+
+    fn new(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_by_key(|piece| {
+            let order = Self::NAME_ORDER
+                .iter()
+                .position(|(_, t)| *t == piece.piece_type());
+            (piece.side().as_u8(), order)
+        });
+        Self { pieces }
+    }
+
+    pub(super) fn from_raw(raw: &ChessboardRaw) -> Self {
+        let mut pieces = Vec::new();
+        for side in Side::ALL {
+            for (_, piece_type) in Self::NAME_ORDER {
+                let piece = Piece::from_side_and_type(side, piece_type);
+                for _ in raw.get_pieces(piece) {
+                    pieces.push(piece);
+                }
+            }
+        }
+        Self { pieces }
+    }
+
+    /// Parse the name like `KBNvK`
+    pub(super) fn from_name(name: &str) -> Result<Self, TablebaseError> {
+        let sides: [&str; 2] = name
+            .split_once('v')
+            .ok_or(TablebaseError::InvalidMaterial)?
+            .into();
+        let mut pieces = Vec::new();
+        for (side, names) in Side::ALL.into_iter().zip(sides) {
+            let names = names
+                .strip_prefix('K')
+                .ok_or(TablebaseError::InvalidMaterial)?;
+            for c in names.chars() {
+                let (_, piece_type) = Self::NAME_ORDER
+                    .iter()
+                    .find(|(name, _)| *name == c)
+                    .ok_or(TablebaseError::InvalidMaterial)?;
+                pieces.push(Piece::from_side_and_type(side, *piece_type));
+            }
+        }
+        if pieces.len() + 2 > MAX_PIECES {
+            return Err(TablebaseError::TooManyPieces);
+        }
+        Ok(Self::new(pieces))
+    }
+
+    pub(super) fn name(&self) -> String {
+        let mut name = String::from("K");
+        for side in Side::ALL {
+            if matches!(side, Side::Black) {
+                name.push_str("vK");
+            }
+            for piece in self.pieces.iter().filter(|p| p.side() == side) {
+                if let Some((c, _)) = Self::NAME_ORDER
+                    .iter()
+                    .find(|(_, t)| *t == piece.piece_type())
+                {
+                    name.push(*c);
+                }
+            }
+        }
+        name
+    }
+
+    fn flipped(&self) -> Self {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|p| Piece::from_side_and_type(p.side().neg(), p.piece_type()))
+            .collect();
+        Self::new(pieces)
+    }
+
+    fn strength(&self, side: Side) -> (u32, String) {
+        let value = self
+            .pieces
+            .iter()
+            .filter(|p| p.side() == side)
+            .map(|p| piece_value(p.piece_type()))
+            .sum();
+        let name = self.name();
+        let names: [&str; 2] = name.split_once('v').unwrap_or_default().into();
+        (value, names[side.as_u8() as usize].to_owned())
+    }
+
+    /// Tables are stored with the stronger side as white
+    pub(super) fn is_canonical(&self) -> bool {
+        self.strength(Side::White) >= self.strength(Side::Black)
+    }
+
+    pub(super) fn canonical(&self) -> Self {
+        if self.is_canonical() {
+            self.clone()
+        } else {
+            self.flipped()
+        }
+    }
+
+    /// Materials reachable by one capture or promotion
+    pub(super) fn successors(&self) -> Vec<Self> {
+        let mut successors = Vec::new();
+        for i in 0..self.pieces.len() {
+            let mut pieces = self.pieces.clone();
+            pieces.remove(i);
+            successors.push(Self::new(pieces));
+        }
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if piece.piece_type() != PieceType::Pawn {
+                continue;
+            }
+            for promotion in [
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+            ] {
+                let mut pieces = self.pieces.clone();
+                pieces[i] = Piece::from_side_and_type(piece.side(), promotion);
+                successors.push(Self::new(pieces));
+            }
+        }
+        successors
+    }
+
+    fn has_pawns(&self) -> bool {
+        self.pieces
+            .iter()
+            .any(|p| p.piece_type() == PieceType::Pawn)
+    }
+
+    const fn king_squares(pawns: bool) -> usize {
+        if pawns { 32 } else { TRIANGLE.len() }
+    }
+
+    /// Number of indices of the table
+    pub(super) fn size(&self) -> usize {
+        2 * Self::king_squares(self.has_pawns()) * 64usize.pow(self.pieces.len() as u32 + 1)
+    }
+
+    /// Squares of the white king, the black king and the other pieces
+    fn squares(&self, raw: &ChessboardRaw) -> Vec<usize> {
+        let mut squares = Vec::with_capacity(self.pieces.len() + 2);
+        for side in Side::ALL {
+            let king = Piece::from_side_and_type(side, PieceType::King);
+            squares.extend(raw.get_pieces(king).map(|sq| sq.as_u8() as usize));
+        }
+        let mut previous = None;
+        for piece in self.pieces.iter().copied() {
+            if previous != Some(piece) {
+                squares.extend(raw.get_pieces(piece).map(|sq| sq.as_u8() as usize));
+            }
+            previous = Some(piece);
+        }
+        squares
+    }
+
+    fn encode(&self, squares: &mut [usize], side: Side, pawns: bool) -> Option<usize> {
+        let code = KING_CODES[pawns as usize][squares[0]];
+        if code == u8::MAX {
+            return None;
+        }
+
+        // Identical pieces are stored in ascending order
+        let mut start = 2;
+        while start < squares.len() {
+            let piece = self.pieces[start - 2];
+            let mut end = start + 1;
+            while end < squares.len() && self.pieces[end - 2] == piece {
+                end += 1;
+            }
+            squares[start..end].sort_unstable();
+            start = end;
+        }
+
+        let mut index = side.as_u8() as usize * Self::king_squares(pawns) + code as usize;
+        for &sq in &squares[1..] {
+            index = index * 64 + sq;
+        }
+        Some(index)
+    }
+
+    /// Smallest index among the symmetric positions, en passant is ignored
+    pub(super) fn index(&self, raw: &ChessboardRaw) -> usize {
+        let squares = self.squares(raw);
+        let pawns = self.has_pawns();
+        let transforms = if pawns { 2 } else { 8 };
+
+        let mut best = usize::MAX;
+        let mut mapped = squares.clone();
+        for t in 0..transforms {
+            for (to, &from) in mapped.iter_mut().zip(&squares) {
+                *to = transform(from, t);
+            }
+            if let Some(index) = self.encode(&mut mapped, raw.side, pawns) {
+                best = best.min(index);
+            }
+        }
+        best
+    }
+
+    /// Legal position stored under the canonical index
+    pub(super) fn position(&self, index: usize) -> Option<Chessboard> {
+        let pawns = self.has_pawns();
+        let mut rest = index;
+        let mut squares = vec![0; self.pieces.len() + 2];
+        for sq in squares[1..].iter_mut().rev() {
+            *sq = rest % 64;
+            rest /= 64;
+        }
+        let kings = Self::king_squares(pawns);
+        let code = rest % kings;
+        let side = Side::from_u8((rest / kings) as u8);
+        squares[0] = if pawns {
+            (code / 4) * 8 + code % 4
+        } else {
+            TRIANGLE[code] as usize
+        };
+
+        let mut raw = ChessboardRaw {
+            side,
+            ..Default::default()
+        };
+        for (i, &sq) in squares.iter().enumerate() {
+            let square = Square::from_u8(sq as u8);
+            let piece = match i {
+                0 => Piece::WHITE_KING,
+                1 => Piece::BLACK_KING,
+                _ => self.pieces[i - 2],
+            };
+            let back_rank = !(8..56).contains(&sq);
+            if raw.at(square).is_some() || (piece.piece_type() == PieceType::Pawn && back_rank) {
+                return None;
+            }
+            raw.place_raw(square, piece);
+        }
+
+        let (wk, bk) = (squares[0], squares[1]);
+        let touching = (wk & 7).abs_diff(bk & 7) <= 1 && (wk >> 3).abs_diff(bk >> 3) <= 1;
+        if touching || attacks_king(&raw) || self.index(&raw) != index {
+            return None;
+        }
+        Chessboard::from_raw(raw).ok()
+    }
+
+    /// Position with the colors swapped and the board mirrored vertically
+    pub(super) fn flip(raw: &ChessboardRaw) -> ChessboardRaw {
+        let mut flipped = ChessboardRaw {
+            side: raw.side.neg(),
+            ..Default::default()
+        };
+        let occupied = raw.colors[0].or(raw.colors[1]);
+        for sq in occupied {
+            if let Some(piece) = raw.at(sq) {
+                let piece = Piece::from_side_and_type(piece.side().neg(), piece.piece_type());
+                flipped.place_raw(Square::from_u8(sq.as_u8() ^ 56), piece);
+            }
+        }
+        flipped
+    }
+}
+
+/// Whether the side to move attacks the king of the other side
+fn attacks_king(raw: &ChessboardRaw) -> bool {
+    let us = raw.side;
+    let king = raw.get_pieces(Piece::from_side_and_type(us.neg(), PieceType::King));
+    let occupied: Bitboard = raw.colors[0].or(raw.colors[1]);
+    let mut ours = raw.colors[us.as_u8() as usize];
+    ours.any(|sq| {
+        raw.at(sq)
+            .is_some_and(|piece| get_piece_attacks(piece, sq, occupied).overlaps(king))
+    })
+}
@@ -0,0 +1,322 @@
+//! Endgame tablebases computed by retrograde analysis
+//!
+//! Tables cover up to four pieces without castling rights and store the
+//! distance to mate of every position, reduced by the board symmetries
+
+mod generate;
+mod index;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use index::Material;
+
+use crate::{Chessboard, ChessboardRaw, Move};
+
+/// Largest number of pieces, kings included
+pub const MAX_PIECES: usize = 4;
+
+const MAGIC: [u8; 4] = *b"CETB";
+const DRAW: u8 = 0;
+const UNUSED: u8 = u8::MAX;
+const LOSS: u8 = 128;
+
+/// Distance to mate in plies for the side to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dtm {
+    Win(u8),
+    Loss(u8),
+    Draw,
+}
+
+impl Dtm {
+    const fn from_byte(value: u8) -> Option<Self> {
+        match value {
+            UNUSED => None,
+            DRAW => Some(Self::Draw),
+            1..LOSS => Some(Self::Win(value)),
+            _ => Some(Self::Loss(value - LOSS)),
+        }
+    }
+
+    const fn as_byte(&self) -> u8 {
+        match self {
+            Self::Win(plies) => *plies,
+            Self::Loss(plies) => LOSS + *plies,
+            Self::Draw => DRAW,
+        }
+    }
+
+    /// Value for the side that made the move into the position
+    pub const fn before_move(&self) -> Self {
+        match self {
+            Self::Win(plies) => Self::Loss(*plies + 1),
+            Self::Loss(plies) => Self::Win(*plies + 1),
+            Self::Draw => Self::Draw,
+        }
+    }
+
+    const fn score(&self) -> i32 {
+        match self {
+            Self::Win(plies) => 256 - *plies as i32,
+            Self::Loss(plies) => *plies as i32 - 256,
+            Self::Draw => 0,
+        }
+    }
+}
+
+/// Better outcomes for the side to move are greater
+impl Ord for Dtm {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for Dtm {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Errors of the tablebase generation and probing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TablebaseError {
+    /// The name is not like `KRvK`
+    InvalidMaterial,
+    /// The material has more than [`MAX_PIECES`] pieces
+    TooManyPieces,
+    /// No table for the material of the position
+    MissingTable,
+    /// Tables do not cover positions with castling rights
+    CastlingRights,
+    /// A mate is longer than the table format can store
+    TooDeep,
+    /// The table data is corrupted
+    InvalidData,
+}
+
+/// Position counts of a table by the side to move
+///
+/// Symmetric positions are counted once
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub wins: [usize; 2],
+    pub draws: [usize; 2],
+    pub losses: [usize; 2],
+    /// Longest distance to mate in plies
+    pub longest: [u8; 2],
+}
+
+/// Distance to mate of all positions of one material
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndgameTable {
+    material: Material,
+    values: Vec<u8>,
+}
+
+impl EndgameTable {
+    /// Material of the table like `KRvK`
+    pub fn name(&self) -> String {
+        self.material.name()
+    }
+
+    fn value(&self, raw: &ChessboardRaw) -> Option<Dtm> {
+        let material = Material::from_raw(raw);
+        let index = if material == self.material {
+            self.material.index(raw)
+        } else if material.canonical() == self.material {
+            self.material.index(&Material::flip(raw))
+        } else {
+            return None;
+        };
+        self.values.get(index).copied().and_then(Dtm::from_byte)
+    }
+
+    /// Distance to mate of the position
+    ///
+    /// Returns `None` for other material, castling rights or a legal en passant
+    /// capture, which needs the tables of the captures
+    pub fn probe(&self, cb: &Chessboard) -> Option<Dtm> {
+        if cb.castling().as_u8() != 0 || has_en_passant(cb) {
+            return None;
+        }
+        self.value(&cb.into_raw())
+    }
+
+    pub fn stats(&self) -> TableStats {
+        let mut stats = TableStats::default();
+        let half = self.values.len() / 2;
+        for (index, &value) in self.values.iter().enumerate() {
+            let side = index / half;
+            match Dtm::from_byte(value) {
+                Some(Dtm::Win(plies)) => {
+                    stats.wins[side] += 1;
+                    stats.longest[side] = stats.longest[side].max(plies);
+                }
+                Some(Dtm::Loss(plies)) => {
+                    stats.losses[side] += 1;
+                    stats.longest[side] = stats.longest[side].max(plies);
+                }
+                Some(Dtm::Draw) => stats.draws[side] += 1,
+                None => (),
+            }
+        }
+        stats
+    }
+
+    /// Serialize the table
+    ///
+    /// Magic, name length and name, number of values as little-endian `u64`,
+    /// then runs of equal values as the value and a LEB128 run length
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.name();
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(self.values.len() as u64).to_le_bytes());
+
+        for run in self.values.chunk_by(|a, b| a == b) {
+            bytes.push(run[0]);
+            let mut len = run.len();
+            while len >= 0x80 {
+                bytes.push((len & 0x7f) as u8 | 0x80);
+                len >>= 7;
+            }
+            bytes.push(len as u8);
+        }
+        bytes
+    }
+
+    /// Deserialize the table written by [`EndgameTable::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TablebaseError> {
+        let rest = bytes
+            .strip_prefix(&MAGIC)
+            .ok_or(TablebaseError::InvalidData)?;
+        let (&name_len, rest) = rest.split_first().ok_or(TablebaseError::InvalidData)?;
+        let (name, rest) = rest
+            .split_at_checked(name_len as usize)
+            .ok_or(TablebaseError::InvalidData)?;
+        let name = std::str::from_utf8(name).map_err(|_| TablebaseError::InvalidData)?;
+        let material = Material::from_name(name)?;
+        let (len, mut rest) = rest
+            .split_first_chunk()
+            .ok_or(TablebaseError::InvalidData)?;
+        let len = u64::from_le_bytes(*len) as usize;
+        if len != material.size() {
+            return Err(TablebaseError::InvalidData);
+        }
+
+        let mut values = Vec::with_capacity(len);
+        while let Some((&value, tail)) = rest.split_first() {
+            rest = tail;
+            let mut run = 0;
+            let mut shift = 0;
+            loop {
+                let (&byte, tail) = rest.split_first().ok_or(TablebaseError::InvalidData)?;
+                rest = tail;
+                run |= ((byte & 0x7f) as usize)
+                    .checked_shl(shift)
+                    .ok_or(TablebaseError::InvalidData)?;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            if values.len() + run > len {
+                return Err(TablebaseError::InvalidData);
+            }
+            values.resize(values.len() + run, value);
+        }
+        if values.len() != len {
+            return Err(TablebaseError::InvalidData);
+        }
+        Ok(Self { material, values })
+    }
+}
+
+/// Set of tables computed on demand with the tables they depend on
+#[derive(Debug, Default)]
+pub struct Generator {
+    tables: HashMap<String, EndgameTable>,
+}
+
+impl Generator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute the table of the material like `KBNvK` and every table
+    /// reachable by captures and promotions
+    ///
+    /// Tables already present are reused
+    pub fn generate(&mut self, name: &str) -> Result<&EndgameTable, TablebaseError> {
+        let material = Material::from_name(name)?.canonical();
+        self.generate_material(&material)?;
+        self.tables
+            .get(&material.name())
+            .ok_or(TablebaseError::MissingTable)
+    }
+
+    fn generate_material(&mut self, material: &Material) -> Result<(), TablebaseError> {
+        if self.tables.contains_key(&material.name()) {
+            return Ok(());
+        }
+        for successor in material.successors() {
+            self.generate_material(&successor.canonical())?;
+        }
+        let table = generate::build(material, &self.tables)?;
+        self.tables.insert(material.name(), table);
+        Ok(())
+    }
+
+    /// Table of the material, either side may be the stronger one
+    pub fn table(&self, name: &str) -> Option<&EndgameTable> {
+        let material = Material::from_name(name).ok()?.canonical();
+        self.tables.get(&material.name())
+    }
+
+    /// Add a table, for example one read from the disk
+    pub fn insert(&mut self, table: EndgameTable) {
+        self.tables.insert(table.material.canonical().name(), table);
+    }
+
+    /// Distance to mate of the position
+    ///
+    /// En passant captures are resolved with the tables of the captures
+    pub fn probe(&self, cb: &Chessboard) -> Result<Dtm, TablebaseError> {
+        if cb.castling().as_u8() != 0 {
+            return Err(TablebaseError::CastlingRights);
+        }
+        if cb.occupied().popcnt() as usize > MAX_PIECES {
+            return Err(TablebaseError::TooManyPieces);
+        }
+
+        if has_en_passant(cb) {
+            let mut best = None;
+            for m in cb.to_vec() {
+                let dtm = self.probe(&cb.move_new(m))?.before_move();
+                best = best.max(Some(dtm));
+            }
+            return best.ok_or(TablebaseError::InvalidData);
+        }
+
+        self.lookup(&cb.into_raw())
+    }
+
+    fn lookup(&self, raw: &ChessboardRaw) -> Result<Dtm, TablebaseError> {
+        let material = Material::from_raw(raw).canonical();
+        let table = self
+            .tables
+            .get(&material.name())
+            .ok_or(TablebaseError::MissingTable)?;
+        table.value(raw).ok_or(TablebaseError::InvalidData)
+    }
+}
+
+fn has_en_passant(cb: &Chessboard) -> bool {
+    cb.en_passant().is_some()
+        && cb
+            .to_vec()
+            .into_iter()
+            .any(|m| matches!(m, Move::EnPassant { .. }))
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use crate::{
+    Chessboard, DrawType, GameResult, Side, WinType,
+    search::MATE,
+    syzygy::{Tablebase, Wdl},
+    uci::Score,
+};
+
+/// An engine loses when its own score stays below `-score` centipawns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResignRule {
+    /// Moves in a row of the engine
+    pub moves: u32,
+    pub score: i32,
+}
+
+/// The game is drawn when the scores of both engines stay within `score`
+/// centipawns of zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawRule {
+    /// Move number from which the rule applies
+    pub move_number: u32,
+    /// Moves in a row of each engine
+    pub moves: u32,
+    pub score: i32,
+}
+
+/// Rules ending games before the board does
+#[derive(Debug, Clone, Default)]
+pub struct Adjudication {
+    pub resign: Option<ResignRule>,
+    pub draw: Option<DrawRule>,
+    /// Positions the tables cover get their result
+    pub tablebase: Option<Arc<Tablebase>>,
+    /// Games are drawn after the moves of both sides with this number
+    pub max_moves: Option<u32>,
+}
+
+/// Score counts of a game being played
+#[derive(Debug)]
+pub struct Adjudicator<'a> {
+    rules: &'a Adjudication,
+    /// Moves in a row each side scored itself lost
+    losing: [u32; 2],
+    /// Plies in a row scored as drawn
+    drawn: u32,
+}
+
+impl<'a> Adjudicator<'a> {
+    pub const fn new(rules: &'a Adjudication) -> Self {
+        Self {
+            rules,
+            losing: [0; 2],
+            drawn: 0,
+        }
+    }
+
+    /// Result of the game after the side moved to the position with the score
+    pub fn update(&mut self, cb: &Chessboard, mover: Side, score: Option<Score>) -> GameResult {
+        // Number of the move played
+        let move_number = match mover {
+            Side::White => cb.full_moves(),
+            Side::Black => cb.full_moves() - 1,
+        };
+        let cp = score.map(|score| match score {
+            Score::Cp(cp) => cp,
+            Score::Mate(moves) if moves > 0 => MATE,
+            Score::Mate(_) => -MATE,
+        });
+
+        if let Some(rule) = self.rules.resign {
+            let losing = &mut self.losing[mover as usize];
+            *losing = match cp {
+                Some(cp) if cp <= -rule.score => *losing + 1,
+                _ => 0,
+            };
+            if *losing >= rule.moves {
+                return GameResult::win(mover.neg(), WinType::Adjudication);
+            }
+        }
+
+        if let Some(rule) = self.rules.draw {
+            self.drawn = match cp {
+                Some(cp) if move_number >= rule.move_number as usize && cp.abs() <= rule.score => {
+                    self.drawn + 1
+                }
+                _ => 0,
+            };
+            if self.drawn >= 2 * rule.moves {
+                return GameResult::Draw(DrawType::Adjudication);
+            }
+        }
+
+        if let Some(tablebase) = &self.rules.tablebase
+            && cb.occupied().popcnt() as usize <= tablebase.max_pieces()
+            && let Ok(wdl) = tablebase.probe_outcome(cb)
+        {
+            return match wdl {
+                Wdl::Win => GameResult::win(cb.side(), WinType::Adjudication),
+                Wdl::Loss => GameResult::win(cb.side().neg(), WinType::Adjudication),
+                Wdl::Draw | Wdl::CursedWin | Wdl::BlessedLoss => {
+                    GameResult::Draw(DrawType::Adjudication)
+                }
+            };
+        }
+
+        match self.rules.max_moves {
+            Some(max_moves) if move_number >= max_moves as usize && mover == Side::Black => {
+                GameResult::Draw(DrawType::Adjudication)
+            }
+            _ => GameResult::None,
+        }
+    }
+}
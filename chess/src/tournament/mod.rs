@@ -0,0 +1,355 @@
+//! Matches between engines
+//!
+//! Two engines play pairs of games from each opening with the colours
+//! swapped, refereed by this crate: moves are checked by a [`Game`] keeping
+//! the clock, and games are adjudicated by score thresholds and tablebases.
+//! Results give an [`Elo`] estimate and can end the match early with an
+//! [`Sprt`]
+
+mod adjudication;
+mod openings;
+mod sprt;
+
+use std::{
+    fmt, io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+pub use adjudication::{Adjudication, DrawRule, ResignRule};
+pub use openings::Opening;
+pub use sprt::{Elo, MatchStats, Sprt, SprtStatus, elo_from_score, score_from_elo};
+
+use crate::{
+    ChessError, GameResult, Side, WinType,
+    clock::{Bonus, Clock, SystemTime, TimeControl, TimeSource},
+    game::{Claim, Game},
+    pgn::Pgn,
+    uci::{BestMove, Engine, Go, Info, Score},
+};
+use adjudication::Adjudicator;
+
+/// Side of a match
+pub trait Player {
+    fn name(&self) -> String;
+
+    /// Forget the previous game
+    fn new_game(&mut self) -> io::Result<()>;
+
+    /// Best move in the current position of the game, searched with the limits
+    /// and answered before the time limit
+    fn think(&mut self, game: &Game, go: &Go, limit: Duration) -> io::Result<BestMove>;
+}
+
+impl Player for Engine {
+    fn name(&self) -> String {
+        self.name().unwrap_or("?").to_string()
+    }
+
+    fn new_game(&mut self) -> io::Result<()> {
+        Self::new_game(self)
+    }
+
+    fn think(&mut self, game: &Game, go: &Go, limit: Duration) -> io::Result<BestMove> {
+        self.set_position(game.start(), &game.played())?;
+        self.search(go, limit, |_| ())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchConfig {
+    /// Openings played in order, the starting position when empty
+    pub openings: Vec<Opening>,
+    /// Games, played in pairs from each opening with the colours swapped
+    pub games: usize,
+    /// Clock of both engines, without it every move gets the margin
+    pub time_control: Option<TimeControl>,
+    /// Limits sent with every `go` besides the clock, like a depth
+    pub limits: Go,
+    /// Time an engine may use beyond its clock before the search is stopped
+    pub margin: Duration,
+    /// Time read by the clock of each game
+    pub time_source: TimeSourceFactory,
+    pub adjudication: Adjudication,
+    /// Test ending the match once it is decided
+    pub sprt: Option<Sprt>,
+    /// Games played at the same time
+    pub concurrency: usize,
+    /// `Event` tag of the games
+    pub event: String,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            openings: Vec::new(),
+            games: 2,
+            time_control: None,
+            limits: Go::default(),
+            margin: Duration::from_secs(5),
+            time_source: TimeSourceFactory::default(),
+            adjudication: Adjudication::default(),
+            sprt: None,
+            concurrency: 1,
+            event: String::from("?"),
+        }
+    }
+}
+
+/// Makes the time source of a game clock, the time of the system by default
+#[derive(Clone)]
+pub struct TimeSourceFactory(Arc<dyn Fn() -> Arc<dyn TimeSource> + Send + Sync>);
+
+impl TimeSourceFactory {
+    pub fn new<T: TimeSource + 'static>(new: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move || Arc::new(new())))
+    }
+}
+
+impl Default for TimeSourceFactory {
+    fn default() -> Self {
+        Self::new(SystemTime::new)
+    }
+}
+
+impl fmt::Debug for TimeSourceFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TimeSourceFactory").finish_non_exhaustive()
+    }
+}
+
+/// Game of a match
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// Index of the game in the match, from 0
+    pub round: usize,
+    /// Engine playing white, 0 or 1
+    pub white: usize,
+    pub result: GameResult,
+    /// Moves with the score, depth and time of the engines in comments
+    pub pgn: Pgn,
+}
+
+/// Play the match between the players made for the engine index, 0 or 1
+///
+/// Each of the concurrent games gets its own pair of players. Games are given
+/// to the callback as they end, with the results so far of the first engine.
+/// Games ending after the SPRT is decided are not counted
+pub fn run<P, F>(
+    config: &MatchConfig,
+    new_player: F,
+    mut on_game: impl FnMut(&GameRecord, &MatchStats),
+) -> io::Result<MatchStats>
+where
+    P: Player,
+    F: Fn(usize) -> io::Result<P> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, games) = mpsc::channel();
+    let mut stats = MatchStats::default();
+    let mut error = None;
+
+    thread::scope(|scope| {
+        for _ in 0..config.concurrency.max(1) {
+            let sender = sender.clone();
+            let (next, stop, new_player) = (&next, &stop, &new_player);
+            scope.spawn(move || {
+                let players = new_player(0).and_then(|first| Ok([first, new_player(1)?]));
+                let mut players = match players {
+                    Ok(players) => players,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
+                };
+                while !stop.load(Ordering::Relaxed) {
+                    let round = next.fetch_add(1, Ordering::Relaxed);
+                    if round >= config.games {
+                        break;
+                    }
+                    if sender.send(Ok(play(config, &mut players, round))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for game in games {
+            let game = match game {
+                Ok(game) => game,
+                Err(e) => {
+                    stop.store(true, Ordering::Relaxed);
+                    error.get_or_insert(e);
+                    continue;
+                }
+            };
+            let first = if game.white == 0 {
+                Side::White
+            } else {
+                Side::Black
+            };
+            stats.add(game.result, first);
+            on_game(&game, &stats);
+            if let Some(sprt) = config.sprt
+                && sprt.status(&stats) != SprtStatus::Running
+            {
+                stop.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+    });
+
+    error.map_or(Ok(stats), Err)
+}
+
+/// Play the game of the round
+fn play<P: Player>(config: &MatchConfig, players: &mut [P; 2], round: usize) -> GameRecord {
+    let opening = match config.openings.len() {
+        0 => Opening::default(),
+        n => config.openings[round / 2 % n].clone(),
+    };
+    let white = round % 2;
+    let engine = |side: Side| match side {
+        Side::White => white,
+        Side::Black => 1 - white,
+    };
+
+    let mut game = Game::from_position(opening.start);
+    for m in opening.moves {
+        if game.make_move(m).is_err() {
+            break;
+        }
+    }
+    let mut comments = vec![None; game.ply()];
+    for side in [Side::White, Side::Black] {
+        if players[engine(side)].new_game().is_err() {
+            let _ = game.adjudicate(GameResult::win(side.neg(), WinType::Abandonment));
+        }
+    }
+    if let Some(control) = &config.time_control {
+        game.set_clock(Clock::new(control.clone(), (config.time_source.0)()));
+    }
+
+    let mut adjudicator = Adjudicator::new(&config.adjudication);
+    while !game.is_over() {
+        let side = game.position().side();
+        let limit = game
+            .clock()
+            .map_or(config.margin, |clock| clock.remaining(side) + config.margin);
+        let best = match players[engine(side)].think(&game, &go(config, &game), limit) {
+            Ok(best) => best,
+            // The flag fell while the engine was thinking
+            Err(_) if game.check_time() => break,
+            Err(_) => {
+                let _ = game.adjudicate(GameResult::win(side.neg(), WinType::Abandonment));
+                break;
+            }
+        };
+
+        match best.m.map(|m| game.make_move(m)) {
+            Some(Ok(_)) => (),
+            // The flag fell
+            Some(Err(ChessError::GameOver)) => break,
+            // An illegal move, or no move in a position that has one
+            _ => {
+                let _ = game.adjudicate(GameResult::win(side.neg(), WinType::Forfeit));
+                break;
+            }
+        }
+        let info = best.info.first();
+        let time = game.move_times().last().copied().flatten();
+        comments.push(comment(info, time));
+        // Drawn by the arbiter like a claim of the engine
+        if !game.is_over() {
+            let _ = game.claim_draw(Claim::FiftyMoves, None);
+        }
+        if !game.is_over() {
+            let result = adjudicator.update(game.position(), side, info.and_then(|i| i.score));
+            let _ = game.adjudicate(result);
+        }
+    }
+
+    let result = game.result();
+    let mut pgn = Pgn::from_game(&game);
+    pgn.comments = comments;
+    pgn.set_tag("Event", config.event.as_str());
+    pgn.set_tag("Date", today());
+    pgn.set_tag("Round", (round + 1).to_string());
+    pgn.set_tag("White", players[white].name());
+    pgn.set_tag("Black", players[1 - white].name());
+    if let Some(control) = &config.time_control {
+        pgn.set_tag("TimeControl", control.to_string());
+    }
+    pgn.set_tag("PlyCount", game.ply().to_string());
+    GameRecord {
+        round,
+        white,
+        result,
+        pgn,
+    }
+}
+
+/// Limits of the search in the current position of the game
+fn go(config: &MatchConfig, game: &Game) -> Go {
+    let mut go = config.limits.clone();
+    if let Some(clock) = game.clock() {
+        let increment = |side| match clock.period(side).bonus {
+            Bonus::Increment(increment) => Some(increment),
+            _ => None,
+        };
+        go.wtime = Some(clock.remaining(Side::White));
+        go.btime = Some(clock.remaining(Side::Black));
+        go.winc = increment(Side::White);
+        go.binc = increment(Side::Black);
+        go.movestogo = clock.moves_to_go(game.position().side());
+    }
+    go
+}
+
+/// Score, depth and time of the move like `+0.35/12 1.204s`
+fn comment(info: Option<&Info>, time: Option<Duration>) -> Option<String> {
+    let mut comment = match info.and_then(|i| i.score) {
+        Some(Score::Cp(cp)) => format!("{:+.2}", f64::from(cp) / 100.0),
+        Some(Score::Mate(moves)) if moves > 0 => format!("+M{moves}"),
+        Some(Score::Mate(moves)) => format!("-M{}", -moves),
+        None => String::new(),
+    };
+    if let Some(depth) = info.and_then(|i| i.depth)
+        && !comment.is_empty()
+    {
+        comment.push_str(&format!("/{depth}"));
+    }
+    if let Some(time) = time {
+        if !comment.is_empty() {
+            comment.push(' ');
+        }
+        comment.push_str(&format!("{:.3}s", time.as_secs_f64()));
+    }
+    (!comment.is_empty()).then_some(comment)
+}
+
+/// Date of the `Date` tag, in UTC
+fn today() -> String {
+    let Ok(elapsed) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) else {
+        return String::from("????.??.??");
+    };
+    // Days to the civil calendar, counting from the 1st of March of year 0
+    let days = (elapsed.as_secs() / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}.{month:02}.{day:02}")
+}
@@ -0,0 +1,79 @@
+use crate::{ChessError, Chessboard, Move, START_POS, pgn::Pgn};
+
+/// Position a pair of games starts from, with the moves leading to it
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub start: Chessboard,
+    pub moves: Vec<Move>,
+}
+
+impl Opening {
+    pub const fn new(start: Chessboard) -> Self {
+        Self {
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Position after the moves
+    pub fn position(&self) -> Chessboard {
+        let mut cb = self.start;
+        for &m in &self.moves {
+            cb.make_move(m);
+        }
+        cb
+    }
+
+    /// Positions of the lines of an EPD file
+    ///
+    /// The `hmvc` and `fmvn` operations set the move counters, the other
+    /// operations are ignored
+    pub fn parse_epd(text: &str) -> Result<Vec<Self>, ChessError> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                let position: Vec<_> = fields.by_ref().take(4).collect();
+                let operations: Vec<_> = fields.collect();
+                let operation = |name| {
+                    operations
+                        .iter()
+                        .position(|&op| op == name)
+                        .and_then(|i| operations.get(i + 1))
+                        .map(|value| value.trim_end_matches(';'))
+                };
+                let fen = format!(
+                    "{} {} {}",
+                    position.join(" "),
+                    operation("hmvc").unwrap_or("0"),
+                    operation("fmvn").unwrap_or("1")
+                );
+                Chessboard::from_fen(&fen).map(Self::new)
+            })
+            .collect()
+    }
+
+    /// Lines of the games of a PGN file, cut after the number of plies
+    pub fn parse_pgn(text: &str, plies: Option<usize>) -> Result<Vec<Self>, ChessError> {
+        Ok(Pgn::parse_all(text)?
+            .into_iter()
+            .map(|mut pgn| {
+                pgn.moves.truncate(plies.unwrap_or(usize::MAX));
+                Self {
+                    start: pgn.start,
+                    moves: pgn.moves,
+                }
+            })
+            .collect())
+    }
+}
+
+impl Default for Opening {
+    /// The starting position
+    fn default() -> Self {
+        let Ok(start) = Chessboard::from_fen(START_POS) else {
+            unreachable!()
+        };
+        Self::new(start)
+    }
+}
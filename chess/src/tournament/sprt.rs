@@ -0,0 +1,152 @@
+use crate::{GameResult, Side};
+
+/// Two-sided 95% quantile of the normal distribution
+const Z_95: f64 = 1.959_963_984_540_054;
+
+/// Results of the first engine against the second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+/// Elo difference and the half width of its 95% confidence interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elo {
+    pub diff: f64,
+    pub margin: f64,
+}
+
+/// Expected score of the Elo difference, from 0 to 1
+pub fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Elo difference of the expected score
+pub fn elo_from_score(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+impl MatchStats {
+    pub const fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Count the result of a game where the first engine played the side
+    pub const fn add(&mut self, result: GameResult, first: Side) {
+        match result.winner() {
+            Some(winner) if winner as u8 == first as u8 => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None if result.is_draw() => self.draws += 1,
+            None => (),
+        }
+    }
+
+    /// Points per game of the first engine, `0.5` without games
+    pub fn score(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(games),
+        }
+    }
+
+    /// Variance of the points of a game
+    fn variance(&self) -> f64 {
+        let games = f64::from(self.games());
+        let score = self.score();
+        let squares = (f64::from(self.wins) + f64::from(self.draws) / 4.0) / games;
+        score.mul_add(-score, squares)
+    }
+
+    /// Elo difference of the first engine, `None` without games or when one
+    /// engine scored everything
+    pub fn elo(&self) -> Option<Elo> {
+        let score = self.score();
+        if self.games() == 0 || score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+        let error = Z_95 * (self.variance() / f64::from(self.games())).sqrt();
+        let (low, high) = (score - error, score + error);
+        let margin = if low > 0.0 && high < 1.0 {
+            (elo_from_score(high) - elo_from_score(low)) / 2.0
+        } else {
+            f64::INFINITY
+        };
+        Some(Elo {
+            diff: elo_from_score(score),
+            margin,
+        })
+    }
+}
+
+/// Sequential probability ratio test of the Elo difference of the first
+/// engine, `elo0` being the null hypothesis and `elo1` the alternative
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// Probability to accept `elo1` when `elo0` holds
+    pub alpha: f64,
+    /// Probability to accept `elo0` when `elo1` holds
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SprtStatus {
+    /// More games are needed
+    Running,
+    AcceptH0,
+    AcceptH1,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 5.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl Sprt {
+    /// Log-likelihood ratios accepting `elo0` and `elo1`
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// Log-likelihood ratio of the results, with the normal approximation
+    /// of the generalized SPRT
+    ///
+    /// Half a win and half a loss are added to the results, so the variance
+    /// stays above zero when every game ended the same way
+    pub fn llr(&self, stats: &MatchStats) -> f64 {
+        if stats.games() == 0 {
+            return 0.0;
+        }
+        let wins = f64::from(stats.wins) + 0.5;
+        let draws = f64::from(stats.draws);
+        let games = f64::from(stats.games()) + 1.0;
+        let score = draws.mul_add(0.5, wins) / games;
+        let variance = score.mul_add(-score, draws.mul_add(0.25, wins) / games);
+        let (score0, score1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        games * (score1 - score0) * (2.0f64.mul_add(score, -score0) - score1) / (2.0 * variance)
+    }
+
+    pub fn status(&self, stats: &MatchStats) -> SprtStatus {
+        let llr = self.llr(stats);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtStatus::AcceptH1
+        } else if llr <= lower {
+            SprtStatus::AcceptH0
+        } else {
+            SprtStatus::Running
+        }
+    }
+}
@@ -0,0 +1,656 @@
+//! Client for engines speaking UCI
+//!
+//! An [`Engine`] runs the binary as a child process. Its output is read on a
+//! thread, so every answer is awaited with a timeout and a hung engine cannot
+//! block the caller. Moves going to the engine and coming back are checked
+//! against the move generator
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    process::{Child, ChildStdin, Command, ExitStatus, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Chessboard, Move, MoveGen, START_POS};
+
+/// Time given to the engine to answer `uci`, `isready` and `stop`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Long algebraic notation of the move, like `e2e4` or `e7e8q`
+///
+/// The null move is `0000`. Returns `None` for the editor moves
+pub fn format_move(m: Move) -> Option<String> {
+    let (from, to, promotion) = match m {
+        Move::Standard { from, to, .. }
+        | Move::EnPassant { from, to }
+        | Move::Castling { from, to, .. } => (from, to, None),
+        Move::Promotion {
+            from,
+            to,
+            promotion,
+        } => (from, to, Some(promotion.piece_type().to_char())),
+        Move::Null => return Some(String::from("0000")),
+        Move::Put { .. } | Move::Remove { .. } => return None,
+    };
+    let mut uci = String::with_capacity(5);
+    uci.extend(from.to_chars());
+    uci.extend(to.to_chars());
+    uci.extend(promotion);
+    Some(uci)
+}
+
+/// Evaluation reported by the engine, for the side to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Score {
+    /// Centipawns
+    Cp(i32),
+    /// Mate in moves, negative when the side to move is mated
+    Mate(i32),
+}
+
+/// The score is only a bound of the real one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bound {
+    Lower,
+    Upper,
+}
+
+/// Search progress of an `info` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    /// Rank of the line, `1` without MultiPV
+    pub multipv: u32,
+    pub score: Option<Score>,
+    pub bound: Option<Bound>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time: Option<Duration>,
+    /// Permill of the hash table in use
+    pub hashfull: Option<u32>,
+    pub tbhits: Option<u64>,
+    pub currmove: Option<Move>,
+    /// Legal part of the principal variation
+    pub pv: Vec<Move>,
+    /// Free text, the rest of the line
+    pub string: Option<String>,
+}
+
+const INFO_KEYWORDS: &[&str] = &[
+    "depth",
+    "seldepth",
+    "time",
+    "nodes",
+    "pv",
+    "multipv",
+    "score",
+    "currmove",
+    "currmovenumber",
+    "hashfull",
+    "nps",
+    "tbhits",
+    "sbhits",
+    "cpuload",
+    "string",
+    "refutation",
+    "currline",
+];
+
+impl Default for Info {
+    fn default() -> Self {
+        Self {
+            depth: None,
+            seldepth: None,
+            multipv: 1,
+            score: None,
+            bound: None,
+            nodes: None,
+            nps: None,
+            time: None,
+            hashfull: None,
+            tbhits: None,
+            currmove: None,
+            pv: Vec::new(),
+            string: None,
+        }
+    }
+}
+
+impl Info {
+    /// Parse the `info` line sent while searching the position
+    ///
+    /// Unknown fields are skipped. The principal variation stops before the
+    /// first move that is not legal
+    pub fn parse(line: &str, cb: &Chessboard) -> Option<Self> {
+        let mut tokens = line.split_whitespace().peekable();
+        if tokens.next()? != "info" {
+            return None;
+        }
+
+        let mut info = Self::default();
+        while let Some(token) = tokens.next() {
+            let mut number = || tokens.next().and_then(|n| n.parse::<u64>().ok());
+            match token {
+                "depth" => info.depth = number().map(|n| n as u32),
+                "seldepth" => info.seldepth = number().map(|n| n as u32),
+                "multipv" => info.multipv = number().map_or(1, |n| n as u32),
+                "nodes" => info.nodes = number(),
+                "nps" => info.nps = number(),
+                "tbhits" => info.tbhits = number(),
+                "hashfull" => info.hashfull = number().map(|n| n as u32),
+                "time" => info.time = number().map(Duration::from_millis),
+                "score" => {
+                    let kind = tokens.next();
+                    let value = tokens.next().and_then(|v| v.parse().ok());
+                    info.score = match (kind, value) {
+                        (Some("cp"), Some(cp)) => Some(Score::Cp(cp)),
+                        (Some("mate"), Some(mate)) => Some(Score::Mate(mate)),
+                        _ => None,
+                    };
+                    info.bound = match tokens.peek() {
+                        Some(&"lowerbound") => Some(Bound::Lower),
+                        Some(&"upperbound") => Some(Bound::Upper),
+                        _ => None,
+                    };
+                    if info.bound.is_some() {
+                        tokens.next();
+                    }
+                }
+                "currmove" => {
+                    info.currmove = tokens
+                        .next()
+                        .and_then(|m| MoveGen::new(cb.moves_cache).from_str(cb, m))
+                }
+                "pv" => {
+                    let mut pv = Vec::new();
+                    let mut position = Some(*cb);
+                    while let Some(m) = tokens.next_if(|t| !INFO_KEYWORDS.contains(t)) {
+                        position = position.and_then(|mut position| {
+                            let m = MoveGen::new(position.moves_cache).from_str(&position, m)?;
+                            position.try_make_move(m).ok()?;
+                            pv.push(m);
+                            Some(position)
+                        });
+                    }
+                    info.pv = pv;
+                }
+                "string" => {
+                    info.string = Some(tokens.by_ref().collect::<Vec<_>>().join(" "));
+                }
+                _ => (),
+            }
+        }
+        Some(info)
+    }
+}
+
+/// Type of an option and its default value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionKind {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, vars: Vec<String> },
+    Button,
+    String { default: String },
+}
+
+/// Option announced by the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: OptionKind,
+}
+
+impl EngineOption {
+    /// Parse the `option` line sent in the handshake
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next()? != "option" {
+            return None;
+        }
+
+        // Names and values can contain spaces, they run until the next keyword
+        let mut fields: Vec<(&str, Vec<&str>)> = Vec::new();
+        for token in tokens {
+            match token {
+                "name" | "type" | "default" | "min" | "max" | "var" => {
+                    fields.push((token, Vec::new()))
+                }
+                _ => fields.last_mut()?.1.push(token),
+            }
+        }
+        let field = |key| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, value)| value.join(" "))
+        };
+        let number = |key| field(key).and_then(|n| n.parse().ok());
+
+        let name = field("name").filter(|name| !name.is_empty())?;
+        let default = field("default")
+            .filter(|d| d != "<empty>")
+            .unwrap_or_default();
+        let kind = match field("type")?.as_str() {
+            "check" => OptionKind::Check {
+                default: default == "true",
+            },
+            "spin" => OptionKind::Spin {
+                default: default.parse().ok()?,
+                min: number("min")?,
+                max: number("max")?,
+            },
+            "combo" => OptionKind::Combo {
+                default,
+                vars: fields
+                    .iter()
+                    .filter(|(k, _)| *k == "var")
+                    .map(|(_, value)| value.join(" "))
+                    .collect(),
+            },
+            "button" => OptionKind::Button,
+            "string" => OptionKind::String { default },
+            _ => return None,
+        };
+        Some(Self { name, kind })
+    }
+
+    /// Whether the value can be set, buttons take no value
+    pub fn accepts(&self, value: Option<&str>) -> bool {
+        match (&self.kind, value) {
+            (OptionKind::Button, None) => true,
+            (OptionKind::Check { .. }, Some(value)) => value == "true" || value == "false",
+            (OptionKind::Spin { min, max, .. }, Some(value)) => value
+                .parse::<i64>()
+                .is_ok_and(|value| (*min..=*max).contains(&value)),
+            (OptionKind::Combo { vars, .. }, Some(value)) => {
+                vars.iter().any(|var| var.eq_ignore_ascii_case(value))
+            }
+            (OptionKind::String { .. }, Some(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Limits of a search, sent with `go`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Go {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    /// Search for a mate in moves
+    pub mate: Option<u32>,
+    pub movetime: Option<Duration>,
+    /// Search until `stop`
+    pub infinite: bool,
+    /// Search on the move expected from the opponent until `ponderhit` or `stop`
+    pub ponder: bool,
+    /// Only search these moves
+    pub searchmoves: Vec<Move>,
+}
+
+impl fmt::Display for Go {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "go")?;
+        if !self.searchmoves.is_empty() {
+            write!(f, " searchmoves")?;
+            for m in self.searchmoves.iter().filter_map(|&m| format_move(m)) {
+                write!(f, " {m}")?;
+            }
+        }
+        if self.ponder {
+            write!(f, " ponder")?;
+        }
+        let times = [
+            ("wtime", self.wtime),
+            ("btime", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+        ];
+        for (name, time) in times {
+            if let Some(time) = time {
+                write!(f, " {name} {}", time.as_millis())?;
+            }
+        }
+        let numbers = [
+            ("movestogo", self.movestogo.map(u64::from)),
+            ("depth", self.depth.map(u64::from)),
+            ("nodes", self.nodes),
+            ("mate", self.mate.map(u64::from)),
+            ("movetime", self.movetime.map(|t| t.as_millis() as u64)),
+        ];
+        for (name, number) in numbers {
+            if let Some(number) = number {
+                write!(f, " {name} {number}")?;
+            }
+        }
+        if self.infinite {
+            write!(f, " infinite")?;
+        }
+        Ok(())
+    }
+}
+
+/// Answer to `go`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestMove {
+    /// `None` when the engine has no legal move
+    pub m: Option<Move>,
+    /// Reply the engine expects
+    pub ponder: Option<Move>,
+    /// Last scored `info` of each line, by rank
+    pub info: Vec<Info>,
+}
+
+/// Engine running as a child process
+#[derive(Debug)]
+pub struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    name: Option<String>,
+    author: Option<String>,
+    options: Vec<EngineOption>,
+    /// Position of the last `position` command
+    position: Chessboard,
+    timeout: Duration,
+}
+
+impl Engine {
+    /// Run the binary and complete the handshake
+    pub fn spawn(program: impl AsRef<OsStr>) -> io::Result<Self> {
+        Self::new(Command::new(program), DEFAULT_TIMEOUT)
+    }
+
+    /// Run the command and complete the handshake, the engine has the timeout
+    /// to answer `uci`, `isready` and `stop`
+    pub fn new(mut command: Command, timeout: Duration) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::Error::other("engine pipes are not available"));
+        };
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let Ok(position) = Chessboard::from_fen(START_POS) else {
+            unreachable!()
+        };
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            name: None,
+            author: None,
+            options: Vec::new(),
+            position,
+            timeout,
+        };
+        engine.handshake()?;
+        Ok(engine)
+    }
+
+    fn handshake(&mut self) -> io::Result<()> {
+        self.send("uci")?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let line = self.read_line(deadline)?;
+            if line.trim() == "uciok" {
+                return Ok(());
+            }
+            if let Some(option) = EngineOption::parse(&line) {
+                self.options.push(option);
+            } else if let Some(id) = line.trim().strip_prefix("id ") {
+                if let Some(name) = id.strip_prefix("name ") {
+                    self.name = Some(name.trim().to_string());
+                } else if let Some(author) = id.strip_prefix("author ") {
+                    self.author = Some(author.trim().to_string());
+                }
+            }
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn options(&self) -> &[EngineOption] {
+        &self.options
+    }
+
+    /// Option announced under the name, ignoring case
+    pub fn option(&self, name: &str) -> Option<&EngineOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Position the next search starts from
+    pub const fn position(&self) -> &Chessboard {
+        &self.position
+    }
+
+    pub const fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Set the option and wait for the engine to be ready
+    ///
+    /// Options the engine did not announce and values out of their range are
+    /// [`ErrorKind::InvalidInput`]
+    pub fn set_option(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        let option = self.option(name).ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, format!("unknown option {name}"))
+        })?;
+        if !option.accepts(value) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid value for option {name}"),
+            ));
+        }
+        let mut command = format!("setoption name {}", option.name);
+        if let Some(value) = value {
+            command.push_str(" value ");
+            command.push_str(value);
+        }
+        self.send(&command)?;
+        self.is_ready()
+    }
+
+    /// Wait until the engine answers `isready`
+    pub fn is_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.read_line(deadline)?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    /// Tell the engine the next search is from another game
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Send the position after the moves played from the start
+    ///
+    /// Illegal moves are [`ErrorKind::InvalidInput`] and nothing is sent
+    pub fn set_position(&mut self, start: &Chessboard, moves: &[Move]) -> io::Result<()> {
+        let mut command = match start.get_fen() {
+            fen if fen == START_POS => String::from("position startpos"),
+            fen => format!("position fen {fen}"),
+        };
+        if !moves.is_empty() {
+            command.push_str(" moves");
+        }
+        let mut position = *start;
+        for &m in moves {
+            position
+                .try_make_move(m)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidInput, format!("{e:?}")))?;
+            command.push(' ');
+            command.push_str(&format_move(m).unwrap_or_default());
+        }
+        self.send(&command)?;
+        self.position = position;
+        Ok(())
+    }
+
+    /// Start searching the position, answered by [`Engine::wait`]
+    pub fn go(&mut self, go: &Go) -> io::Result<()> {
+        self.send(&go.to_string())
+    }
+
+    /// The opponent played the expected move, the ponder search goes on as a
+    /// normal one
+    pub fn ponder_hit(&mut self) -> io::Result<()> {
+        self.send("ponderhit")
+    }
+
+    /// Ask the engine to end the search, it still answers with `bestmove`
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.send("stop")
+    }
+
+    /// Wait for the best move, passing every `info` line to the callback
+    ///
+    /// The search is stopped after the time limit, an engine that does not
+    /// answer `stop` in time is [`ErrorKind::TimedOut`]. An illegal best move
+    /// is [`ErrorKind::InvalidData`]
+    pub fn wait(
+        &mut self,
+        limit: Duration,
+        mut on_info: impl FnMut(&Info),
+    ) -> io::Result<BestMove> {
+        let mut deadline = Instant::now() + limit;
+        let mut stopped = false;
+        let mut info: Vec<Info> = Vec::new();
+        loop {
+            let line = match self.read_line(deadline) {
+                Ok(line) => line,
+                Err(e) if e.kind() == ErrorKind::TimedOut && !stopped => {
+                    self.stop()?;
+                    stopped = true;
+                    deadline = Instant::now() + self.timeout;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if let Some(line) = Info::parse(&line, &self.position) {
+                on_info(&line);
+                if line.score.is_some() {
+                    match info.iter_mut().find(|i| i.multipv == line.multipv) {
+                        Some(i) => *i = line,
+                        None => info.push(line),
+                    }
+                }
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            if tokens.next() != Some("bestmove") {
+                continue;
+            }
+            let invalid =
+                |m: &str| io::Error::new(ErrorKind::InvalidData, format!("illegal best move {m}"));
+            let mut position = self.position;
+            let m = match tokens.next() {
+                None | Some("(none)" | "0000") => None,
+                Some(m) => {
+                    let parsed = MoveGen::new(position.moves_cache)
+                        .from_str(&position, m)
+                        .filter(|&m| position.try_make_move(m).is_ok());
+                    Some(parsed.ok_or_else(|| invalid(m))?)
+                }
+            };
+            // A wrong ponder move is only a hint, it is dropped
+            let ponder = match (m, tokens.next(), tokens.next()) {
+                (Some(_), Some("ponder"), Some(reply)) => MoveGen::new(position.moves_cache)
+                    .from_str(&position, reply)
+                    .filter(|&reply| position.try_make_move(reply).is_ok()),
+                _ => None,
+            };
+            info.sort_by_key(|i| i.multipv);
+            return Ok(BestMove { m, ponder, info });
+        }
+    }
+
+    /// Search the position with the limits, see [`Engine::wait`]
+    pub fn search(
+        &mut self,
+        go: &Go,
+        limit: Duration,
+        on_info: impl FnMut(&Info),
+    ) -> io::Result<BestMove> {
+        self.go(go)?;
+        self.wait(limit, on_info)
+    }
+
+    /// Ask the engine to exit, killing it after the timeout
+    pub fn quit(mut self) -> io::Result<ExitStatus> {
+        self.shut_down()
+    }
+
+    fn shut_down(&mut self) -> io::Result<ExitStatus> {
+        // The engine may be gone already
+        let _ = self.send("quit");
+        let deadline = Instant::now() + self.timeout;
+        while Instant::now() < deadline {
+            if let Some(status) = self.child.try_wait()? {
+                return Ok(status);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        self.child.kill()?;
+        self.child.wait()
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{command}")?;
+        self.stdin.flush()
+    }
+
+    fn read_line(&self, deadline: Instant) -> io::Result<String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => {
+                io::Error::new(ErrorKind::TimedOut, "engine did not answer")
+            }
+            RecvTimeoutError::Disconnected => {
+                io::Error::new(ErrorKind::UnexpectedEof, "engine exited")
+            }
+        })
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.shut_down();
+        }
+    }
+}
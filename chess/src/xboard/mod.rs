@@ -0,0 +1,327 @@
+//! Engine side of the xboard protocol (CECP)
+//!
+//! [`XBoard`] answers the commands of an xboard or WinBoard interface with the
+//! alpha-beta [`Search`], keeping the moves in a [`Game`] so they can be taken
+//! back. Searches run on the calling thread: commands sent while the engine
+//! thinks are answered after its move
+
+use std::{
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    DrawType, GameResult, Move, MoveGen, Side, WinType,
+    clock::{Bonus, Period},
+    eval::Evaluator,
+    game::{Claim, Game},
+    search::{Limit, MATE, Search, is_mate},
+    uci::format_move,
+};
+
+/// Name given to the interface
+const NAME: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+/// Depth searched without a time control or a depth limit
+const DEFAULT_DEPTH: u32 = 6;
+/// Moves the remaining time is shared between when the control has no count
+const MOVES_TO_GO: u32 = 30;
+/// Time kept back from every search for the answer to arrive
+const MARGIN: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone)]
+pub struct XBoard<E> {
+    game: Game,
+    search: Search<E>,
+    /// Side the engine plays, `None` in force mode
+    engine: Option<Side>,
+    /// Time control of `level`
+    level: Option<Period>,
+    /// Time per move of `st`
+    move_time: Option<Duration>,
+    /// Depth limit of `sd`
+    depth: Option<u32>,
+    /// Clock of the engine, as last told by `time`
+    time: Option<Duration>,
+    /// Clock of the opponent, as last told by `otim`
+    opponent_time: Option<Duration>,
+    /// Whether thinking output is sent
+    post: bool,
+}
+
+impl<E: Evaluator> XBoard<E> {
+    /// Engine playing black from the starting position
+    pub fn new(eval: E) -> Self {
+        Self {
+            game: Game::new(),
+            search: Search::new(eval),
+            engine: Some(Side::Black),
+            level: None,
+            move_time: None,
+            depth: None,
+            time: None,
+            opponent_time: None,
+            post: false,
+        }
+    }
+
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Side played by the engine, `None` in force mode
+    pub const fn engine(&self) -> Option<Side> {
+        self.engine
+    }
+
+    /// Clocks of the engine and of its opponent, as told by the interface
+    pub const fn clocks(&self) -> (Option<Duration>, Option<Duration>) {
+        (self.time, self.opponent_time)
+    }
+
+    /// Answer commands until `quit` or the end of the input
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let running = self.handle(&line?, &mut output)?;
+            output.flush()?;
+            if !running {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer the command, returning `false` after `quit`
+    pub fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match command {
+            "" | "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "name" | "rating" | "ics" | "draw" | "?" | "." => (),
+            "protover" => writeln!(
+                out,
+                "feature myname=\"{NAME}\" ping=1 setboard=1 usermove=1 playother=1 san=0 \
+                 time=1 draw=0 sigint=0 sigterm=0 colors=0 analyze=0 reuse=1 done=1"
+            )?,
+            "ping" => writeln!(out, "pong {args}")?,
+            "new" => {
+                self.game = Game::new();
+                self.engine = Some(Side::Black);
+                self.depth = None;
+                self.time = None;
+                self.opponent_time = None;
+            }
+            "force" | "result" => self.engine = None,
+            "go" => {
+                self.engine = Some(self.game.position().side());
+                self.think(out)?;
+            }
+            "playother" => self.engine = Some(self.game.position().side().neg()),
+            "usermove" => self.user_move(args, out)?,
+            "setboard" => match Game::from_fen(args) {
+                Ok(game) => self.game = game,
+                Err(_) => writeln!(out, "tellusererror Illegal position")?,
+            },
+            "undo" => {
+                self.game.undo();
+            }
+            "remove" => {
+                self.game.undo();
+                self.game.undo();
+            }
+            "level" => match parse_level(args) {
+                Some(level) => {
+                    self.level = Some(level);
+                    self.move_time = None;
+                }
+                None => writeln!(out, "Error (bad argument): {line}")?,
+            },
+            "st" => match seconds(args) {
+                Some(time) => self.move_time = Some(time),
+                None => writeln!(out, "Error (bad argument): {line}")?,
+            },
+            "sd" => match args.parse() {
+                Ok(depth) => self.depth = Some(depth),
+                Err(_) => writeln!(out, "Error (bad argument): {line}")?,
+            },
+            "time" | "otim" => match args.parse::<u64>() {
+                Ok(centiseconds) => {
+                    let time = Some(Duration::from_millis(centiseconds * 10));
+                    if command == "time" {
+                        self.time = time;
+                    } else {
+                        self.opponent_time = time;
+                    }
+                }
+                Err(_) => writeln!(out, "Error (bad argument): {line}")?,
+            },
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "quit" => return Ok(false),
+            // Interfaces not accepting `usermove` send the bare move
+            _ if args.is_empty() && self.parse_move(command).is_some() => {
+                self.user_move(command, out)?;
+            }
+            _ => writeln!(out, "Error (unknown command): {command}")?,
+        }
+        Ok(true)
+    }
+
+    /// Play the move of the opponent, answering it when the engine is to move
+    fn user_move(&mut self, m: &str, out: &mut impl Write) -> io::Result<()> {
+        let played = self
+            .parse_move(m)
+            .is_some_and(|legal| self.game.make_move(legal).is_ok());
+        if !played {
+            return writeln!(out, "Illegal move: {m}");
+        }
+        self.announce(out)?;
+        self.think(out)
+    }
+
+    fn parse_move(&self, m: &str) -> Option<Move> {
+        let cb = self.game.position();
+        MoveGen::new(cb.moves_cache)
+            .from_str(cb, m)
+            .filter(|&m| m != Move::Null)
+    }
+
+    /// Search and play a move when the engine is to move
+    fn think(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let cb = *self.game.position();
+        if self.engine != Some(cb.side()) || self.game.is_over() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let budget = self.budget();
+        let limit = match (self.depth, budget) {
+            (Some(depth), _) => Limit::Depth(depth),
+            // Deepening until the time is up
+            (None, Some(_)) => Limit::Nodes(u64::MAX),
+            (None, None) => Limit::Depth(DEFAULT_DEPTH),
+        };
+        self.search.set_history(self.game.history());
+        self.search
+            .set_deadline(budget.map(|budget| start + budget));
+        let result = self.search.search(&cb, limit);
+        let Some(m) = result.best_move else {
+            return Ok(());
+        };
+
+        if self.post {
+            let pv: Vec<_> = result.pv.iter().filter_map(|&m| format_move(m)).collect();
+            writeln!(
+                out,
+                "{} {} {} {} {}",
+                result.depth,
+                score(result.score),
+                start.elapsed().as_millis() / 10,
+                result.nodes,
+                pv.join(" ")
+            )?;
+        }
+        if self.game.make_move(m).is_err() {
+            return Ok(());
+        }
+        writeln!(out, "move {}", format_move(m).unwrap_or_default())?;
+
+        // Draws by repetition and by the fifty-move rule are claimed
+        if self.game.repetitions() >= 3 {
+            let _ = self.game.claim_draw(Claim::Threefold, None);
+        } else if self.game.position().half_moves() >= 100 {
+            let _ = self.game.claim_draw(Claim::FiftyMoves, None);
+        }
+        self.announce(out)
+    }
+
+    /// Time to spend on the next move, `None` without a time control
+    pub fn budget(&self) -> Option<Duration> {
+        if let Some(time) = self.move_time {
+            return Some(time.saturating_sub(MARGIN));
+        }
+        let level = self.level?;
+        let remaining = self.time.unwrap_or(level.time).saturating_sub(MARGIN);
+        let increment = match level.bonus {
+            Bonus::Increment(increment) => increment,
+            _ => Duration::ZERO,
+        };
+        let moves_to_go = match level.moves {
+            Some(moves) if moves > 0 => {
+                moves - (self.game.position().full_moves().saturating_sub(1) as u32 % moves)
+            }
+            _ => MOVES_TO_GO,
+        };
+        Some((remaining / moves_to_go + increment * 3 / 4).min(remaining / 2))
+    }
+
+    /// Send the result once the game is over
+    fn announce(&self, out: &mut impl Write) -> io::Result<()> {
+        let result = self.game.result();
+        reason(result).map_or(Ok(()), |reason| {
+            writeln!(out, "{} {{{reason}}}", result.pgn_result())
+        })
+    }
+}
+
+/// Control of `level MPS BASE INC`, the base in minutes or `minutes:seconds`
+/// and the increment in seconds
+fn parse_level(args: &str) -> Option<Period> {
+    let [moves, base, increment] = args.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let moves: u32 = moves.parse().ok()?;
+    let base = match base.split_once(':') {
+        Some((minutes, secs)) => seconds(minutes)? * 60 + seconds(secs)?,
+        None => seconds(base)? * 60,
+    };
+    let bonus = match seconds(increment)? {
+        Duration::ZERO => Bonus::None,
+        increment => Bonus::Increment(increment),
+    };
+    Some(match moves {
+        0 => Period::new(base, bonus),
+        moves => Period::with_moves(moves, base, bonus),
+    })
+}
+
+fn seconds(s: &str) -> Option<Duration> {
+    s.parse()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+}
+
+/// Score of the thinking output, mates are 100000 plus the moves to mate
+const fn score(score: i32) -> i32 {
+    if !is_mate(score) {
+        return score;
+    }
+    let moves = (MATE - score.abs() + 1) / 2;
+    score.signum() * (100_000 + moves)
+}
+
+/// Reason sent with the result, `None` while the game goes on
+const fn reason(result: GameResult) -> Option<&'static str> {
+    Some(match result {
+        GameResult::None => return None,
+        GameResult::WhiteWin(WinType::Checkmate) => "White mates",
+        GameResult::BlackWin(WinType::Checkmate) => "Black mates",
+        GameResult::WhiteWin(WinType::Resign) => "Black resigns",
+        GameResult::BlackWin(WinType::Resign) => "White resigns",
+        GameResult::WhiteWin(WinType::Timeout) => "Black forfeits on time",
+        GameResult::BlackWin(WinType::Timeout) => "White forfeits on time",
+        GameResult::WhiteWin(_) | GameResult::BlackWin(_) => "Adjudication",
+        GameResult::Draw(draw) => match draw {
+            DrawType::Stalemate => "Stalemate",
+            DrawType::Agreement => "Draw agreed",
+            DrawType::Repetition => "Draw by repetition",
+            DrawType::FivefoldRepetition => "Draw by fivefold repetition",
+            DrawType::FiftyMoveRule => "Draw by fifty move rule",
+            DrawType::SeventyFiveMoveRule => "Draw by seventy-five move rule",
+            DrawType::InsufficientMaterial => "Insufficient material",
+            DrawType::DeadPosition => "Dead position",
+            DrawType::TimeoutVsInsufficientMaterial => "Time forfeit, insufficient material",
+            DrawType::Adjudication => "Adjudication",
+        },
+    })
+}
@@ -0,0 +1,140 @@
+use std::io::Cursor;
+
+use chess::book::{Book, BookBuilder, BookEntry, BookFile, decode_move, encode_move, polyglot_key};
+use chess::{
+    Castling, Chessboard, DrawType, GameResult, Move, MoveGen, Piece, START_POS, Square, WinType,
+};
+
+fn parse_moves(start: &Chessboard, moves: &[&str]) -> Vec<Move> {
+    let mut board = *start;
+    let mut parsed = Vec::new();
+    for m in moves {
+        let m = MoveGen::new(board.moves_cache).from_str(&board, m).unwrap();
+        board.make_move(m);
+        parsed.push(m);
+    }
+    parsed
+}
+
+#[test]
+fn reference_keys() {
+    const KEYS: &[(&str, u64)] = &[
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b_9618_1691_fc9c),
+        ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c_9b50_fd11_4196),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756_b944_61c5_0fb0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662f_afb9_65db_29d4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a4_8b5a_8e47_ff78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a_607c_a3f2_42c1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fd_d303_c946_bdd9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c81_23ea_7b06_7637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f_9b82_9b27_9560),
+    ];
+
+    for (fen, key) in KEYS {
+        let board = Chessboard::from_fen(fen).unwrap();
+        assert_eq!(polyglot_key(&board), *key, "{fen}");
+    }
+}
+
+#[test]
+fn move_encoding() {
+    let board = Chessboard::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    let castling = Move::Castling {
+        castling: Castling::QueenSide,
+        from: Square::E1,
+        to: Square::C1,
+    };
+    let encoded = encode_move(castling).unwrap();
+    assert_eq!(encoded, 4 << 6);
+    assert_eq!(decode_move(&board, encoded), Some(castling));
+
+    let promotion = Move::Promotion {
+        from: Square::B7,
+        to: Square::A8,
+        promotion: Piece::WHITE_KNIGHT,
+    };
+    let encoded = encode_move(promotion).unwrap();
+    assert_eq!(decode_move(&board, encoded), Some(promotion));
+
+    let rook = Move::Standard {
+        piece: Piece::WHITE_ROOK,
+        from: Square::H1,
+        to: Square::H8,
+    };
+    assert_eq!(decode_move(&board, encode_move(rook).unwrap()), Some(rook));
+
+    assert_eq!(encode_move(Move::Null), None);
+    // Illegal in the position
+    assert_eq!(decode_move(&board, encode_move(rook).unwrap() ^ 1), None);
+}
+
+#[test]
+fn build_and_probe() {
+    let start = Chessboard::from_fen(START_POS).unwrap();
+    let mut builder = BookBuilder::new().max_ply(4);
+
+    let e4 = parse_moves(&start, &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]);
+    let d4 = parse_moves(&start, &["d2d4", "d7d5"]);
+    builder
+        .add_game(start, &e4, GameResult::WhiteWin(WinType::Checkmate))
+        .unwrap();
+    builder.add_game(start, &e4, GameResult::Draw(DrawType::Stalemate)).unwrap();
+    builder
+        .add_game(start, &d4, GameResult::BlackWin(WinType::Resign))
+        .unwrap();
+
+    let book = builder.build();
+    // d2d4 lost and is dropped, Bb5 is past the ply limit
+    assert_eq!(book.entries().len(), 5);
+    assert_eq!(book.moves(&start), vec![(e4[0], 3)]);
+    assert_eq!(book.best_move(&start), Some(e4[0]));
+
+    let after_d4 = start.move_new(d4[0]);
+    assert_eq!(book.moves(&after_d4), vec![(d4[1], 2)]);
+
+    let bytes = book.to_bytes();
+    assert_eq!(Book::from_bytes(&bytes).unwrap(), book);
+    assert!(Book::from_bytes(&bytes[1..]).is_err());
+
+    let mut file = BookFile::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(file.len(), 5);
+    assert_eq!(file.moves(&start).unwrap(), vec![(e4[0], 3)]);
+    assert_eq!(file.probe(0).unwrap(), vec![]);
+}
+
+#[test]
+fn weighted_selection() {
+    let start = Chessboard::from_fen(START_POS).unwrap();
+    let key = polyglot_key(&start);
+    let e4 = parse_moves(&start, &["e2e4"])[0];
+    let d4 = parse_moves(&start, &["d2d4"])[0];
+    let book = Book::new(vec![
+        BookEntry {
+            key,
+            mv: encode_move(e4).unwrap(),
+            weight: 1,
+            learn: 0,
+        },
+        BookEntry {
+            key,
+            mv: encode_move(d4).unwrap(),
+            weight: 3,
+            learn: 0,
+        },
+    ]);
+
+    assert_eq!(book.weighted_move(&start, 0), Some(d4));
+    assert_eq!(book.weighted_move(&start, 2), Some(d4));
+    assert_eq!(book.weighted_move(&start, 3), Some(e4));
+    assert_eq!(book.weighted_move(&start, 4), None);
+
+    let mut rng = fastrand::Rng::with_seed(7);
+    let mut e4_count = 0;
+    for _ in 0..1000 {
+        if book.random_move(&start, &mut rng) == Some(e4) {
+            e4_count += 1;
+        }
+    }
+    assert!((150..350).contains(&e4_count));
+}
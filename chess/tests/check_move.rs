@@ -0,0 +1,188 @@
+use chess::{Chessboard, KIWIPETE_POS, Move, Piece, START_POS, Square};
+use types::{Castling, IllegalMoveReason, PieceType, Rank, Side};
+
+fn board(fen: &str) -> Chessboard {
+    Chessboard::from_fen(fen).unwrap()
+}
+
+fn guess_move(cb: &Chessboard, from: Square, to: Square) -> Option<Move> {
+    let piece = cb.at(from)?;
+    let last_rank = match piece.side() {
+        Side::White => Rank::Eighth,
+        Side::Black => Rank::First,
+    };
+    let m = match piece.piece_type() {
+        PieceType::Pawn if to.rank() == last_rank => Move::Promotion {
+            from,
+            to,
+            promotion: Piece::from_side_and_type(piece.side(), PieceType::Queen),
+        },
+        PieceType::Pawn if cb.en_passant() == Some(to) => Move::EnPassant { from, to },
+        PieceType::King if matches!((from, to), (Square::E1, Square::G1) | (Square::E8, Square::G8)) => {
+            Move::Castling {
+                castling: Castling::KingSide,
+                from,
+                to,
+            }
+        }
+        PieceType::King if matches!((from, to), (Square::E1, Square::C1) | (Square::E8, Square::C8)) => {
+            Move::Castling {
+                castling: Castling::QueenSide,
+                from,
+                to,
+            }
+        }
+        _ => Move::Standard { piece, from, to },
+    };
+    Some(m)
+}
+
+#[test]
+fn matches_move_generation() {
+    const FENS: &[&str] = &[
+        START_POS,
+        KIWIPETE_POS,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "4k3/8/8/KPp4r/8/8/8/8 w - c6 0 1",
+        "4r1K1/4k1B1/8/8/8/8/8/6r1 w - - 0 1",
+    ];
+
+    for fen in FENS {
+        let cb = board(fen);
+        let legal = cb.to_vec();
+        for from in Square::ALL {
+            for to in Square::ALL {
+                let Some(m) = guess_move(&cb, from, to) else {
+                    continue;
+                };
+                assert_eq!(
+                    cb.check_move(m).is_ok(),
+                    legal.contains(&m),
+                    "{fen} {m:?} {:?}",
+                    cb.check_move(m)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn reasons() {
+    let cb = board(START_POS);
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::BLACK_PAWN,
+            from: Square::E7,
+            to: Square::E5,
+        }),
+        Err(IllegalMoveReason::WrongSideToMove)
+    );
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_PAWN,
+            from: Square::E4,
+            to: Square::E5,
+        }),
+        Err(IllegalMoveReason::NoPieceOnOrigin)
+    );
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_KNIGHT,
+            from: Square::G1,
+            to: Square::G3,
+        }),
+        Err(IllegalMoveReason::InvalidMovement)
+    );
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_BISHOP,
+            from: Square::C1,
+            to: Square::E3,
+        }),
+        Err(IllegalMoveReason::PathBlocked)
+    );
+    assert_eq!(
+        cb.check_move(Move::Remove { from: Square::E2 }),
+        Err(IllegalMoveReason::NotAGameMove)
+    );
+}
+
+#[test]
+fn king_safety() {
+    let cb = board("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1");
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_BISHOP,
+            from: Square::E2,
+            to: Square::D3,
+        }),
+        Err(IllegalMoveReason::PinnedPiece)
+    );
+
+    let cb = board("4k3/8/8/8/8/8/8/3rK2N w - - 0 1");
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_KNIGHT,
+            from: Square::H1,
+            to: Square::G3,
+        }),
+        Err(IllegalMoveReason::LeavesKingInCheck)
+    );
+}
+
+#[test]
+fn castling() {
+    let castle = Move::Castling {
+        castling: Castling::KingSide,
+        from: Square::E1,
+        to: Square::G1,
+    };
+    assert_eq!(
+        board("4k3/8/8/8/8/8/8/4K2R w - - 0 1").check_move(castle),
+        Err(IllegalMoveReason::NoCastlingRight)
+    );
+    assert_eq!(
+        board("4k3/8/8/8/8/8/8/4KB1R w K - 0 1").check_move(castle),
+        Err(IllegalMoveReason::PathBlocked)
+    );
+    assert_eq!(
+        board("4k3/8/8/8/8/8/8/r3K2R w K - 0 1").check_move(castle),
+        Err(IllegalMoveReason::CastlingOutOfCheck)
+    );
+    assert_eq!(
+        board("4kr2/8/8/8/8/8/8/4K2R w K - 0 1").check_move(castle),
+        Err(IllegalMoveReason::CastlingThroughCheck)
+    );
+    assert_eq!(board("4k3/8/8/8/8/8/8/4K2R w K - 0 1").check_move(castle), Ok(()));
+}
+
+#[test]
+fn promotion() {
+    let cb = board("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(
+        cb.check_move(Move::Standard {
+            piece: Piece::WHITE_PAWN,
+            from: Square::A7,
+            to: Square::A8,
+        }),
+        Err(IllegalMoveReason::MissingPromotion)
+    );
+    assert_eq!(
+        cb.check_move(Move::Promotion {
+            from: Square::A7,
+            to: Square::A8,
+            promotion: Piece::WHITE_KING,
+        }),
+        Err(IllegalMoveReason::InvalidPromotion)
+    );
+    assert_eq!(
+        cb.check_move(Move::Promotion {
+            from: Square::A7,
+            to: Square::A8,
+            promotion: Piece::WHITE_KNIGHT,
+        }),
+        Ok(())
+    );
+}
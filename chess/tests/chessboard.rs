@@ -1,5 +1,7 @@
-use chess::{Chessboard, ChessboardRaw, EMPTY_POS, KIWIPETE_POS, Move, Piece, START_POS, Square};
-use types::{Castling, GameResult, WinType};
+use chess::{
+    Chessboard, ChessboardRaw, EMPTY_POS, KIWIPETE_POS, Move, Piece, START_POS, Side, Square,
+};
+use types::{Castling, ChessError, DrawType, GameResult, IllegalMoveReason, MoveOutcome, WinType};
 
 #[test]
 #[cfg(not(feature = "no_std"))]
@@ -26,6 +28,63 @@ fn game_over() {
     assert_eq!(chess.result, GameResult::BlackWin(WinType::Checkmate));
 }
 
+#[test]
+fn fifty_moves() {
+    let chess = Chessboard::from_fen("8/8/4k3/8/8/4K3/8/R7 w - - 99 80").unwrap();
+    assert_eq!(chess.result, GameResult::None);
+    let chess = Chessboard::from_fen("8/8/4k3/8/8/4K3/8/R7 w - - 100 80").unwrap();
+    assert_eq!(chess.result, GameResult::Draw(DrawType::FiftyMoveRule));
+}
+
+#[test]
+fn insufficient_material() {
+    let draw = GameResult::Draw(DrawType::InsufficientMaterial);
+    let cases = [
+        ("8/8/4k3/8/8/4K3/8/8 w - - 0 1", true),
+        ("8/8/4k3/8/8/2N1K3/8/8 w - - 0 1", true),
+        ("8/8/4k3/8/8/4K3/8/2B5 w - - 0 1", true),
+        // Any number of bishops on squares of one colour
+        ("1b6/b7/4k3/8/8/4K3/8/2B1B3 w - - 0 1", true),
+        ("8/8/4k3/8/8/4K3/8/2BB4 w - - 0 1", false),
+        ("8/3b4/4k3/8/8/4K3/8/2B5 w - - 0 1", false),
+        ("8/4n3/4k3/8/8/2N1K3/8/8 w - - 0 1", false),
+        ("8/4p3/4k3/8/8/4K3/8/2B5 w - - 0 1", false),
+        ("8/8/4k3/8/8/2NNK3/8/8 w - - 0 1", false),
+    ];
+    for (fen, dead) in cases {
+        let chess = Chessboard::from_fen(fen).unwrap();
+        assert_eq!(chess.insufficient_material(), dead, "{fen}");
+        assert_eq!(chess.result == draw, dead, "{fen}");
+    }
+
+    let chess = Chessboard::from_fen("8/4p3/4k3/8/8/4K3/8/2B5 w - - 0 1").unwrap();
+    assert!(chess.has_mating_material(Side::White));
+    assert!(chess.has_mating_material(Side::Black));
+    let chess = Chessboard::from_fen("8/4q3/4k3/8/8/4K3/8/8 w - - 0 1").unwrap();
+    assert!(!chess.has_mating_material(Side::White));
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn dead_positions() {
+    let cases = [
+        ("4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/8/4K3 w - - 0 1", true),
+        ("4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/8/4K3 b - - 0 1", true),
+        // The pawns stay covered even with the h-file open
+        ("4k3/8/1p1p1p2/pPpPpPp1/P1P1P1P1/8/8/4K3 w - - 0 1", true),
+        // The kings can walk up to the pawns and take them
+        ("4k3/8/8/p7/P7/8/8/4K3 w - - 0 1", false),
+        // Pawns can still move
+        ("4k3/8/1p1p1p1p/pPpPpPp1/P1P1P1PP/8/8/4K3 w - - 0 1", false),
+        ("8/8/4k3/8/8/4K3/8/8 w - - 0 1", true),
+        (START_POS, false),
+    ];
+    for (fen, dead) in cases {
+        let chess = Chessboard::from_fen(fen).unwrap();
+        assert_eq!(chess.is_dead_position(), dead, "{fen}");
+    }
+}
+
 #[test]
 #[cfg(not(feature = "no_std"))]
 fn new_empty() {
@@ -33,6 +92,22 @@ fn new_empty() {
     assert_eq!(chess.get_fen(), EMPTY_POS);
 }
 
+#[test]
+fn kings() {
+    assert_eq!(
+        Chessboard::from_fen(EMPTY_POS).err(),
+        Some(ChessError::InvalidKings)
+    );
+    assert_eq!(
+        Chessboard::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1").err(),
+        Some(ChessError::InvalidKings)
+    );
+    assert_eq!(
+        Chessboard::from_fen("4k3/8/8/8/8/8/8/K3K3 w - - 0 1").err(),
+        Some(ChessError::InvalidKings)
+    );
+}
+
 #[test]
 #[cfg(not(feature = "no_std"))]
 fn new_kiwipete() {
@@ -153,6 +228,15 @@ fn moves() {
                 to: Square::E3,
             },
         ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1",
+            Move::Standard {
+                piece: Piece::WHITE_ROOK,
+                from: Square::A1,
+                to: Square::A8,
+            },
+        ),
     ];
 
     for (fen, want, m) in MOVES {
@@ -160,6 +244,36 @@ fn moves() {
     }
 }
 
+#[test]
+fn rook_capture_clears_castling() {
+    let castles = |board: &Chessboard| {
+        board
+            .to_vec()
+            .into_iter()
+            .filter(|m| matches!(m, Move::Castling { .. }))
+            .count()
+    };
+
+    let mut board = Chessboard::from_fen("r3k2r/8/8/8/8/8/1B6/4K3 w kq - 0 1").unwrap();
+    assert!(board.make_move(Move::Standard {
+        piece: Piece::WHITE_BISHOP,
+        from: Square::B2,
+        to: Square::H8,
+    }));
+    assert_eq!(board.get_fen(), "r3k2B/8/8/8/8/8/8/4K3 b q - 0 1");
+    // Only the rook on a8 can castle
+    assert_eq!(castles(&board), 1);
+
+    // Capturing by promotion on the rook's square
+    let mut board = Chessboard::from_fen("r3k2r/6P1/8/8/8/8/8/4K3 w kq - 0 1").unwrap();
+    assert!(board.make_move(Move::Promotion {
+        from: Square::G7,
+        to: Square::H8,
+        promotion: Piece::WHITE_KNIGHT,
+    }));
+    assert_eq!(board.get_fen(), "r3k2N/8/8/8/8/8/8/4K3 b q - 0 1");
+}
+
 #[test]
 fn invalid_moves() {
     let mut board = Chessboard::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
@@ -169,3 +283,120 @@ fn invalid_moves() {
     };
     assert!(!board.make_move(m));
 }
+
+#[test]
+fn try_make_move() {
+    let mut board = Chessboard::from_fen("4k3/8/8/3p4/4P3/8/8/R3K3 w - - 0 1").unwrap();
+
+    let before = board;
+    let err = board.try_make_move(Move::Standard {
+        piece: Piece::WHITE_ROOK,
+        from: Square::A1,
+        to: Square::B2,
+    });
+    assert_eq!(
+        err,
+        Err(ChessError::IllegalMove(IllegalMoveReason::InvalidMovement))
+    );
+    assert_eq!(board, before);
+
+    let outcome = board
+        .try_make_move(Move::Standard {
+            piece: Piece::WHITE_PAWN,
+            from: Square::E4,
+            to: Square::D5,
+        })
+        .unwrap();
+    assert_eq!(
+        outcome,
+        MoveOutcome {
+            captured: Some(Piece::BLACK_PAWN),
+            gave_check: false,
+            result: GameResult::None,
+        }
+    );
+
+    board.make_move(Move::Standard {
+        piece: Piece::BLACK_KING,
+        from: Square::E8,
+        to: Square::D8,
+    });
+    let outcome = board
+        .try_make_move(Move::Standard {
+            piece: Piece::WHITE_ROOK,
+            from: Square::A1,
+            to: Square::A8,
+        })
+        .unwrap();
+    assert!(outcome.gave_check);
+    assert_eq!(outcome.result, GameResult::None);
+}
+
+#[test]
+fn try_make_move_game_over() {
+    let mut board = Chessboard::from_fen("k7/8/1K6/8/8/8/8/7Q w - - 0 1").unwrap();
+    let outcome = board
+        .try_make_move(Move::Standard {
+            piece: Piece::WHITE_QUEEN,
+            from: Square::H1,
+            to: Square::H8,
+        })
+        .unwrap();
+    assert_eq!(outcome.result, GameResult::WhiteWin(WinType::Checkmate));
+
+    let before = board;
+    let err = board.try_make_move(Move::Standard {
+        piece: Piece::BLACK_KING,
+        from: Square::A8,
+        to: Square::A7,
+    });
+    assert_eq!(err, Err(ChessError::GameOver));
+    assert_eq!(board, before);
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn null_move() {
+    let mut board =
+        Chessboard::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2")
+            .unwrap();
+    board.make_move(Move::Standard {
+        piece: Piece::BLACK_PAWN,
+        from: Square::F7,
+        to: Square::F5,
+    });
+    let before = board;
+
+    let en_passant = board.make_null_move().unwrap();
+    assert_eq!(en_passant, Some(Square::F6));
+    assert_eq!(
+        board.get_fen(),
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+    );
+    assert_eq!(board.moves_cache.side, types::Side::Black);
+
+    board.unmake_null_move(en_passant);
+    assert_eq!(board, before);
+}
+
+#[test]
+fn null_move_in_check() {
+    let mut board = Chessboard::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    let before = board;
+    assert_eq!(
+        board.make_null_move(),
+        Err(ChessError::IllegalMove(IllegalMoveReason::LeavesKingInCheck))
+    );
+    assert_eq!(board, before);
+}
+
+#[test]
+fn null_move_uci() {
+    let board = Chessboard::from_fen(START_POS).unwrap();
+    let mut move_gen = chess::MoveGen::new(board.moves_cache);
+    assert_eq!(move_gen.from_str(&board, "0000"), Some(Move::Null));
+
+    let mut raw = board.into_raw();
+    raw.make_move(Move::Null);
+    assert_eq!(raw.side, types::Side::Black);
+}
@@ -0,0 +1,228 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::time::Duration;
+
+use chess::{
+    ChessError, DrawType, GameResult, MoveGen, Side, WinType,
+    clock::{Bonus, Clock, ManualTime, Period, TimeControl},
+    game::Game,
+};
+
+const fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+fn started(control: TimeControl) -> (Clock, ManualTime) {
+    let time = ManualTime::new();
+    let mut clock = Clock::new(control, time.clone());
+    clock.start(Side::White);
+    (clock, time)
+}
+
+#[test]
+fn parse_time_control() {
+    let control: TimeControl = "40/5400+30:1800+30".parse().unwrap();
+    assert_eq!(
+        control.periods(),
+        &[
+            Period::with_moves(40, secs(5400), Bonus::Increment(secs(30))),
+            Period::new(secs(1800), Bonus::Increment(secs(30))),
+        ]
+    );
+    assert_eq!(
+        "300+2".parse(),
+        Ok(TimeControl::fischer(secs(300), secs(2)))
+    );
+    assert_eq!("60".parse(), Ok(TimeControl::sudden_death(secs(60))));
+    assert_eq!(
+        "10+0.1".parse(),
+        Ok(TimeControl::fischer(secs(10), Duration::from_millis(100)))
+    );
+    assert_eq!(control.to_string(), "40/5400+30:1800+30");
+    assert_eq!(
+        TimeControl::fischer(secs(10), Duration::from_millis(100)).to_string(),
+        "10+0.1"
+    );
+    for bad in ["", "-", "40/", "5+", "a/60", "60:", "-5", "inf"] {
+        assert_eq!(
+            bad.parse::<TimeControl>(),
+            Err(ChessError::InvalidTimeControl),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn sudden_death() {
+    let (mut clock, time) = started(TimeControl::sudden_death(secs(60)));
+    time.advance(secs(10));
+    assert_eq!(clock.remaining(Side::White), secs(50));
+    assert_eq!(clock.press(), Some(secs(10)));
+    assert_eq!(clock.running(), Some(Side::Black));
+
+    time.advance(secs(59));
+    assert_eq!(clock.flagged(), None);
+    time.advance(secs(1));
+    assert_eq!(clock.flagged(), Some(Side::Black));
+    assert_eq!(clock.press(), None);
+    assert_eq!(clock.remaining(Side::Black), Duration::ZERO);
+    assert_eq!(clock.remaining(Side::White), secs(50));
+    assert_eq!(clock.running(), None);
+}
+
+#[test]
+fn bonuses() {
+    let (mut clock, time) = started(TimeControl::fischer(secs(60), secs(5)));
+    time.advance(secs(2));
+    clock.press();
+    assert_eq!(clock.remaining(Side::White), secs(63));
+
+    // Bronstein gives back the time used, up to the delay
+    let (mut clock, time) = started(TimeControl::bronstein(secs(60), secs(5)));
+    time.advance(secs(2));
+    clock.press();
+    assert_eq!(clock.remaining(Side::White), secs(60));
+    time.advance(secs(8));
+    clock.press();
+    assert_eq!(clock.remaining(Side::Black), secs(57));
+
+    // Simple delay does not run the clock for the delay
+    let (mut clock, time) = started(TimeControl::delay(secs(60), secs(5)));
+    time.advance(secs(3));
+    assert_eq!(clock.remaining(Side::White), secs(60));
+    clock.press();
+    time.advance(secs(8));
+    assert_eq!(clock.remaining(Side::Black), secs(57));
+    clock.stop();
+    assert_eq!(clock.remaining(Side::Black), secs(57));
+    assert_eq!(clock.running(), None);
+}
+
+#[test]
+fn periods() {
+    let control = TimeControl::new(vec![
+        Period::with_moves(2, secs(60), Bonus::None),
+        Period::new(secs(30), Bonus::Increment(secs(1))),
+    ])
+    .unwrap();
+    let (mut clock, time) = started(control);
+    assert_eq!(clock.moves_to_go(Side::White), Some(2));
+    time.advance(secs(10));
+    clock.press();
+    assert_eq!(clock.moves_to_go(Side::White), Some(1));
+    for _ in 0..3 {
+        time.advance(secs(10));
+        clock.press();
+    }
+    assert_eq!(clock.moves_to_go(Side::Black), None);
+    assert_eq!(clock.period(Side::Black).bonus, Bonus::Increment(secs(1)));
+    // Both made the two moves of the first period
+    assert_eq!(clock.remaining(Side::White), secs(70));
+    assert_eq!(clock.remaining(Side::Black), secs(70));
+    time.advance(secs(10));
+    clock.press();
+    assert_eq!(clock.remaining(Side::White), secs(61));
+
+    // A last period with moves repeats
+    let (mut clock, time) = started("1/10".parse().unwrap());
+    for _ in 0..6 {
+        time.advance(secs(1));
+        clock.press();
+    }
+    assert_eq!(clock.remaining(Side::White), secs(37));
+
+    assert_eq!(TimeControl::new(Vec::new()), None);
+}
+
+fn play(game: &mut Game, time: &ManualTime, moves: &[(&str, u64)]) {
+    for &(m, secs) in moves {
+        time.advance(Duration::from_secs(secs));
+        let cb = game.position();
+        let m = MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap();
+        game.make_move(m).unwrap();
+    }
+}
+
+#[test]
+fn game_times() {
+    let time = ManualTime::new();
+    let mut game = Game::new();
+    game.set_clock(Clock::new(
+        TimeControl::fischer(secs(60), secs(1)),
+        time.clone(),
+    ));
+    play(&mut game, &time, &[("e2e4", 3), ("e7e5", 5)]);
+    assert_eq!(game.move_times(), vec![Some(secs(3)), Some(secs(5))]);
+    let clock = game.clock().unwrap();
+    assert_eq!(clock.remaining(Side::White), secs(58));
+    assert_eq!(clock.running(), Some(Side::White));
+
+    game.resign(Side::White).unwrap();
+    assert_eq!(game.clock().unwrap().running(), None);
+}
+
+#[test]
+fn flag_fall() {
+    let time = ManualTime::new();
+    let mut game = Game::new();
+    game.set_clock(Clock::new(
+        TimeControl::sudden_death(secs(10)),
+        time.clone(),
+    ));
+    play(&mut game, &time, &[("e2e4", 3)]);
+    time.advance(secs(11));
+    assert!(game.check_time());
+    assert_eq!(game.result(), GameResult::WhiteWin(WinType::Timeout));
+
+    // Moving after the flag fell loses on time as well
+    let time = ManualTime::new();
+    let mut game = Game::new();
+    game.set_clock(Clock::new(
+        TimeControl::sudden_death(secs(10)),
+        time.clone(),
+    ));
+    time.advance(secs(10));
+    let cb = game.position();
+    let m = MoveGen::new(cb.moves_cache).from_str(cb, "e2e4").unwrap();
+    assert_eq!(game.make_move(m), Err(ChessError::GameOver));
+    assert_eq!(game.result(), GameResult::BlackWin(WinType::Timeout));
+    assert!(game.is_empty());
+}
+
+#[test]
+fn flag_fall_without_mating_material() {
+    let flag = |fen: &str| {
+        let time = ManualTime::new();
+        let mut game = Game::from_fen(fen).unwrap();
+        game.set_clock(Clock::new(
+            TimeControl::sudden_death(secs(10)),
+            time.clone(),
+        ));
+        time.advance(secs(10));
+        assert!(game.check_time());
+        assert!(!game.check_time());
+        game.result()
+    };
+
+    assert_eq!(
+        flag("8/8/4k3/8/8/2B1K3/8/R7 b - - 0 1"),
+        GameResult::WhiteWin(WinType::Timeout)
+    );
+    assert_eq!(
+        flag("8/4p3/4k3/8/8/4K3/8/8 b - - 0 1"),
+        GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial)
+    );
+    // The pawn could block its own king, so the bishop can still mate
+    assert_eq!(
+        flag("8/4p3/4k3/8/8/2B1K3/8/8 b - - 0 1"),
+        GameResult::WhiteWin(WinType::Timeout)
+    );
+    assert_eq!(
+        flag("8/4b3/4k3/8/8/2N1K3/8/8 b - - 0 1"),
+        GameResult::WhiteWin(WinType::Timeout)
+    );
+    assert_eq!(
+        flag("8/4r3/4k3/8/8/4K3/8/8 b - - 0 1"),
+        GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial)
+    );
+}
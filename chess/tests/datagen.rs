@@ -0,0 +1,155 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::sync::Arc;
+
+use chess::{
+    Chessboard, DrawType, GameResult, MoveGen, START_POS, WinType,
+    book::{BookBuilder, polyglot_key},
+    datagen::{DatagenConfig, Format, Openings, Record, generate, play_game},
+    eval::{HandCrafted, tune},
+    packed::PackedReader,
+    search::{Limit, Search},
+};
+
+fn config() -> DatagenConfig {
+    DatagenConfig {
+        limit: Limit::Depth(2),
+        games: 4,
+        threads: 2,
+        max_plies: 60,
+        seed: 1,
+        ..DatagenConfig::default()
+    }
+}
+
+#[test]
+fn games_are_filtered() {
+    let config = config();
+    let mut search = Search::new(HandCrafted::new());
+    let mut rng = fastrand::Rng::with_seed(2);
+    for _ in 0..3 {
+        let records = play_game(&config, &mut search, &mut rng);
+        assert!(!records.is_empty());
+        let result = records[0].result;
+        assert_ne!(result, GameResult::None);
+        for record in &records {
+            assert_eq!(record.result, result);
+            assert!(record.cb.checkers.is_empty());
+            assert_eq!(record.cb.result, GameResult::None);
+            // Random openings leave the starting position behind
+            assert!(record.cb.full_moves() > 4);
+        }
+    }
+}
+
+#[test]
+fn book_openings() {
+    let cb = Chessboard::from_fen(START_POS).unwrap();
+    let e4 = MoveGen::new(cb.moves_cache).from_str(&cb, "e2e4").unwrap();
+    let mut builder = BookBuilder::new();
+    builder
+        .add_game(cb, &[e4], GameResult::WhiteWin(WinType::Checkmate))
+        .unwrap();
+    let book = Arc::new(builder.build());
+    let after = cb.move_new(e4);
+
+    let config = DatagenConfig {
+        openings: Openings::Book {
+            book,
+            random_plies: 0,
+        },
+        max_plies: 6,
+        ..config()
+    };
+    let mut search = Search::new(HandCrafted::new());
+    let records = play_game(&config, &mut search, &mut fastrand::Rng::with_seed(3));
+    assert_eq!(polyglot_key(&records[0].cb), polyglot_key(&after));
+}
+
+#[test]
+fn sharded_output() {
+    let dir = std::env::temp_dir().join("chess-datagen-test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let stats = generate(&config(), &dir, HandCrafted::new).unwrap();
+    assert_eq!(stats.games, 4);
+
+    let mut lines = 0;
+    for shard in 0..2 {
+        let text = std::fs::read_to_string(dir.join(format!("shard-{shard}.txt"))).unwrap();
+        for line in text.lines() {
+            let (fen, rest) = line.split_once(" | ").unwrap();
+            let (score, result) = rest.split_once(" | ").unwrap();
+            assert!(Chessboard::from_fen(fen).is_ok());
+            assert!(score.parse::<i32>().is_ok());
+            assert!(["1-0", "0-1", "1/2-1/2"].contains(&result));
+            assert!(tune::parse_line(line).is_some());
+            lines += 1;
+        }
+    }
+    assert_eq!(lines, stats.positions);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reproducible_shards() {
+    // Each game gets its seed whatever the number of threads
+    let lines = |threads: usize| {
+        let dir = std::env::temp_dir().join(format!("chess-datagen-threads-{threads}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = DatagenConfig {
+            threads,
+            ..config()
+        };
+        generate(&config, &dir, HandCrafted::new).unwrap();
+        let mut lines = Vec::new();
+        for shard in 0..threads {
+            let text = std::fs::read_to_string(dir.join(format!("shard-{shard}.txt"))).unwrap();
+            lines.extend(text.lines().map(str::to_string));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        lines.sort();
+        lines
+    };
+    let lines_of_one = lines(1);
+    assert!(!lines_of_one.is_empty());
+    assert_eq!(lines_of_one, lines(3));
+}
+
+#[test]
+fn packed_output() {
+    let dir = std::env::temp_dir().join("chess-datagen-packed-test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = DatagenConfig {
+        format: Format::Packed,
+        ..config()
+    };
+    let stats = generate(&config, &dir, HandCrafted::new).unwrap();
+
+    let mut records = 0;
+    for shard in 0..2 {
+        let file = std::fs::File::open(dir.join(format!("shard-{shard}.bin"))).unwrap();
+        for record in PackedReader::<_, Record>::new(file) {
+            let record = record.unwrap();
+            assert_ne!(record.result, GameResult::None);
+            assert!(record.cb.checkers.is_empty());
+            records += 1;
+        }
+    }
+    assert_eq!(records, stats.positions);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn ply_limit() {
+    let config = DatagenConfig {
+        openings: Openings::Random { plies: 0 },
+        max_plies: 1,
+        ..config()
+    };
+    let mut search = Search::new(HandCrafted::new());
+    let records = play_game(&config, &mut search, &mut fastrand::Rng::with_seed(4));
+    // Nothing can be captured from the starting position, so it is recorded
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].cb.get_fen(), START_POS);
+    assert_eq!(records[0].result, GameResult::Draw(DrawType::Adjudication));
+}
@@ -0,0 +1,283 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::sync::Arc;
+
+use chess::{
+    ChessError, Chessboard, KIWIPETE_POS, MoveGen, Piece, PieceType, START_POS, Side,
+    eval::{
+        EvalParams, Evaluator, HandCrafted, Network, Nnue,
+        nnue::FeatureSet,
+        tune::{self, Tuner},
+    },
+};
+
+/// Network with small random weights, in the file layout
+fn network_bytes(features: u32, hidden: u32, seed: u64) -> Vec<u8> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let inputs = if features == 0 { 64 * 640 } else { 64 * 768 };
+    let mut bytes = b"CLNN".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(features.to_le_bytes());
+    bytes.extend(hidden.to_le_bytes());
+    bytes.extend(400i32.to_le_bytes());
+    for _ in 0..(inputs + 1) * hidden as usize {
+        bytes.extend(rng.i16(-64..64).to_le_bytes());
+    }
+    bytes.extend((0..2 * hidden).map(|_| rng.i8(..) as u8));
+    bytes.extend(rng.i32(-1000..1000).to_le_bytes());
+    bytes
+}
+
+fn network(features: u32) -> Arc<Network> {
+    Arc::new(Network::from_bytes(&network_bytes(features, 32, 1)).unwrap())
+}
+
+/// The position with the colors swapped
+fn mirror(fen: &str) -> String {
+    let parts: Vec<_> = fen.split(' ').collect();
+    let swap = |s: &str| {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect::<String>()
+    };
+    let board: Vec<_> = parts[0].split('/').rev().map(swap).collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    let mut castling: Vec<_> = swap(parts[2]).chars().collect();
+    castling.sort_by_key(|c| (c.is_ascii_lowercase(), *c != 'K' && *c != 'k'));
+    let castling: String = castling.into_iter().collect();
+    let en_passant = parts[3]
+        .replace('3', "x")
+        .replace('6', "3")
+        .replace('x', "6");
+    format!(
+        "{} {side} {castling} {en_passant} {} {}",
+        board.join("/"),
+        parts[4],
+        parts[5]
+    )
+}
+
+#[test]
+fn hand_crafted() {
+    let eval = HandCrafted::new();
+    let cb = Chessboard::from_fen(START_POS).unwrap();
+    assert_eq!(eval.evaluate(&cb), 0);
+
+    let cb = Chessboard::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    assert!(eval.evaluate(&cb) > 800);
+    let cb = Chessboard::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+    assert!(eval.evaluate(&cb) < -800);
+
+    // The king belongs in the center once the pieces are gone
+    let center = Chessboard::from_fen("4k3/p7/8/8/3K4/8/P7/8 w - - 0 1").unwrap();
+    let corner = Chessboard::from_fen("4k3/p7/8/8/8/8/P7/K7 w - - 0 1").unwrap();
+    assert!(eval.evaluate(&center) > eval.evaluate(&corner));
+}
+
+#[test]
+fn colors_are_symmetric() {
+    let hce = HandCrafted::new();
+    for features in [0, 1] {
+        let network = network(features);
+        for fen in [START_POS, KIWIPETE_POS, "8/2P5/8/4k3/8/1K6/5p2/8 b - - 0 1"] {
+            let cb = Chessboard::from_fen(fen).unwrap();
+            let mirrored = Chessboard::from_fen(&mirror(fen)).unwrap();
+            assert_eq!(hce.evaluate(&cb), hce.evaluate(&mirrored), "{fen}");
+            assert_eq!(network.evaluate(&cb), network.evaluate(&mirrored), "{fen}");
+        }
+    }
+}
+
+#[test]
+fn network_format() {
+    let bytes = network_bytes(1, 16, 2);
+    let network = Network::from_bytes(&bytes).unwrap();
+    assert_eq!(network.features(), FeatureSet::HalfKA);
+    assert_eq!(network.hidden(), 16);
+    assert_eq!(network.to_bytes(), bytes);
+
+    assert_eq!(
+        Network::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ChessError::InvalidNetwork)
+    );
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert_eq!(Network::from_bytes(&extra), Err(ChessError::InvalidNetwork));
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(Network::from_bytes(&magic), Err(ChessError::InvalidNetwork));
+    let mut features = bytes;
+    features[8] = 2;
+    assert_eq!(
+        Network::from_bytes(&features),
+        Err(ChessError::InvalidNetwork)
+    );
+
+    let path = std::env::temp_dir().join("chess-eval-network.nnue");
+    std::fs::write(&path, network_bytes(0, 16, 3)).unwrap();
+    let loaded = Network::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.features(), FeatureSet::HalfKP);
+    assert!(Network::open(std::env::temp_dir().join("chess-eval-missing.nnue")).is_err());
+}
+
+#[test]
+fn incremental_updates() {
+    let fens = [
+        START_POS,
+        KIWIPETE_POS,
+        "r3k2r/1P4P1/8/3pP3/8/8/1p4p1/R3K2R w KQkq d6 0 1",
+    ];
+    for features in [0, 1] {
+        let network = network(features);
+        let mut rng = fastrand::Rng::with_seed(7);
+        for fen in fens {
+            for _ in 0..10 {
+                let root = Chessboard::from_fen(fen).unwrap();
+                let mut nnue = Nnue::new(network.clone(), &root);
+                let mut line = vec![root];
+                for _ in 0..40 {
+                    let cb = *line.last().unwrap();
+                    let count = cb.moves_cache.count();
+                    if count == 0 {
+                        break;
+                    }
+                    let m = MoveGen::new(cb.moves_cache)
+                        .nth(rng.usize(..count))
+                        .unwrap();
+                    let next = cb.move_new(m);
+                    nnue.make_move(&cb, &next);
+                    assert_eq!(nnue.evaluate(&next), network.evaluate(&next), "{m:?}");
+                    line.push(next);
+                }
+                while line.len() > 1 {
+                    line.pop();
+                    nnue.unmake_move();
+                    let cb = line.last().unwrap();
+                    assert_eq!(nnue.evaluate(cb), network.evaluate(cb));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn swap_evaluators() {
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    let network = network(1);
+    let mut evaluators: Vec<Box<dyn Evaluator>> = vec![
+        Box::new(HandCrafted::new()),
+        Box::new(Nnue::new(network.clone(), &cb)),
+    ];
+    let m = MoveGen::new(cb.moves_cache).nth(0).unwrap();
+    let next = cb.move_new(m);
+    for eval in &mut evaluators {
+        eval.make_move(&cb, &next);
+    }
+    assert_eq!(
+        evaluators[0].evaluate(&next),
+        HandCrafted::new().evaluate(&next)
+    );
+    assert_eq!(evaluators[1].evaluate(&next), network.evaluate(&next));
+
+    let other = Chessboard::from_fen(START_POS).unwrap();
+    evaluators[1].reset(&other);
+    assert_eq!(evaluators[1].evaluate(&other), network.evaluate(&other));
+}
+
+#[test]
+fn eval_params_format() {
+    let params = EvalParams::default();
+    assert_eq!(
+        EvalParams::from_slice(&params.to_vec()),
+        Some(params.clone())
+    );
+    assert_eq!(params.to_string().parse(), Ok(params.clone()));
+
+    // The tune example writes the weights file in the same layout
+    let file = include_str!("../src/eval/weights.rs");
+    let body = &file[file.find("EvalParams").unwrap()..];
+    assert_eq!(body, params.to_string());
+
+    assert_eq!(
+        "EvalParams { material_mg: [1, 2] }".parse::<EvalParams>(),
+        Err(ChessError::InvalidEvalParams)
+    );
+    assert_eq!(EvalParams::from_slice(&[0; 10]), None);
+}
+
+#[test]
+fn tuning_lines() {
+    let lines = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [0.5]",
+            0.5,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1 1-0",
+            1.0,
+        ),
+        ("4k3/8/8/8/8/8/8/3QK3 w - - c9 \"0-1\";", 0.0),
+        ("4k3/8/8/8/8/8/8/3QK3 b - - 1/2-1/2", 0.5),
+    ];
+    for (line, want) in lines {
+        let (_, result) = tune::parse_line(line).unwrap();
+        assert_eq!(result, want, "{line}");
+    }
+    assert!(tune::parse_line("4k3/8/8/8/8/8/8/3QK3 w - - 0 1 [2]").is_none());
+    assert!(tune::parse_line("not a fen 1-0").is_none());
+
+    // Finished games say nothing about the evaluation
+    let mut tuner = Tuner::new(EvalParams::default());
+    assert!(!tuner.add_line("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1 1-0"));
+    assert!(tuner.add_line("7k/8/6K1/8/8/8/8/6Q1 w - - 0 1 1-0"));
+    assert_eq!(tuner.len(), 1);
+}
+
+#[test]
+fn tuning_reduces_error() {
+    let mut tuner = Tuner::new(EvalParams::default());
+    let mut rng = fastrand::Rng::with_seed(3);
+    for _ in 0..20 {
+        let mut cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+        for _ in 0..30 {
+            let count = cb.moves_cache.count();
+            if count == 0 {
+                break;
+            }
+            let m = MoveGen::new(cb.moves_cache)
+                .nth(rng.usize(..count))
+                .unwrap();
+            cb.make_move(m);
+            // Label by the knights, which the starting weights don't favor
+            let knights = |side| cb.get_pieces(Piece::from_side_and_type(side, PieceType::Knight));
+            let diff = knights(Side::White).popcnt() as i32 - knights(Side::Black).popcnt() as i32;
+            let result = match diff.signum() {
+                1 => 1.0,
+                -1 => 0.0,
+                _ => 0.5,
+            };
+            tuner.add(&cb, result);
+        }
+    }
+    assert!(tuner.len() > 100);
+
+    let k = tuner.fit_scaling();
+    assert!(k > 0.0);
+    let before = tuner.error(k);
+    for _ in 0..200 {
+        tuner.epoch(k, 1.0);
+    }
+    let after = tuner.error(k);
+    assert!(after < before, "{after} >= {before}");
+
+    // Rounding to centipawns keeps the tuned weights
+    let params = tuner.params();
+    assert_eq!(params.to_string().parse(), Ok(params));
+}
@@ -0,0 +1,212 @@
+#![cfg(not(feature = "no_std"))]
+
+use chess::{
+    ChessError, Chessboard, DrawType, GameResult, Move, MoveGen, START_POS, Side, WinType,
+    game::{Claim, Game},
+};
+
+fn uci(game: &Game, m: &str) -> Move {
+    let cb = game.position();
+    MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap()
+}
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for m in moves {
+        let m = uci(game, m);
+        game.make_move(m).unwrap();
+    }
+}
+
+#[test]
+fn undo_redo() {
+    let mut game = Game::new();
+    play(&mut game, &["e2e4", "e7e5", "g1f3"]);
+    assert_eq!(game.ply(), 3);
+    assert_eq!(game.len(), 3);
+    let after = game.position().get_fen();
+
+    let nf3 = game.undo().unwrap();
+    assert_eq!(nf3, uci(&game, "g1f3"));
+    game.undo();
+    game.undo();
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.position().get_fen(), START_POS);
+    assert_eq!(game.len(), 3);
+    assert!(game.played().is_empty());
+
+    assert!(game.redo().is_some());
+    assert!(game.go_to(3));
+    assert!(!game.go_to(4));
+    assert_eq!(game.position().get_fen(), after);
+    assert_eq!(game.redo(), None);
+
+    // Moving after an undo drops the rest of the line
+    game.go_to(2);
+    play(&mut game, &["b1c3"]);
+    assert_eq!(game.len(), 3);
+    assert_eq!(game.moves()[2], game.played()[2]);
+    assert_ne!(game.position().get_fen(), after);
+}
+
+#[test]
+fn illegal_moves() {
+    let mut game = Game::new();
+    let cb = Chessboard::from_fen(START_POS).unwrap();
+    let m = MoveGen::new(cb.moves_cache).from_str(&cb, "e2e4").unwrap();
+    game.make_move(m).unwrap();
+    assert!(matches!(game.make_move(m), Err(ChessError::IllegalMove(_))));
+    assert_eq!(game.len(), 1);
+}
+
+#[test]
+fn resign() {
+    let mut game = Game::new();
+    play(&mut game, &["e2e4"]);
+    game.resign(Side::Black).unwrap();
+    assert_eq!(game.result(), GameResult::WhiteWin(WinType::Resign));
+    assert!(game.is_over());
+    let m = uci(&game, "e7e5");
+    assert_eq!(game.make_move(m), Err(ChessError::GameOver));
+    assert_eq!(game.resign(Side::White), Err(ChessError::GameOver));
+
+    // Browsing back shows the game before it ended, moving on continues it
+    game.undo();
+    assert_eq!(game.result(), GameResult::None);
+    game.redo();
+    assert_eq!(game.result(), GameResult::WhiteWin(WinType::Resign));
+    game.undo();
+    play(&mut game, &["d2d4"]);
+    assert_eq!(game.result(), GameResult::None);
+}
+
+#[test]
+fn adjudicate() {
+    let mut game = Game::new();
+    play(&mut game, &["e2e4"]);
+    game.adjudicate(GameResult::None).unwrap();
+    assert!(!game.is_over());
+    game.adjudicate(GameResult::BlackWin(WinType::Forfeit))
+        .unwrap();
+    assert_eq!(game.result(), GameResult::BlackWin(WinType::Forfeit));
+    assert_eq!(
+        game.adjudicate(GameResult::Draw(DrawType::Adjudication)),
+        Err(ChessError::GameOver)
+    );
+}
+
+#[test]
+fn draw_offers() {
+    let mut game = Game::new();
+    assert_eq!(game.accept_draw(Side::Black), Err(ChessError::NoDrawOffer));
+
+    game.offer_draw(Side::White).unwrap();
+    assert_eq!(game.draw_offer(), Some(Side::White));
+    assert_eq!(game.accept_draw(Side::White), Err(ChessError::NoDrawOffer));
+    game.decline_draw(Side::Black).unwrap();
+    assert_eq!(game.draw_offer(), None);
+
+    // The offer survives the move of the side offering, not the reply
+    game.offer_draw(Side::White).unwrap();
+    play(&mut game, &["e2e4"]);
+    assert_eq!(game.draw_offer(), Some(Side::White));
+    play(&mut game, &["e7e5"]);
+    assert_eq!(game.draw_offer(), None);
+
+    game.offer_draw(Side::Black).unwrap();
+    game.accept_draw(Side::White).unwrap();
+    assert_eq!(game.result(), GameResult::Draw(DrawType::Agreement));
+    assert_eq!(game.draw_offer(), None);
+}
+
+#[test]
+fn threefold() {
+    let mut game = Game::new();
+    let knights = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    play(&mut game, &knights);
+    assert_eq!(game.repetitions(), 2);
+    assert_eq!(game.history().len(), 4);
+    assert_eq!(
+        game.claim_draw(Claim::Threefold, None),
+        Err(ChessError::InvalidClaim)
+    );
+
+    play(&mut game, &knights[..3]);
+    // Claiming with the move that repeats the position a third time
+    let m = uci(&game, "f6g8");
+    assert_eq!(
+        game.claim_draw(Claim::FiftyMoves, Some(m)),
+        Err(ChessError::InvalidClaim)
+    );
+    assert_eq!(game.len(), 7);
+    game.claim_draw(Claim::Threefold, Some(m)).unwrap();
+    assert_eq!(game.len(), 8);
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(game.result(), GameResult::Draw(DrawType::Repetition));
+}
+
+#[test]
+fn fifty_moves() {
+    let mut game = Game::from_fen("8/8/4k3/8/8/4K3/8/R7 w - - 98 80").unwrap();
+    play(&mut game, &["a1a2"]);
+    assert_eq!(
+        game.claim_draw(Claim::FiftyMoves, None),
+        Err(ChessError::InvalidClaim)
+    );
+
+    let m = uci(&game, "e6e5");
+    game.claim_draw(Claim::FiftyMoves, Some(m)).unwrap();
+    assert_eq!(game.position().half_moves(), 100);
+    assert_eq!(game.result(), GameResult::Draw(DrawType::FiftyMoveRule));
+
+    // The game goes on until the draw is claimed
+    game.undo();
+    play(&mut game, &["e6e5", "a2a1"]);
+    assert_eq!(game.position().half_moves(), 101);
+    assert_eq!(game.result(), GameResult::None);
+    game.claim_draw(Claim::FiftyMoves, None).unwrap();
+    assert_eq!(game.result(), GameResult::Draw(DrawType::FiftyMoveRule));
+}
+
+#[test]
+fn seventy_five_moves() {
+    let mut game = Game::from_fen("8/8/4k3/8/8/4K3/8/R7 w - - 148 80").unwrap();
+    play(&mut game, &["a1a2"]);
+    assert_eq!(game.result(), GameResult::None);
+    play(&mut game, &["e6e5"]);
+    assert_eq!(
+        game.result(),
+        GameResult::Draw(DrawType::SeventyFiveMoveRule)
+    );
+    assert_eq!(
+        game.make_move(uci(&game, "a2a1")),
+        Err(ChessError::GameOver)
+    );
+
+    // A mate on the last move stands
+    let mut game = Game::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 120 90").unwrap();
+    play(&mut game, &["a1a8"]);
+    assert_eq!(game.result(), GameResult::WhiteWin(WinType::Checkmate));
+}
+
+#[test]
+fn fivefold() {
+    let mut game = Game::new();
+    let knights = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    for _ in 0..3 {
+        play(&mut game, &knights);
+    }
+    assert_eq!(game.result(), GameResult::None);
+    play(&mut game, &knights);
+    assert_eq!(game.repetitions(), 5);
+    assert_eq!(
+        game.result(),
+        GameResult::Draw(DrawType::FivefoldRepetition)
+    );
+}
+
+#[test]
+fn dead_position() {
+    let mut game = Game::from_fen("4k3/8/1p1p1p1p/pPpPpPp1/P1P1P1PP/8/8/4K3 w - - 0 1").unwrap();
+    play(&mut game, &["h4h5"]);
+    assert_eq!(game.result(), GameResult::Draw(DrawType::DeadPosition));
+}
@@ -0,0 +1,82 @@
+use chess::{Chessboard, KIWIPETE_POS, Move, MoveGen, Piece, START_POS, Square};
+use types::Castling;
+
+fn compare(cb: &Chessboard, depth: u32) {
+    if depth == 0 {
+        return;
+    }
+
+    let mut move_gen = MoveGen::new(cb.moves_cache);
+    while let Some(m) = move_gen.next_const() {
+        let next = cb.move_new(m);
+        assert_eq!(
+            cb.gives_check(m),
+            !next.checkers.is_empty(),
+            "{} {:?}",
+            cb.get_fen(),
+            m
+        );
+        compare(&next, depth - 1);
+    }
+}
+
+#[test]
+fn matches_slow_path() {
+    const FENS: &[&str] = &[
+        START_POS,
+        KIWIPETE_POS,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ];
+
+    for fen in FENS {
+        compare(&Chessboard::from_fen(fen).unwrap(), 3);
+    }
+}
+
+#[test]
+fn discovered() {
+    let cb = Chessboard::from_fen("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1").unwrap();
+    assert_eq!(cb.calculate_discoverers(), Square::E4.bitboard());
+    assert!(cb.gives_check(Move::Standard {
+        piece: Piece::WHITE_KNIGHT,
+        from: Square::E4,
+        to: Square::C3,
+    }));
+}
+
+#[test]
+fn promotion() {
+    let cb = Chessboard::from_fen("k7/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(cb.gives_check(Move::Promotion {
+        from: Square::E7,
+        to: Square::E8,
+        promotion: Piece::WHITE_QUEEN,
+    }));
+    assert!(!cb.gives_check(Move::Promotion {
+        from: Square::E7,
+        to: Square::E8,
+        promotion: Piece::WHITE_BISHOP,
+    }));
+}
+
+#[test]
+fn castling() {
+    let cb = Chessboard::from_fen("5k2/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert!(cb.gives_check(Move::Castling {
+        castling: Castling::KingSide,
+        from: Square::E1,
+        to: Square::G1,
+    }));
+}
+
+#[test]
+fn en_passant() {
+    let cb = Chessboard::from_fen("8/8/8/k2pP2R/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert!(cb.gives_check(Move::EnPassant {
+        from: Square::E5,
+        to: Square::D6,
+    }));
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chess::mcts::{Budget, Mcts, MctsConfig, Playout};
+use chess::{Chessboard, KIWIPETE_POS, Move, MoveGen, START_POS};
+
+fn search(fen: &str, iterations: u64) -> (Mcts, Move) {
+    let cb = Chessboard::from_fen(fen).unwrap();
+    let mut mcts = Mcts::new(cb);
+    mcts.seed(1);
+    let m = mcts.search(Budget::Iterations(iterations)).unwrap();
+    (mcts, m)
+}
+
+fn uci(mcts: &Mcts, m: &str) -> Move {
+    let cb = mcts.position();
+    MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap()
+}
+
+#[test]
+fn nth_move() {
+    for fen in [
+        START_POS,
+        KIWIPETE_POS,
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let cb = Chessboard::from_fen(fen).unwrap();
+        let count = cb.moves_cache.count();
+        let move_gen = MoveGen::new(cb.moves_cache);
+        let sampled: HashSet<_> = (0..count).map(|i| move_gen.nth(i).unwrap()).collect();
+        let all: HashSet<_> = cb.to_vec().into_iter().collect();
+        assert_eq!(sampled.len(), count, "{fen}");
+        assert_eq!(sampled, all, "{fen}");
+        assert_eq!(move_gen.nth(count), None);
+    }
+}
+
+#[test]
+fn finds_mate() {
+    let (mcts, m) = search("k7/8/1K6/8/8/8/8/7R w - - 0 1", 2000);
+    assert_eq!(m, uci(&mcts, "h1h8"));
+}
+
+#[test]
+fn takes_the_queen() {
+    let (mcts, m) = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 3000);
+    assert_eq!(m, uci(&mcts, "d1d5"));
+    let stats = mcts.stats();
+    let best = stats.iter().max_by_key(|s| s.visits).unwrap();
+    assert!(best.score > 0.5);
+    assert_eq!(stats.iter().map(|s| s.visits).sum::<u32>(), 3000);
+}
+
+#[test]
+fn tree_reuse() {
+    let (mut mcts, _) = search(START_POS, 2000);
+    assert_eq!(mcts.visits(), 2000);
+    let best = mcts.best_move().unwrap();
+    let size = mcts.size();
+
+    mcts.advance(best);
+    let kept = mcts.visits();
+    assert!(kept > 0);
+    assert!(mcts.size() < size);
+    assert_eq!(
+        mcts.position().get_fen(),
+        Chessboard::from_fen(START_POS)
+            .unwrap()
+            .move_new(best)
+            .get_fen()
+    );
+
+    mcts.search(Budget::Iterations(100));
+    assert_eq!(mcts.visits(), kept + 100);
+
+    // A move outside the tree starts over, one iteration expands one move
+    let (mut mcts, searched) = search(START_POS, 1);
+    assert_eq!(mcts.stats().len(), 1);
+    let outside = mcts
+        .position()
+        .to_vec()
+        .into_iter()
+        .find(|&m| m != searched)
+        .unwrap();
+    mcts.advance(outside);
+    assert_eq!(mcts.visits(), 0);
+    assert_eq!(mcts.size(), 1);
+    assert_eq!(
+        mcts.position().get_fen(),
+        Chessboard::from_fen(START_POS)
+            .unwrap()
+            .move_new(outside)
+            .get_fen()
+    );
+}
+
+#[test]
+fn budgets() {
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    let config = MctsConfig {
+        playout: Playout::Random,
+        max_playout_plies: 50,
+        ..Default::default()
+    };
+    let mut mcts = Mcts::with_config(cb, config);
+    assert!(
+        mcts.search(Budget::Time(Duration::from_millis(50)))
+            .is_some()
+    );
+    assert!(mcts.visits() > 0);
+
+    // Nothing to search in a finished game
+    let stalemate = Chessboard::from_fen("k7/8/1Q6/8/8/8/8/K7 b - - 0 1").unwrap();
+    let mut mcts = Mcts::new(stalemate);
+    assert_eq!(mcts.search(Budget::Iterations(10)), None);
+}
@@ -1,4 +1,4 @@
-use chess::{Chessboard, START_POS, perft as _perft};
+use chess::{Bitboard, Chessboard, Move, MoveGen, Piece, PieceType, START_POS, Square, perft as _perft};
 
 mod movegen {
     use super::*;
@@ -130,3 +130,49 @@ fn perft_f(cb: &mut Chessboard, depth: u32) -> usize {
 
     perft
 }
+
+#[test]
+fn legal_targets() {
+    let board = Chessboard::from_fen("4k3/1P6/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.legal_targets(Square::E2),
+        Bitboard::from_squares(&[Square::E3, Square::E4])
+    );
+    assert_eq!(board.legal_targets(Square::E8), Bitboard::EMPTY);
+
+    assert!(board.needs_promotion_choice(Square::B7, Square::B8));
+    assert!(!board.needs_promotion_choice(Square::E2, Square::E4));
+    assert_eq!(board.move_from_squares(Square::B7, Square::B8, None), None);
+    assert_eq!(
+        board.move_from_squares(Square::B7, Square::B8, Some(PieceType::King)),
+        None
+    );
+    assert_eq!(
+        board.move_from_squares(Square::B7, Square::B8, Some(PieceType::Rook)),
+        Some(Move::Promotion {
+            from: Square::B7,
+            to: Square::B8,
+            promotion: Piece::WHITE_ROOK,
+        })
+    );
+    assert_eq!(
+        board.move_from_squares(Square::E2, Square::E4, None),
+        Some(Move::Standard {
+            piece: Piece::WHITE_PAWN,
+            from: Square::E2,
+            to: Square::E4,
+        })
+    );
+    assert_eq!(board.move_from_squares(Square::E2, Square::E5, None), None);
+
+    let mut move_gen = MoveGen::new(board.moves_cache);
+    assert_eq!(
+        move_gen.from_str(&board, "b7b8n"),
+        Some(Move::Promotion {
+            from: Square::B7,
+            to: Square::B8,
+            promotion: Piece::WHITE_KNIGHT,
+        })
+    );
+    assert_eq!(move_gen.from_str(&board, "b7b8"), None);
+}
@@ -0,0 +1,128 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::io::{Cursor, ErrorKind};
+
+use chess::{
+    ChessError, Chessboard, ChessboardRaw, EMPTY_POS, KIWIPETE_POS, START_POS,
+    packed::{Packed, PackedReader, PackedWriter},
+};
+
+static FENS: [&str; 7] = [
+    START_POS,
+    EMPTY_POS,
+    KIWIPETE_POS,
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 17 42",
+    "8/2k5/8/8/8/8/5K2/8 w - - 70000 5000000000",
+    "QQQQQQQQ/QQQQQQQQ/8/8/8/8/qqqqqqqq/qqqqqqqk b - - 0 1",
+];
+
+#[test]
+fn round_trip() {
+    for fen in FENS {
+        let raw = ChessboardRaw::from_fen(fen).unwrap();
+        let packed = raw.to_packed();
+        let unpacked = ChessboardRaw::from_packed(&packed).unwrap();
+        assert_eq!(unpacked.pieces, raw.pieces, "{fen}");
+        assert_eq!(unpacked.colors, raw.colors, "{fen}");
+        assert_eq!(unpacked.castling, raw.castling, "{fen}");
+        assert_eq!(unpacked.en_passant, raw.en_passant, "{fen}");
+        assert_eq!(unpacked.side, raw.side, "{fen}");
+        if raw.half_moves <= usize::from(u16::MAX) && raw.full_moves <= u32::MAX as usize {
+            assert_eq!(unpacked.get_fen(), fen);
+        }
+    }
+
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    let mut bytes = Vec::new();
+    cb.pack(&mut bytes);
+    assert_eq!(bytes.len(), Chessboard::SIZE);
+    assert_eq!(Chessboard::unpack(&bytes).unwrap().get_fen(), KIWIPETE_POS);
+}
+
+#[test]
+fn saturating_clocks() {
+    let raw = ChessboardRaw::from_fen(FENS[5]).unwrap();
+    let unpacked = ChessboardRaw::from_packed(&raw.to_packed()).unwrap();
+    assert_eq!(unpacked.half_moves, usize::from(u16::MAX));
+    assert_eq!(unpacked.full_moves, u32::MAX as usize);
+}
+
+#[test]
+fn layout() {
+    let packed = ChessboardRaw::from_fen(START_POS).unwrap().to_packed();
+    assert_eq!(packed.len(), 32);
+    assert_eq!(&packed[..8], &0xFFFF_0000_0000_FFFFu64.to_le_bytes());
+    // White rook and knight on a1 and b1, black pawns on a7 and b7
+    assert_eq!(packed[8], 0x21);
+    assert_eq!(packed[16], 0x88);
+    assert_eq!(packed[24], 0b1111);
+    assert_eq!(packed[25], 255);
+    assert_eq!(&packed[26..], &[0, 0, 1, 0, 0, 0]);
+}
+
+#[test]
+fn corrupted() {
+    let packed = ChessboardRaw::from_fen(START_POS).unwrap().to_packed();
+
+    let mut bad = packed;
+    bad[8] = 0x06;
+    assert_eq!(
+        ChessboardRaw::from_packed(&bad),
+        Err(ChessError::InvalidPackedBoard)
+    );
+
+    let mut bad = packed;
+    bad[25] = 64;
+    assert_eq!(
+        ChessboardRaw::from_packed(&bad),
+        Err(ChessError::InvalidPackedBoard)
+    );
+
+    let mut bad = packed;
+    bad[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(
+        ChessboardRaw::from_packed(&bad),
+        Err(ChessError::InvalidPackedBoard)
+    );
+
+    assert_eq!(
+        ChessboardRaw::unpack(&packed[1..]),
+        Err(ChessError::InvalidPackedBoard)
+    );
+}
+
+#[test]
+fn streaming() {
+    let boards: Vec<_> = FENS
+        .iter()
+        .map(|fen| ChessboardRaw::from_fen(fen).unwrap())
+        .collect();
+    let mut writer = PackedWriter::new(Vec::new());
+    for raw in &boards {
+        writer.write(raw).unwrap();
+    }
+    let bytes = writer.into_inner().unwrap();
+    assert_eq!(bytes.len(), boards.len() * ChessboardRaw::SIZE);
+
+    let read: Vec<ChessboardRaw> = PackedReader::new(Cursor::new(&bytes))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read.len(), boards.len());
+    for (read, raw) in read.iter().zip(&boards) {
+        assert_eq!(read.to_packed(), raw.to_packed());
+    }
+
+    let mut reader = PackedReader::<_, ChessboardRaw>::new(Cursor::new(&bytes[..40]));
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!(
+        reader.next().unwrap().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+
+    assert!(
+        PackedReader::<_, ChessboardRaw>::new(Cursor::new([]))
+            .next()
+            .is_none()
+    );
+}
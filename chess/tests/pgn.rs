@@ -0,0 +1,133 @@
+#![cfg(not(feature = "no_std"))]
+
+use chess::{
+    ChessError, Chessboard, GameResult, KIWIPETE_POS, MoveGen, START_POS, Side, WinType,
+    game::Game, pgn::Pgn,
+};
+
+const GAME: &str = r#"[Event "Casual"]
+[Site "?"]
+[Date "2024.01.02"]
+[Round "1"]
+[White "Anderssen, \"The Immortal\""]
+[Black "Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 {King's gambit} exf4 3. Bc4 Qh4+ $1 4. Kf1 (4. g3? fxg3 (4... Qe7)
+5. O-O) 4... b5!? ; the Bryan counter-gambit
+5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5
+12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18.
+Bd6 Bxg1 19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+#[test]
+fn read_game() {
+    let pgn = Pgn::parse(GAME).unwrap();
+    assert_eq!(pgn.tag("White"), Some("Anderssen, \"The Immortal\""));
+    assert_eq!(pgn.result(), "1-0");
+    assert_eq!(pgn.moves.len(), 45);
+    assert_eq!(pgn.comments[2].as_deref(), Some("King's gambit"));
+    assert_eq!(pgn.comments[7].as_deref(), Some("the Bryan counter-gambit"));
+    assert_eq!(pgn.comments[0], None);
+
+    let end = pgn.position();
+    assert!(end.result.is_over());
+    assert_eq!(end.side(), Side::Black);
+    assert_eq!(end.full_moves(), 23);
+}
+
+#[test]
+fn round_trip() {
+    let pgn = Pgn::parse(GAME).unwrap();
+    let text = pgn.to_string();
+    assert!(text.lines().all(|line| line.len() <= 80));
+    assert!(text.contains("[White \"Anderssen, \\\"The Immortal\\\"\"]"));
+    assert!(text.contains("2. f4 {King's gambit} 2... exf4"));
+    assert!(text.trim_end().ends_with("1-0"));
+
+    let again = Pgn::parse(&text).unwrap();
+    assert_eq!(again.tags, pgn.tags);
+    assert_eq!(again.moves, pgn.moves);
+    assert_eq!(again.comments, pgn.comments);
+}
+
+#[test]
+fn several_games() {
+    let text = format!(
+        "{GAME}\n[Event \"Second\"]\n[SetUp \"1\"]\n[FEN \"{KIWIPETE_POS}\"]\n\n\
+         1. 0-0 0-0-0 2. a3 *\n\n1. d4 d5"
+    );
+    let games = Pgn::parse_all(&text).unwrap();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[1].tag("Event"), Some("Second"));
+    assert_eq!(games[1].start.get_fen(), KIWIPETE_POS);
+    assert_eq!(games[1].moves.len(), 3);
+    assert_eq!(games[1].result(), "*");
+    // Without tags and a result
+    assert_eq!(games[2].moves.len(), 2);
+    assert_eq!(games[2].result(), "*");
+
+    assert_eq!(Pgn::parse("1. e4 e4").err(), Some(ChessError::InvalidPgn));
+    assert_eq!(
+        Pgn::parse("[Event \"x\"]").err(),
+        Some(ChessError::InvalidPgn)
+    );
+    assert_eq!(
+        Pgn::parse("[Event x]\n1. e4 *").err(),
+        Some(ChessError::InvalidPgn)
+    );
+    assert_eq!(
+        Pgn::parse("1. e4 ) e5 *").err(),
+        Some(ChessError::InvalidPgn)
+    );
+}
+
+#[test]
+fn written_from_a_game() {
+    let mut game = Game::from_fen(KIWIPETE_POS).unwrap();
+    for m in ["e1g1", "e8c8"] {
+        let cb = game.position();
+        let m = MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap();
+        game.make_move(m).unwrap();
+    }
+    game.resign(Side::White).unwrap();
+    let pgn = Pgn::from_game(&game);
+    assert_eq!(
+        pgn.result(),
+        GameResult::BlackWin(WinType::Resign).pgn_result()
+    );
+    assert_eq!(pgn.tag("FEN"), Some(KIWIPETE_POS));
+    assert_eq!(pgn.tag("SetUp"), Some("1"));
+    assert_eq!(pgn.tag("Termination"), Some("normal"));
+
+    let text = pgn.to_string();
+    assert!(text.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+    assert!(text.ends_with("\n\n1. O-O O-O-O 0-1\n"));
+
+    // Black to move starts with the move number and dots
+    let mut pgn = Pgn::from_position(game.start().move_new(game.moves()[0]));
+    pgn.moves.push(game.moves()[1]);
+    assert!(pgn.to_string().ends_with("\n\n1... O-O-O *\n"));
+
+    assert_eq!(Pgn::new().tag("FEN"), None);
+    assert_eq!(Pgn::new().start.get_fen(), START_POS);
+}
+
+#[test]
+fn standard_algebraic_notation() {
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    for m in cb.to_vec() {
+        let san = cb.to_san(m).unwrap();
+        assert_eq!(cb.from_san(&san), Some(m), "{san}");
+    }
+    let uci = |m| MoveGen::new(cb.moves_cache).from_str(&cb, m);
+    assert_eq!(cb.from_san("0-0-0"), uci("e1c1"));
+    assert_eq!(cb.from_san("Qxf6!!"), uci("f3f6"));
+    assert_eq!(cb.from_san("Ra3"), None);
+    assert_eq!(cb.from_san(""), None);
+
+    let cb = Chessboard::from_fen("8/1P6/8/8/8/8/8/K1k5 w - - 0 1").unwrap();
+    let uci = |m| MoveGen::new(cb.moves_cache).from_str(&cb, m);
+    assert_eq!(cb.from_san("b8=N"), uci("b7b8n"));
+    assert_eq!(cb.from_san("b8Q+"), uci("b7b8q"));
+}
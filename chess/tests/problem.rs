@@ -0,0 +1,118 @@
+use chess::problem::{Stipulation, solve};
+use chess::{ChessError, Chessboard, MoveGen};
+
+fn san(fen: &str, uci: &str) -> String {
+    let cb = Chessboard::from_fen(fen).unwrap();
+    let m = MoveGen::new(cb.moves_cache).from_str(&cb, uci).unwrap();
+    cb.to_san(m).unwrap()
+}
+
+fn solution(fen: &str, stipulation: &str) -> chess::problem::Solution {
+    let cb = Chessboard::from_fen(fen).unwrap();
+    solve(&cb, stipulation.parse().unwrap())
+}
+
+#[test]
+fn san_notation() {
+    let fen = "4k3/8/8/R7/8/8/8/RN2KN2 w - - 0 1";
+    assert_eq!(san(fen, "a1a3"), "R1a3");
+    assert_eq!(san(fen, "b1d2"), "Nbd2");
+    assert_eq!(san(fen, "f1d2"), "Nfd2");
+    assert_eq!(san(fen, "a5a8"), "Ra8+");
+    assert_eq!(san(fen, "e1e2"), "Ke2");
+
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(san(fen, "e1g1"), "O-O");
+    assert_eq!(san(fen, "e1c1"), "O-O-O");
+    assert_eq!(san(fen, "a1a8"), "Rxa8+");
+
+    assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+    assert_eq!(san("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8q"), "exd8=Q+");
+    assert_eq!(san("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8n"), "e8=N");
+    assert_eq!(san("k7/8/1K6/8/8/8/8/7R w - - 0 1", "h1h8"), "Rh8#");
+}
+
+#[test]
+fn stipulations() {
+    for (text, stipulation) in [
+        ("#2", Stipulation::Mate(2)),
+        ("=1", Stipulation::Stalemate(1)),
+        ("h#3", Stipulation::HelpMate(3)),
+        ("h=2", Stipulation::HelpStalemate(2)),
+        ("s#12", Stipulation::SelfMate(12)),
+    ] {
+        assert_eq!(text.parse::<Stipulation>(), Ok(stipulation));
+        assert_eq!(stipulation.to_string(), text);
+    }
+    for text in ["", "#", "#0", "2", "x#2", "h#-1"] {
+        assert_eq!(
+            text.parse::<Stipulation>(),
+            Err(ChessError::InvalidStipulation)
+        );
+    }
+}
+
+#[test]
+fn direct_mate() {
+    let s = solution("k7/8/1K6/8/8/8/8/7R w - - 0 1", "#1");
+    assert_eq!(s.key_moves(), ["Rh8#"]);
+    assert!(s.is_sound());
+
+    let s = solution("K7/7R/8/8/8/6R1/k5N1/8 w - - 0 1", "#2");
+    assert_eq!(s.key_moves(), ["Rb7"]);
+    assert_eq!(s.variations(), ["1. Rb7 Ka1 2. Ra3#"]);
+    assert!(s.is_sound());
+
+    // Too short for the mate
+    assert!(!solution("K7/7R/8/8/8/6R1/k5N1/8 w - - 0 1", "#1").is_solvable());
+}
+
+#[test]
+fn cooks() {
+    let s = solution("7k/8/5K2/8/8/8/8/R7 w - - 0 1", "#2");
+    assert!(s.is_cooked());
+    assert!(!s.is_sound());
+    assert_eq!(s.key_moves(), ["Kg6", "Kf7"]);
+    assert_eq!(s.variations(), ["1. Kg6 Kg8 2. Ra8#", "1. Kf7 Kh7 2. Rh1#"]);
+}
+
+#[test]
+fn stalemate() {
+    let s = solution("7B/8/8/8/8/7K/k7/3Q4 w - - 0 1", "=1");
+    assert_eq!(s.key_moves(), ["Qd3"]);
+    assert!(s.is_sound());
+
+    let s = solution("k7/8/8/1Q6/8/8/8/K7 w - - 0 1", "=1");
+    assert_eq!(s.variations(), ["1. Qb6"]);
+    // Only Rh8 takes b8 away and it mates
+    assert!(!solution("k7/8/1K6/8/8/8/8/7R w - - 0 1", "=1").is_solvable());
+}
+
+#[test]
+fn help_play() {
+    let s = solution("7k/8/6K1/8/8/8/8/1R6 b - - 0 1", "h#1");
+    assert_eq!(s.variations(), ["1... Kg8 2. Rb8#"]);
+    assert!(s.is_sound());
+
+    let s = solution("8/3K4/4B3/1k6/3Q4/8/8/8 b - - 0 1", "h#2");
+    assert_eq!(s.key_moves(), ["Ka5"]);
+    assert!(!s.is_cooked());
+    assert_eq!(s.variations().len(), 5);
+    assert_eq!(
+        s.duals(),
+        [
+            "1... Ka5 2. Kc6/Kc7",
+            "1... Ka5 2. Kc6 Ka6 3. Qa1#/Qa4#/Qb6#",
+            "1... Ka5 2. Kc7 Ka6 3. Qa4#/Qb6#",
+        ]
+    );
+}
+
+#[test]
+fn self_mate() {
+    let s = solution("8/8/8/5k2/1Q5K/8/8/B5q1 w - - 0 1", "s#1");
+    assert_eq!(s.variations(), ["1. Qg4+ Qxg4#"]);
+    assert!(s.is_sound());
+    // Mating black is not a solution
+    assert!(!solution("k7/8/1K6/8/8/8/8/7R w - - 0 1", "s#1").is_solvable());
+}
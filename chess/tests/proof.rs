@@ -0,0 +1,83 @@
+use chess::proof::{Proof, ProofSearch};
+use chess::tablebase::{Dtm, Generator};
+use chess::{Chessboard, GameResult, WinType};
+
+fn prove(fen: &str) -> (Proof, Chessboard, ProofSearch) {
+    let cb = Chessboard::from_fen(fen).unwrap();
+    let mut search = ProofSearch::new();
+    (search.prove(&cb), cb, search)
+}
+
+/// Play the principal variation and check that it ends in mate
+fn assert_mates(fen: &str) {
+    let (proof, cb, search) = prove(fen);
+    let Proof::Mate(plies) = proof else {
+        panic!("{fen} {proof:?}");
+    };
+    let line = search.principal_variation(&cb);
+    assert!(!line.is_empty() && line.len() <= plies as usize, "{fen}");
+
+    let mut board = cb;
+    for m in line {
+        assert!(board.to_vec().contains(&m));
+        board.make_move(m);
+    }
+    let mated = match cb.side() {
+        chess::Side::White => GameResult::WhiteWin(WinType::Checkmate),
+        chess::Side::Black => GameResult::BlackWin(WinType::Checkmate),
+    };
+    assert_eq!(board.result, mated, "{fen}");
+}
+
+#[test]
+fn short_mates() {
+    assert_eq!(prove("k7/8/1K6/8/8/8/8/7R w - - 0 1").0, Proof::Mate(1));
+    assert_eq!(prove("K7/7R/8/8/8/6R1/k5N1/8 w - - 0 1").0, Proof::Mate(3));
+    assert_mates("K7/7R/8/8/8/6R1/k5N1/8 w - - 0 1");
+    assert_mates("8/8/8/5k2/1Q5K/8/8/B5q1 b - - 0 1");
+}
+
+#[test]
+fn long_mates() {
+    assert_mates("8/8/8/3k4/8/8/8/4K1Q1 w - - 0 1");
+}
+
+#[test]
+fn no_mate() {
+    // Bare king and a lone knight can't mate
+    assert_eq!(prove("k7/8/1K6/8/8/8/8/8 w - - 0 1").0, Proof::NoMate);
+    assert_eq!(prove("k7/8/1K6/8/8/8/8/6N1 w - - 0 1").0, Proof::NoMate);
+    // Stalemate is not a win
+    assert_eq!(prove("k7/8/1Q6/8/8/8/8/K7 b - - 0 1").0, Proof::NoMate);
+    // The rook pawn can't win against the king in the corner
+    assert_eq!(prove("k7/8/8/8/8/8/P7/4K3 w - - 0 1").0, Proof::NoMate);
+}
+
+#[test]
+fn node_limit() {
+    let cb = Chessboard::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 0 1").unwrap();
+    let mut search = ProofSearch::with_limit(10);
+    assert_eq!(search.prove(&cb), Proof::Unknown);
+    assert!(search.nodes() <= 10);
+}
+
+#[test]
+fn agrees_with_tablebase() {
+    let mut generator = Generator::new();
+    generator.generate("KRvK").unwrap();
+    for fen in [
+        "8/8/8/8/8/2k5/8/K6R w - - 0 1",
+        "8/8/3k4/8/8/8/1R6/3K4 w - - 0 1",
+        "4k3/8/4K3/8/8/8/8/7R w - - 0 1",
+        "4k3/8/4K3/8/8/8/8/7R b - - 0 1",
+        "8/8/8/8/8/8/1k6/1R2K3 b - - 0 1",
+    ] {
+        let cb = Chessboard::from_fen(fen).unwrap();
+        let expected = match generator.probe(&cb).unwrap() {
+            Dtm::Win(_) => true,
+            Dtm::Loss(_) | Dtm::Draw => false,
+        };
+        let proof = ProofSearch::new().prove(&cb);
+        assert_eq!(matches!(proof, Proof::Mate(_)), expected, "{fen}");
+    }
+}
@@ -0,0 +1,107 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::time::{Duration, Instant};
+
+use chess::{
+    Chessboard, KIWIPETE_POS, MoveGen,
+    book::polyglot_key,
+    eval::HandCrafted,
+    search::{Limit, MATE, Search, is_mate},
+};
+
+fn search(fen: &str, limit: Limit) -> (Chessboard, chess::search::SearchResult) {
+    let cb = Chessboard::from_fen(fen).unwrap();
+    let result = Search::new(HandCrafted::new()).search(&cb, limit);
+    (cb, result)
+}
+
+fn uci(cb: &Chessboard, m: &str) -> chess::Move {
+    MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap()
+}
+
+#[test]
+fn finds_mates() {
+    let (cb, result) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Limit::Depth(4));
+    assert_eq!(result.best_move, Some(uci(&cb, "a1a8")));
+    assert_eq!(result.score, MATE - 1);
+    assert!(is_mate(result.score));
+
+    // Mate in two, the rook has to cut off the king first
+    let (cb, result) = search("7k/8/5K2/8/8/8/8/1R6 w - - 0 1", Limit::Depth(4));
+    assert_eq!(result.score, MATE - 3);
+    assert_eq!(result.pv.len(), 3);
+    let mut line = cb;
+    for m in &result.pv {
+        line.make_move(*m);
+    }
+    assert!(matches!(line.result, chess::GameResult::WhiteWin(_)));
+
+    // Mates for black score the same for the side to move
+    let (_, result) = search("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1", Limit::Depth(2));
+    assert_eq!(result.score, MATE - 1);
+}
+
+#[test]
+fn wins_material() {
+    let (cb, result) = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", Limit::Depth(3));
+    assert_eq!(result.best_move, Some(uci(&cb, "d1d5")));
+    assert!(result.score > 300);
+
+    // The rook is defended, taking it loses the queen
+    let (cb, result) = search("4k3/4p3/3r4/8/8/8/8/3QK3 w - - 0 1", Limit::Depth(3));
+    assert_ne!(result.best_move, Some(uci(&cb, "d1d6")));
+}
+
+#[test]
+fn limits() {
+    let (_, result) = search(KIWIPETE_POS, Limit::Depth(3));
+    assert_eq!(result.depth, 3);
+    assert!(result.nodes > 0);
+
+    let (cb, result) = search(KIWIPETE_POS, Limit::Nodes(5000));
+    assert!(result.nodes <= 5000);
+    assert!(result.best_move.is_some());
+    assert!(cb.to_vec().contains(&result.best_move.unwrap()));
+
+    // Even a tiny budget plays a legal move
+    let (cb, result) = search(KIWIPETE_POS, Limit::Nodes(1));
+    assert!(cb.to_vec().contains(&result.best_move.unwrap()));
+
+    let (_, result) = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Limit::Depth(3));
+    assert_eq!(result.best_move, None);
+}
+
+#[test]
+fn deadline() {
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    let mut search = Search::new(HandCrafted::new());
+    search.set_deadline(Some(Instant::now() + Duration::from_millis(50)));
+    let start = Instant::now();
+    let result = search.search(&cb, Limit::Depth(64));
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(result.depth < 64);
+    assert!(cb.to_vec().contains(&result.best_move.unwrap()));
+
+    // Past the deadline there is still a move to play
+    search.set_deadline(Some(Instant::now()));
+    let result = search.search(&cb, Limit::Depth(64));
+    assert_eq!(result.depth, 0);
+    assert!(cb.to_vec().contains(&result.best_move.unwrap()));
+
+    search.set_deadline(None);
+    assert_eq!(search.search(&cb, Limit::Depth(2)).depth, 2);
+}
+
+#[test]
+fn repetitions() {
+    // Down a queen, black is happy to repeat the position it came from
+    let cb = Chessboard::from_fen("7k/8/8/8/8/8/1Q6/K7 b - - 0 1").unwrap();
+    let previous = Chessboard::from_fen("6k1/8/8/8/8/8/1Q6/K7 w - - 0 1").unwrap();
+    let mut search = Search::new(HandCrafted::new());
+    let alone = search.search(&cb, Limit::Depth(3));
+    assert!(alone.score < -500);
+
+    search.set_history(&[polyglot_key(&previous)]);
+    let repeated = search.search(&cb, Limit::Depth(3));
+    assert!(repeated.score > alone.score);
+}
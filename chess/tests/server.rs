@@ -0,0 +1,205 @@
+#![cfg(all(feature = "server", not(feature = "no_std")))]
+
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::OnceLock,
+    thread,
+};
+
+use chess::{KIWIPETE_POS, START_POS, server::Server};
+use serde_json::{Value, json};
+
+/// Address of the server shared by the tests
+fn server() -> SocketAddr {
+    static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDR.get_or_init(|| {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    })
+}
+
+/// Status and body of the answer to the raw request
+fn send(request: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(server()).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut answer = String::new();
+    stream.read_to_string(&mut answer).unwrap();
+    let (head, body) = answer.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    assert!(head.contains("Content-Type: application/json"));
+    assert!(head.contains(&format!("Content-Length: {}", body.len())));
+    (status, body.to_string())
+}
+
+fn post(path: &str, body: &Value) -> (u16, Value) {
+    let body = body.to_string();
+    let (status, answer) = send(&format!(
+        "POST {path} HTTP/1.1\r\nHost: localhost\r\ncontent-type: application/json\r\n\
+         content-length: {}\r\n\r\n{body}",
+        body.len()
+    ));
+    (status, serde_json::from_str(&answer).unwrap())
+}
+
+#[test]
+fn validate() {
+    let (status, answer) = post("/validate", &json!({ "fen": KIWIPETE_POS }));
+    assert_eq!(status, 200);
+    assert_eq!(answer["valid"], true);
+    assert_eq!(answer["fen"], KIWIPETE_POS);
+    assert_eq!(answer["side"], "White");
+    assert_eq!(answer["check"], false);
+    assert_eq!(answer["result"], "None");
+
+    let (status, answer) = post("/validate", &json!({ "fen": "8/8/8/8/8/8/8/8 w - - 0 1" }));
+    assert_eq!(status, 200);
+    assert_eq!(answer["valid"], false);
+    assert_eq!(answer["error"], "InvalidKings");
+
+    let (_, answer) = post(
+        "/validate",
+        &json!({ "fen": "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1" }),
+    );
+    assert_eq!(answer["result"], json!({ "Draw": "Stalemate" }));
+}
+
+#[test]
+fn moves() {
+    let (status, answer) = post("/moves", &json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(answer["fen"], START_POS);
+    let moves = answer["moves"].as_array().unwrap();
+    assert_eq!(moves.len(), 20);
+    assert!(moves.contains(&json!({ "uci": "g1f3", "san": "Nf3" })));
+
+    let (_, answer) = post("/moves", &json!({ "fen": KIWIPETE_POS }));
+    assert_eq!(answer["moves"].as_array().unwrap().len(), 48);
+
+    // After the moves of Fool's mate there are none
+    let (_, answer) = post(
+        "/moves",
+        &json!({ "moves": ["f2f3", "e7e5", "g2g4", "Qh4#"] }),
+    );
+    assert_eq!(answer["moves"], json!([]));
+    assert_eq!(answer["result"], json!({ "BlackWin": "Checkmate" }));
+}
+
+#[test]
+fn play() {
+    let (status, answer) = post("/play", &json!({ "moves": ["e4", "d7d5", "exd5"] }));
+    assert_eq!(status, 200);
+    assert_eq!(
+        answer["fen"],
+        "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2"
+    );
+    let moves = answer["moves"].as_array().unwrap();
+    assert_eq!(moves[0]["uci"], "e2e4");
+    assert_eq!(moves[1]["san"], "d5");
+    assert_eq!(moves[2]["san"], "exd5");
+    // Pieces are written as their number
+    assert!(moves[2]["outcome"]["captured"].is_number());
+    assert!(moves[1]["outcome"]["captured"].is_null());
+
+    let (status, answer) = post("/play", &json!({ "moves": ["e4", "e4"] }));
+    assert_eq!(status, 400);
+    assert_eq!(answer["error"], "Illegal move e4");
+    assert_eq!(answer["reason"], "InvalidMovement");
+
+    // UCI moves are judged by their squares
+    for (m, reason) in [
+        ("e1e2", "OwnPieceOnTarget"),
+        ("e3e4", "NoPieceOnOrigin"),
+        ("e7e5", "WrongSideToMove"),
+        ("f1b5", "PathBlocked"),
+    ] {
+        let (status, answer) = post("/play", &json!({ "moves": [m] }));
+        assert_eq!(status, 400);
+        assert_eq!(answer["reason"], reason, "{m}");
+    }
+    let (_, answer) = post(
+        "/play",
+        &json!({ "moves": ["e2e4", "f7f6", "d1h5", "e8f7"] }),
+    );
+    assert_eq!(answer["reason"], "LeavesKingInCheck");
+
+    // Failures without a move carry no reason
+    let (_, answer) = post("/perft", &json!({}));
+    assert!(answer.get("reason").is_none());
+}
+
+#[test]
+fn result() {
+    let (_, answer) = post("/result", &json!({}));
+    assert_eq!(answer["over"], false);
+    assert_eq!(answer["pgn"], "*");
+
+    let (_, answer) = post("/result", &json!({ "moves": ["f3", "e5", "g4", "Qh4"] }));
+    assert_eq!(answer["over"], true);
+    assert_eq!(answer["pgn"], "0-1");
+    assert_eq!(answer["termination"], "normal");
+
+    // Repeating the position five times
+    let knights = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let moves: Vec<_> = knights.iter().cycle().take(16).collect();
+    let (_, answer) = post("/result", &json!({ "moves": moves }));
+    assert_eq!(answer["result"], json!({ "Draw": "FivefoldRepetition" }));
+}
+
+#[test]
+fn perft() {
+    let (status, answer) = post("/perft", &json!({ "fen": KIWIPETE_POS, "depth": 2 }));
+    assert_eq!(status, 200);
+    assert_eq!(answer["nodes"], 2039);
+    assert_eq!(answer["moves"].as_array().unwrap().len(), 48);
+
+    let (_, answer) = post("/perft", &json!({ "depth": 1 }));
+    assert_eq!(answer["nodes"], 20);
+
+    let (status, _) = post("/perft", &json!({ "depth": 12 }));
+    assert_eq!(status, 400);
+    let (status, _) = post("/perft", &json!({}));
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn search() {
+    let (status, answer) = post(
+        "/search",
+        &json!({ "fen": "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "time_ms": 200 }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(answer["best_move"], json!({ "uci": "a1a8", "san": "Ra8#" }));
+    assert_eq!(answer["mate"], 1);
+    assert!(answer["time_ms"].as_u64().unwrap() < 2000);
+
+    let (_, answer) = post("/search", &json!({ "fen": KIWIPETE_POS, "depth": 2 }));
+    assert_eq!(answer["depth"], 2);
+    assert!(answer["nodes"].as_u64().unwrap() > 0);
+    assert!(!answer["pv"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn errors() {
+    let (status, answer) = post("/unknown", &json!({}));
+    assert_eq!(status, 404);
+    assert_eq!(answer["error"], "No endpoint /unknown");
+
+    let (status, _) = send("GET /moves HTTP/1.1\r\n\r\n");
+    assert_eq!(status, 405);
+
+    let (status, body) = send("OPTIONS /moves HTTP/1.1\r\n\r\n");
+    assert_eq!((status, body.as_str()), (204, ""));
+
+    let (status, answer) = send("POST /moves HTTP/1.1\r\ncontent-length: 5\r\n\r\n{fen:");
+    assert_eq!(status, 400);
+    assert!(answer.contains("Invalid request"));
+
+    let (status, _) = post("/moves", &json!({ "fen": "8/8 w" }));
+    assert_eq!(status, 400);
+
+    let (status, _) = send("POST /moves HTTP/1.1\r\ncontent-length: 1000000\r\n\r\n");
+    assert_eq!(status, 413);
+}
@@ -0,0 +1,467 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use chess::syzygy::{ProbeError, Tablebase, Wdl};
+use chess::{Chessboard, Move, Piece, Square};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+const VALUES_PER_BLOCK: usize = 64;
+
+/// Values of one side and file of a table
+enum Part {
+    Single(u8),
+    /// Values `0..5` stored with a fixed 3 bit code
+    Packed(Vec<u8>),
+}
+
+/// Write a table in the Syzygy layout
+///
+/// `pieces` are the table piece codes, identical for both sides
+fn write_table(dtz: bool, header: u8, pieces: &[u8], files: usize, parts: &[Part]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend(if dtz { DTZ_MAGIC } else { WDL_MAGIC });
+    bytes.push(header);
+
+    for _ in 0..files {
+        bytes.push(0);
+        bytes.extend(pieces.iter().map(|p| p << 4 | p));
+    }
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+
+    for part in parts {
+        match part {
+            Part::Single(value) => bytes.extend([0x80, *value]),
+            Part::Packed(values) => {
+                bytes.extend([0, 5, 6, 0]);
+                bytes.extend((values.len().div_ceil(VALUES_PER_BLOCK) as u32).to_le_bytes());
+                bytes.extend([3, 3, 0, 0, 5, 0]);
+                for value in 0..5 {
+                    bytes.extend([value, 0xF0, 0xFF]);
+                }
+                bytes.push(0);
+            }
+        }
+    }
+
+    for part in parts {
+        if let Part::Packed(values) = part {
+            let blocks = values.len().div_ceil(VALUES_PER_BLOCK);
+            for k in 0..values.len().div_ceil(64) {
+                let idx = k * 64 + 32;
+                let block = (idx / VALUES_PER_BLOCK).min(blocks - 1);
+                bytes.extend((block as u32).to_le_bytes());
+                bytes.extend(((idx - block * VALUES_PER_BLOCK) as u16).to_le_bytes());
+            }
+        }
+    }
+    for part in parts {
+        if let Part::Packed(values) = part {
+            for chunk in values.chunks(VALUES_PER_BLOCK) {
+                bytes.extend((chunk.len() as u16 - 1).to_le_bytes());
+            }
+        }
+    }
+    for part in parts {
+        while bytes.len() % 64 != 0 {
+            bytes.push(0);
+        }
+        if let Part::Packed(values) = part {
+            for chunk in values.chunks(VALUES_PER_BLOCK) {
+                let mut block = [0u8; 32];
+                for (i, value) in chunk.iter().enumerate() {
+                    for bit in 0..3 {
+                        if value >> (2 - bit) & 1 == 1 {
+                            let pos = i * 3 + bit;
+                            block[pos / 8] |= 0x80 >> (pos % 8);
+                        }
+                    }
+                }
+                bytes.extend(block);
+            }
+        }
+    }
+
+    while bytes.len() % 64 != 16 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// Arbitrary values `0..5` that differ between indices
+fn packed(size: usize, salt: u64) -> Part {
+    Part::Packed(
+        (0..size as u64)
+            .map(|idx| ((idx.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ salt) >> 59) as u8 % 5)
+            .collect(),
+    )
+}
+
+fn tablebase(name: &str, tables: &[(&str, Vec<u8>)]) -> Tablebase {
+    let dir: PathBuf = std::env::temp_dir().join(format!("syzygy-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, bytes) in tables {
+        std::fs::write(dir.join(file), bytes).unwrap();
+    }
+    // Tables are read lazily, the directory has to stay
+    Tablebase::open(&dir).unwrap()
+}
+
+/// KQvK where white to move always wins and black to move always loses
+fn kqk_tablebase(name: &str) -> Tablebase {
+    let wdl = write_table(
+        false,
+        1,
+        &[6, 5, 14],
+        1,
+        &[Part::Single(4), Part::Single(0)],
+    );
+    let dtz = write_table(true, 0, &[6, 5, 14], 1, &[Part::Single(5)]);
+    tablebase(name, &[("KQvK.rtbw", wdl), ("KQvK.rtbz", dtz)])
+}
+
+fn probe_wdl(tb: &Tablebase, fen: &str) -> Result<Wdl, ProbeError> {
+    tb.probe_wdl(&Chessboard::from_fen(fen).unwrap())
+}
+
+/// FEN of the pieces on the squares, `None` if the position is not legal
+fn position(pieces: &[(char, usize)], side: char) -> Option<String> {
+    let mut board = [None; 64];
+    for &(piece, sq) in pieces {
+        if board[sq].is_some() {
+            return None;
+        }
+        if piece.eq_ignore_ascii_case(&'p') && !(8..56).contains(&sq) {
+            return None;
+        }
+        board[sq] = Some(piece);
+    }
+
+    let kings: Vec<_> = pieces
+        .iter()
+        .filter(|(p, _)| p.eq_ignore_ascii_case(&'k'))
+        .collect();
+    let (a, b) = (kings[0].1, kings[1].1);
+    if (a & 7).abs_diff(b & 7) <= 1 && (a >> 3).abs_diff(b >> 3) <= 1 {
+        return None;
+    }
+
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            match board[rank * 8 + file] {
+                Some(piece) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    fen.push(piece);
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+
+    let other = if side == 'w' { 'b' } else { 'w' };
+    let waiting = Chessboard::from_fen(&format!("{fen} {other} - - 0 1")).ok()?;
+    if !waiting.checkers.is_empty() {
+        return None;
+    }
+    Some(format!("{fen} {side} - - 0 1"))
+}
+
+fn transform(sq: usize, t: usize) -> usize {
+    let (mut file, mut rank) = (sq & 7, sq >> 3);
+    if t & 1 != 0 {
+        file = 7 - file;
+    }
+    if t & 2 != 0 {
+        rank = 7 - rank;
+    }
+    if t & 4 != 0 {
+        (file, rank) = (rank, file);
+    }
+    rank * 8 + file
+}
+
+fn swap_colors(pieces: &[(char, usize)]) -> Vec<(char, usize)> {
+    pieces
+        .iter()
+        .map(|&(piece, sq)| {
+            let piece = if piece.is_ascii_uppercase() {
+                piece.to_ascii_lowercase()
+            } else {
+                piece.to_ascii_uppercase()
+            };
+            (piece, sq ^ 56)
+        })
+        .collect()
+}
+
+#[test]
+fn single_value() {
+    let tb = kqk_tablebase("single");
+    assert_eq!(tb.max_pieces(), 3);
+
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/8/8/8/KQ6 w - - 0 1"),
+        Ok(Wdl::Win)
+    );
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/8/8/8/KQ6 b - - 0 1"),
+        Ok(Wdl::Loss)
+    );
+    // The queen can be captured
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/3Q4/8/8/K7 b - - 0 1"),
+        Ok(Wdl::Draw)
+    );
+    // Colors are swapped when black has the queen
+    assert_eq!(
+        probe_wdl(&tb, "kq6/8/8/8/3K4/8/8/8 b - - 0 1"),
+        Ok(Wdl::Win)
+    );
+    assert_eq!(
+        probe_wdl(&tb, "kq6/8/8/8/3K4/8/8/8 w - - 0 1"),
+        Ok(Wdl::Loss)
+    );
+
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/8/8/8/K7 w - - 0 1"),
+        Ok(Wdl::Draw)
+    );
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/8/8/8/KR6 w - - 0 1"),
+        Err(ProbeError::MissingTable)
+    );
+    assert_eq!(
+        probe_wdl(&tb, "4k3/8/8/8/8/8/8/4K2R w K - 0 1"),
+        Err(ProbeError::CastlingRights)
+    );
+}
+
+#[test]
+fn dtz() {
+    let tb = kqk_tablebase("dtz");
+
+    let board = Chessboard::from_fen("8/8/8/3k4/8/8/8/KQ6 w - - 0 1").unwrap();
+    // Stored in moves, converted to plies
+    assert_eq!(tb.probe_dtz(&board), Ok(11));
+    assert_eq!(tb.probe_outcome(&board), Ok(Wdl::Win));
+
+    let board = Chessboard::from_fen("8/8/8/3k4/8/8/8/KQ6 w - - 95 60").unwrap();
+    assert_eq!(tb.probe_outcome(&board), Ok(Wdl::CursedWin));
+
+    // Black to move is looked up one ply ahead
+    let board = Chessboard::from_fen("8/8/8/3k4/8/8/8/KQ6 b - - 0 1").unwrap();
+    assert_eq!(tb.probe_dtz(&board), Ok(-12));
+
+    let board = Chessboard::from_fen("8/8/8/3k4/3Q4/8/8/K7 b - - 0 1").unwrap();
+    assert_eq!(tb.probe_dtz(&board), Ok(0));
+
+    assert_eq!(Wdl::from_dtz(-20, 90), Wdl::BlessedLoss);
+    assert_eq!(Wdl::from_dtz(-20, 80), Wdl::Loss);
+    assert_eq!(Wdl::from_dtz(0, 99), Wdl::Draw);
+    assert_eq!(Wdl::Win.neg(), Wdl::Loss);
+}
+
+#[test]
+fn root_moves() {
+    let tb = kqk_tablebase("root");
+    let board = Chessboard::from_fen("8/8/8/3k4/8/8/8/KQ6 w - - 0 1").unwrap();
+
+    let moves = tb.root_moves(&board).unwrap();
+    assert_eq!(moves.len(), board.to_vec().len());
+    assert!(moves.windows(2).all(|w| w[0].rank >= w[1].rank));
+
+    let filtered = tb.filter_root_moves(&board).unwrap();
+    assert!(!filtered.is_empty());
+    assert!(filtered.len() < moves.len());
+
+    // Moves next to the king hang the queen
+    let hanging = |m: &Move| match m {
+        Move::Standard { to, .. } => [
+            Square::C4,
+            Square::C5,
+            Square::C6,
+            Square::D4,
+            Square::D6,
+            Square::E4,
+        ]
+        .contains(to),
+        _ => false,
+    };
+    assert!(filtered.iter().all(|m| !hanging(m)));
+    for root in moves {
+        assert_eq!(hanging(&root.m), root.dtz == 0, "{:?}", root.m);
+    }
+
+    // Every move reaches the fifty-move rule without mating
+    let board = Chessboard::from_fen("8/8/8/3k4/8/8/8/KQ6 w - - 99 60").unwrap();
+    let moves = tb.root_moves(&board).unwrap();
+    assert_eq!(moves.len(), board.to_vec().len());
+    assert!(moves.iter().all(|root| root.dtz == 0 && root.rank == 0));
+}
+
+#[test]
+fn compressed_symmetries() {
+    const SIZE: usize = 31332;
+    let wdl = write_table(
+        false,
+        1,
+        &[6, 4, 14],
+        1,
+        &[packed(SIZE, 1), packed(SIZE, 2)],
+    );
+    let tb = tablebase("pieces", &[("KRvK.rtbw", wdl)]);
+
+    let mut seen = HashSet::new();
+    for king in (0..64).step_by(5) {
+        for rook in (0..64).step_by(3) {
+            for enemy in (0..64).step_by(7) {
+                for side in ['w', 'b'] {
+                    let pieces = [('K', king), ('R', rook), ('k', enemy)];
+                    let Some(fen) = position(&pieces, side) else {
+                        continue;
+                    };
+                    let wdl = probe_wdl(&tb, &fen).unwrap();
+                    seen.insert(wdl);
+
+                    for t in 1..8 {
+                        let moved: Vec<_> = pieces
+                            .iter()
+                            .map(|&(p, sq)| (p, transform(sq, t)))
+                            .collect();
+                        let fen = position(&moved, side).unwrap();
+                        assert_eq!(probe_wdl(&tb, &fen), Ok(wdl), "{fen}");
+                    }
+
+                    let other = if side == 'w' { 'b' } else { 'w' };
+                    let fen = position(&swap_colors(&pieces), other).unwrap();
+                    assert_eq!(probe_wdl(&tb, &fen), Ok(wdl), "{fen}");
+                }
+            }
+        }
+    }
+    assert_eq!(seen.len(), 5);
+}
+
+#[test]
+fn compressed_pawns() {
+    const SIZE: usize = 6 * 63 * 62;
+    let parts: Vec<_> = (0..4)
+        .flat_map(|file| [packed(SIZE, file), packed(SIZE, file + 8)])
+        .collect();
+    let wdl = write_table(false, 3, &[1, 6, 14], 4, &parts);
+    let tb = tablebase("pawns", &[("KPvK.rtbw", wdl)]);
+
+    let mut seen = HashSet::new();
+    for pawn in 8..56 {
+        for king in (0..64).step_by(5) {
+            for enemy in (0..64).step_by(3) {
+                for side in ['w', 'b'] {
+                    let pieces = [('P', pawn), ('K', king), ('k', enemy)];
+                    let Some(fen) = position(&pieces, side) else {
+                        continue;
+                    };
+                    let wdl = probe_wdl(&tb, &fen).unwrap();
+                    seen.insert(wdl);
+
+                    let mirrored: Vec<_> = pieces.iter().map(|&(p, sq)| (p, sq ^ 7)).collect();
+                    let fen = position(&mirrored, side).unwrap();
+                    assert_eq!(probe_wdl(&tb, &fen), Ok(wdl), "{fen}");
+
+                    let other = if side == 'w' { 'b' } else { 'w' };
+                    let fen = position(&swap_colors(&pieces), other).unwrap();
+                    assert_eq!(probe_wdl(&tb, &fen), Ok(wdl), "{fen}");
+                }
+            }
+        }
+    }
+    assert!(seen.len() > 1);
+}
+
+#[test]
+fn corrupted_table() {
+    let mut wdl = write_table(
+        false,
+        1,
+        &[6, 4, 14],
+        1,
+        &[packed(31332, 1), packed(31332, 2)],
+    );
+    wdl.truncate(wdl.len() - 64);
+    let tb = tablebase("corrupted", &[("KRvK.rtbw", wdl)]);
+
+    assert_eq!(
+        probe_wdl(&tb, "8/8/8/3k4/8/8/8/KR6 w - - 0 1"),
+        Err(ProbeError::InvalidTable)
+    );
+}
+
+/// Tables of the published Syzygy set, the ones above only cover the layout
+fn published_tablebase() -> Tablebase {
+    let dir = std::env::var("SYZYGY_PATH").expect("SYZYGY_PATH holds the KQvK and KRvK tables");
+    Tablebase::open(dir).unwrap()
+}
+
+#[test]
+#[ignore = "needs the KQvK and KRvK tables of the Syzygy set in SYZYGY_PATH"]
+fn published_tables() {
+    let tb = published_tablebase();
+    assert_eq!(tb.max_pieces(), 3);
+
+    for (fen, wdl) in [
+        ("7k/8/8/8/8/8/8/KQ6 w - - 0 1", Wdl::Win),
+        ("7k/8/8/8/8/8/8/KR6 w - - 0 1", Wdl::Win),
+        ("8/8/8/8/8/2k5/8/KQ6 b - - 0 1", Wdl::Loss),
+        ("kq6/8/8/8/3K4/8/8/8 w - - 0 1", Wdl::Loss),
+        // The king takes the piece
+        ("K7/8/8/8/8/8/6Q1/7k b - - 0 1", Wdl::Draw),
+        ("K7/8/8/8/8/8/6R1/7k b - - 0 1", Wdl::Draw),
+        // Stalemate
+        ("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Wdl::Draw),
+    ] {
+        assert_eq!(probe_wdl(&tb, fen), Ok(wdl), "{fen}");
+    }
+
+    let board = Chessboard::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1").unwrap();
+    assert_eq!(tb.probe_dtz(&board), Ok(1));
+    let mate = Move::Standard {
+        piece: Piece::WHITE_QUEEN,
+        from: Square::B1,
+        to: Square::B8,
+    };
+    assert!(tb.filter_root_moves(&board).unwrap().contains(&mate));
+
+    // Without captures the distance to zeroing is the distance to mate, the
+    // longest being mates in 10 and 16 moves
+    for (piece, longest) in [('Q', 19), ('R', 31)] {
+        let mut max = 0;
+        for king in 0..64 {
+            for sq in 0..64 {
+                for enemy in 0..64 {
+                    let pieces = [('K', king), (piece, sq), ('k', enemy)];
+                    let Some(fen) = position(&pieces, 'w') else {
+                        continue;
+                    };
+                    let board = Chessboard::from_fen(&fen).unwrap();
+                    let dtz = tb.probe_dtz(&board).unwrap();
+                    assert_eq!(dtz > 0, tb.probe_wdl(&board) == Ok(Wdl::Win), "{fen}");
+                    max = max.max(dtz);
+                }
+            }
+        }
+        assert_eq!(max, longest, "K{piece}vK");
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::LazyLock;
+
+use chess::Chessboard;
+use chess::tablebase::{Dtm, EndgameTable, Generator, TablebaseError};
+
+static GENERATOR: LazyLock<Generator> = LazyLock::new(|| {
+    let mut generator = Generator::new();
+    for name in ["KQvK", "KRvK", "KPvK"] {
+        generator.generate(name).unwrap();
+    }
+    generator
+});
+
+fn probe(fen: &str) -> Result<Dtm, TablebaseError> {
+    GENERATOR.probe(&Chessboard::from_fen(fen).unwrap())
+}
+
+/// FEN of the pieces on the squares, `None` if the position is not legal
+fn position(pieces: &[(char, usize)], side: char) -> Option<String> {
+    let mut board = [None; 64];
+    for &(piece, sq) in pieces {
+        if board[sq].is_some() {
+            return None;
+        }
+        if piece.eq_ignore_ascii_case(&'p') && !(8..56).contains(&sq) {
+            return None;
+        }
+        board[sq] = Some(piece);
+    }
+
+    let (a, b) = (pieces[0].1, pieces[1].1);
+    if (a & 7).abs_diff(b & 7) <= 1 && (a >> 3).abs_diff(b >> 3) <= 1 {
+        return None;
+    }
+
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            match board[rank * 8 + file] {
+                Some(piece) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    fen.push(piece);
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+
+    let other = if side == 'w' { 'b' } else { 'w' };
+    let waiting = Chessboard::from_fen(&format!("{fen} {other} - - 0 1")).ok()?;
+    if !waiting.checkers.is_empty() {
+        return None;
+    }
+    Some(format!("{fen} {side} - - 0 1"))
+}
+
+#[test]
+fn longest_mates() {
+    let kqk = GENERATOR.table("KQvK").unwrap().stats();
+    assert_eq!(kqk.longest[0], 19);
+    assert_eq!(kqk.draws[0], 0);
+    assert_eq!(kqk.losses[0], 0);
+
+    let krk = GENERATOR.table("KRvK").unwrap().stats();
+    assert_eq!(krk.longest[0], 31);
+    assert_eq!(krk.draws[0], 0);
+
+    let kpk = GENERATOR.table("KPvK").unwrap().stats();
+    assert_eq!(kpk.longest[0], 55);
+
+    for name in ["KvK", "KNvK", "KBvK"] {
+        let stats = GENERATOR.table(name).unwrap().stats();
+        assert_eq!(stats.wins, [0, 0]);
+        assert_eq!(stats.losses, [0, 0]);
+    }
+}
+
+#[test]
+fn known_positions() {
+    assert_eq!(probe("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1"), Ok(Dtm::Loss(0)));
+    assert_eq!(probe("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1"), Ok(Dtm::Win(1)));
+    assert_eq!(probe("7k/5Q2/5K2/8/8/8/8/8 b - - 0 1"), Ok(Dtm::Draw));
+    assert_eq!(probe("6k1/5Q2/8/8/8/8/8/K7 b - - 0 1"), Ok(Dtm::Draw));
+
+    assert_eq!(probe("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"), Ok(Dtm::Draw));
+    assert_eq!(probe("4k3/4P3/4K3/8/8/8/8/8 w - - 0 1"), Ok(Dtm::Win(17)));
+    assert_eq!(probe("k7/8/8/8/8/8/P7/4K3 w - - 0 1"), Ok(Dtm::Draw));
+    assert!(matches!(
+        probe("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"),
+        Ok(Dtm::Loss(_))
+    ));
+    assert!(matches!(
+        probe("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"),
+        Ok(Dtm::Win(_))
+    ));
+
+    // The stronger side may be black
+    assert_eq!(probe("8/8/8/8/8/6k1/6q1/7K w - - 0 1"), Ok(Dtm::Loss(0)));
+    assert!(matches!(
+        probe("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1"),
+        Ok(Dtm::Loss(_))
+    ));
+}
+
+/// Every position is one ply away from the best of its moves
+#[test]
+fn consistent_with_moves() {
+    let materials: [&[char]; 4] = [&['Q'], &['R'], &['P'], &['p']];
+    let mut checked = 0;
+    for pieces in materials {
+        for i in (0..64 * 64 * 64).step_by(97) {
+            let squares = [i / 4096, i / 64 % 64, i % 64];
+            let mut placement = vec![('K', squares[0]), ('k', squares[1])];
+            placement.extend(pieces.iter().map(|&p| (p, squares[2])));
+
+            for side in ['w', 'b'] {
+                let Some(fen) = position(&placement, side) else {
+                    continue;
+                };
+                let cb = Chessboard::from_fen(&fen).unwrap();
+                let moves = cb.to_vec();
+                let expected = if moves.is_empty() && !cb.checkers.is_empty() {
+                    Dtm::Loss(0)
+                } else if moves.is_empty() {
+                    Dtm::Draw
+                } else {
+                    moves
+                        .into_iter()
+                        .map(|m| GENERATOR.probe(&cb.move_new(m)).unwrap().before_move())
+                        .max()
+                        .unwrap()
+                };
+                assert_eq!(GENERATOR.probe(&cb), Ok(expected), "{fen}");
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 4000);
+}
+
+#[test]
+fn serialization() {
+    let table = GENERATOR.table("KPvK").unwrap();
+    let bytes = table.to_bytes();
+    assert_eq!(EndgameTable::from_bytes(&bytes).as_ref(), Ok(table));
+
+    let mut generator = Generator::new();
+    generator.insert(EndgameTable::from_bytes(&bytes).unwrap());
+    let cb = Chessboard::from_fen("8/8/8/8/8/3k4/3p4/5K2 b - - 0 1").unwrap();
+    assert_eq!(
+        generator.table("KvKP").unwrap().probe(&cb),
+        GENERATOR.probe(&cb).ok()
+    );
+
+    assert_eq!(
+        EndgameTable::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(TablebaseError::InvalidData)
+    );
+    assert_eq!(
+        EndgameTable::from_bytes(&bytes[1..]),
+        Err(TablebaseError::InvalidData)
+    );
+}
+
+#[test]
+fn errors() {
+    let mut generator = Generator::new();
+    assert_eq!(
+        generator.generate("KQK").err(),
+        Some(TablebaseError::InvalidMaterial)
+    );
+    assert_eq!(
+        generator.generate("KQRvKR").err(),
+        Some(TablebaseError::TooManyPieces)
+    );
+    assert_eq!(
+        generator
+            .probe(&Chessboard::from_fen("7k/8/8/8/8/8/8/K6Q w - - 0 1").unwrap())
+            .err(),
+        Some(TablebaseError::MissingTable)
+    );
+    assert_eq!(
+        probe("4k3/8/8/8/8/8/8/4K2R w K - 0 1"),
+        Err(TablebaseError::CastlingRights)
+    );
+}
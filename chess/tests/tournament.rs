@@ -0,0 +1,394 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::{io, time::Duration};
+
+use chess::{
+    ChessError, Chessboard, DrawType, GameResult, KIWIPETE_POS, Move, START_POS, WinType,
+    clock::ManualTime,
+    eval::HandCrafted,
+    game::Game,
+    pgn::Pgn,
+    search::{Limit, Search},
+    tournament::{
+        DrawRule, GameRecord, MatchConfig, MatchStats, Opening, Player, ResignRule, Sprt,
+        SprtStatus, TimeSourceFactory, elo_from_score, run, score_from_elo,
+    },
+    uci::{BestMove, Go, Info, Score},
+};
+
+enum Kind {
+    Search(Box<Search<HandCrafted>>),
+    /// Plays the first legal move, reporting the score
+    First(i32),
+    /// Plays the first legal move once the time has moved by the delay
+    Slow {
+        time: ManualTime,
+        delay: Duration,
+    },
+    Illegal,
+    Broken,
+}
+
+struct TestPlayer {
+    name: &'static str,
+    kind: Kind,
+}
+
+impl TestPlayer {
+    fn search(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: Kind::Search(Box::new(Search::new(HandCrafted::new()))),
+        }
+    }
+
+    const fn first(name: &'static str, score: i32) -> Self {
+        Self {
+            name,
+            kind: Kind::First(score),
+        }
+    }
+}
+
+impl Player for TestPlayer {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn new_game(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn think(&mut self, game: &Game, go: &Go, _: Duration) -> io::Result<BestMove> {
+        let (m, info) = match &mut self.kind {
+            Kind::Search(search) => {
+                search.set_history(game.history());
+                let depth = go.depth.unwrap_or(1);
+                let result = search.search(game.position(), Limit::Depth(depth));
+                let info = Info {
+                    depth: Some(result.depth),
+                    score: Some(Score::Cp(result.score)),
+                    pv: result.pv,
+                    ..Default::default()
+                };
+                (result.best_move, info)
+            }
+            Kind::First(score) => {
+                let info = Info {
+                    depth: Some(1),
+                    score: Some(Score::Cp(*score)),
+                    ..Default::default()
+                };
+                (game.position().to_vec().first().copied(), info)
+            }
+            Kind::Slow { time, delay } => {
+                time.advance(*delay);
+                (game.position().to_vec().first().copied(), Info::default())
+            }
+            Kind::Illegal => (Some(Move::Null), Info::default()),
+            Kind::Broken => return Err(io::Error::other("crashed")),
+        };
+        Ok(BestMove {
+            m,
+            ponder: None,
+            info: vec![info],
+        })
+    }
+}
+
+fn play_match(
+    config: &MatchConfig,
+    players: impl Fn(usize) -> TestPlayer + Sync,
+) -> (MatchStats, Vec<GameRecord>) {
+    let mut games = Vec::new();
+    let stats = run(
+        config,
+        |i| Ok(players(i)),
+        |game, _| games.push(game.clone()),
+    )
+    .unwrap();
+    games.sort_by_key(|game| game.round);
+    (stats, games)
+}
+
+#[test]
+fn statistics() {
+    let stats = MatchStats {
+        wins: 60,
+        draws: 20,
+        losses: 20,
+    };
+    assert_eq!(stats.games(), 100);
+    assert!((stats.score() - 0.7).abs() < 1e-9);
+    let elo = stats.elo().unwrap();
+    assert!((elo.diff - 147.19).abs() < 0.01, "{elo:?}");
+    assert!(elo.margin > 60.0 && elo.margin < 80.0, "{elo:?}");
+    for elo in [-300.0, -10.0, 0.0, 42.0] {
+        assert!((elo_from_score(score_from_elo(elo)) - elo).abs() < 1e-9);
+    }
+    assert_eq!(MatchStats::default().elo(), None);
+    let sweep = MatchStats {
+        wins: 10,
+        ..Default::default()
+    };
+    assert_eq!(sweep.elo(), None);
+
+    let sprt = Sprt::default();
+    let (lower, upper) = sprt.bounds();
+    assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+    assert_eq!(sprt.status(&stats), SprtStatus::Running);
+    let strong = MatchStats {
+        wins: 600,
+        draws: 200,
+        losses: 200,
+    };
+    assert_eq!(sprt.status(&strong), SprtStatus::AcceptH1);
+    let even = MatchStats {
+        wins: 20_000,
+        draws: 20_000,
+        losses: 20_000,
+    };
+    assert!(sprt.llr(&even) < lower);
+    assert_eq!(sprt.status(&even), SprtStatus::AcceptH0);
+    let few = MatchStats {
+        wins: 6,
+        draws: 10,
+        losses: 4,
+    };
+    assert_eq!(sprt.status(&few), SprtStatus::Running);
+
+    // The variance is regularised, so sweeps decide the test too
+    assert!(sprt.llr(&sweep) > 0.0);
+    assert_eq!(sprt.status(&sweep), SprtStatus::Running);
+    let wins = MatchStats {
+        wins: 30,
+        ..Default::default()
+    };
+    assert_eq!(sprt.status(&wins), SprtStatus::AcceptH1);
+    let losses = MatchStats {
+        losses: 30,
+        ..Default::default()
+    };
+    assert_eq!(sprt.status(&losses), SprtStatus::AcceptH0);
+}
+
+#[test]
+fn openings() {
+    let epd = format!(
+        "{}\n\n{} hmvc 3; fmvn 12; id \"kiwipete\";\n",
+        &START_POS[..START_POS.len() - 4],
+        &KIWIPETE_POS[..KIWIPETE_POS.len() - 4]
+    );
+    let openings = Opening::parse_epd(&epd).unwrap();
+    assert_eq!(openings.len(), 2);
+    assert_eq!(openings[0].start.get_fen(), START_POS);
+    assert_eq!(openings[1].start.half_moves(), 3);
+    assert_eq!(openings[1].start.full_moves(), 12);
+    assert!(Opening::parse_epd("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq -").is_err());
+
+    let pgn = "1. e4 e5 2. Nf3 Nc6 3. Bb5 *\n\n1. d4 d5 *";
+    let openings = Opening::parse_pgn(pgn, Some(4)).unwrap();
+    assert_eq!(openings.len(), 2);
+    assert_eq!(openings[0].moves.len(), 4);
+    assert_eq!(openings[1].moves.len(), 2);
+    assert_eq!(
+        openings[0].position().get_fen(),
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"
+    );
+    assert_eq!(
+        Opening::parse_pgn("1. e4 e4 *", None).err(),
+        Some(ChessError::InvalidPgn)
+    );
+}
+
+#[test]
+fn match_between_searches() {
+    let openings = Opening::parse_pgn("1. e4 e5 *\n\n1. d4 d5 *", None).unwrap();
+    let config = MatchConfig {
+        openings,
+        games: 4,
+        concurrency: 2,
+        limits: Go {
+            depth: Some(1),
+            ..Default::default()
+        },
+        adjudication: chess::tournament::Adjudication {
+            max_moves: Some(20),
+            ..Default::default()
+        },
+        event: String::from("Test match"),
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| TestPlayer::search(["First", "Second"][i]));
+    assert_eq!(stats.games(), 4);
+    assert_eq!(games.len(), 4);
+
+    for (round, game) in games.iter().enumerate() {
+        assert_eq!(game.round, round);
+        assert_eq!(game.white, round % 2);
+        assert!(game.result.is_over());
+        let pgn = &game.pgn;
+        assert_eq!(pgn.tag("Event"), Some("Test match"));
+        assert_eq!(pgn.tag("Round"), Some((round + 1).to_string().as_str()));
+        assert_eq!(pgn.tag("White"), Some(["First", "Second"][round % 2]));
+        assert_eq!(pgn.tag("Black"), Some(["Second", "First"][round % 2]));
+        assert_eq!(pgn.result(), game.result.pgn_result());
+        let first = if round < 2 { "e2e4" } else { "d2d4" };
+        let start = pgn.start;
+        assert_eq!(
+            pgn.moves[0],
+            chess::MoveGen::new(start.moves_cache)
+                .from_str(&start, first)
+                .unwrap()
+        );
+        // The opening moves have no comment, the engine moves have the depth
+        assert_eq!(pgn.comments[0], None);
+        assert!(pgn.comments[2].as_deref().unwrap().ends_with("/1"));
+
+        let again = Pgn::parse(&pgn.to_string()).unwrap();
+        assert_eq!(again.moves, pgn.moves);
+        assert_eq!(again.comments, pgn.comments);
+    }
+}
+
+#[test]
+fn forfeits() {
+    let config = MatchConfig::default();
+    let (stats, games) = play_match(&config, |i| match i {
+        0 => TestPlayer::first("Legal", 0),
+        _ => TestPlayer {
+            name: "Illegal",
+            kind: Kind::Illegal,
+        },
+    });
+    assert_eq!(stats.wins, 2);
+    assert!(
+        games
+            .iter()
+            .all(|game| game.pgn.tag("Termination") == Some("rules infraction"))
+    );
+
+    let (stats, games) = play_match(&config, |i| match i {
+        0 => TestPlayer {
+            name: "Broken",
+            kind: Kind::Broken,
+        },
+        _ => TestPlayer::first("Legal", 0),
+    });
+    assert_eq!(stats.losses, 2);
+    assert_eq!(games[0].result, GameResult::BlackWin(WinType::Abandonment));
+    assert_eq!(games[0].pgn.moves.len(), 0);
+}
+
+#[test]
+fn score_adjudication() {
+    let config = MatchConfig {
+        games: 1,
+        adjudication: chess::tournament::Adjudication {
+            resign: Some(ResignRule {
+                moves: 3,
+                score: 500,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| match i {
+        0 => TestPlayer::first("Pessimist", -1000),
+        _ => TestPlayer::first("Optimist", 1000),
+    });
+    assert_eq!(stats.losses, 1);
+    assert_eq!(games[0].result, GameResult::BlackWin(WinType::Adjudication));
+    assert_eq!(games[0].pgn.moves.len(), 5);
+    assert_eq!(games[0].pgn.tag("Termination"), Some("adjudication"));
+
+    let config = MatchConfig {
+        games: 1,
+        adjudication: chess::tournament::Adjudication {
+            draw: Some(DrawRule {
+                move_number: 3,
+                moves: 2,
+                score: 10,
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| TestPlayer::first(["A", "B"][i], 0));
+    assert_eq!(stats.draws, 1);
+    assert_eq!(games[0].result, GameResult::Draw(DrawType::Adjudication));
+    // Moves 3 and 4 of both sides
+    assert_eq!(games[0].pgn.moves.len(), 8);
+}
+
+#[test]
+fn fifty_moves_claimed() {
+    let start = Chessboard::from_fen("8/8/4k3/8/8/4K3/8/R7 w - - 99 80").unwrap();
+    let config = MatchConfig {
+        openings: vec![Opening::new(start)],
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| TestPlayer::first(["A", "B"][i], 0));
+    assert_eq!(stats.draws, 2);
+    for game in games {
+        assert_eq!(game.result, GameResult::Draw(DrawType::FiftyMoveRule));
+        assert_eq!(game.pgn.moves.len(), 1);
+    }
+}
+
+#[test]
+fn time_forfeit() {
+    let time = ManualTime::new();
+    let config = MatchConfig {
+        games: 1,
+        time_control: Some("0.05".parse().unwrap()),
+        margin: Duration::from_secs(1),
+        time_source: TimeSourceFactory::new({
+            let time = time.clone();
+            move || time.clone()
+        }),
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| match i {
+        0 => TestPlayer {
+            name: "Slow",
+            kind: Kind::Slow {
+                time: time.clone(),
+                delay: Duration::from_millis(100),
+            },
+        },
+        _ => TestPlayer::first("Fast", 0),
+    });
+    assert_eq!(stats.losses, 1);
+    assert_eq!(games[0].result, GameResult::BlackWin(WinType::Timeout));
+    assert_eq!(games[0].pgn.moves.len(), 0);
+    assert_eq!(games[0].pgn.tag("TimeControl"), Some("0.05"));
+    assert_eq!(games[0].pgn.tag("Termination"), Some("time forfeit"));
+}
+
+#[test]
+fn sprt_ends_the_match() {
+    let sprt = Sprt::default();
+    let config = MatchConfig {
+        games: 200,
+        sprt: Some(sprt),
+        ..Default::default()
+    };
+    let (stats, games) = play_match(&config, |i| match i {
+        0 => TestPlayer::first("Legal", 0),
+        _ => TestPlayer {
+            name: "Illegal",
+            kind: Kind::Illegal,
+        },
+    });
+    assert_eq!(sprt.status(&stats), SprtStatus::AcceptH1);
+    assert!(stats.games() < 200);
+    assert_eq!(stats.wins, stats.games());
+    assert_eq!(games.len() as u32, stats.games());
+    // The match stops at the first game deciding the test
+    let before = MatchStats {
+        wins: stats.wins - 1,
+        ..Default::default()
+    };
+    assert_eq!(sprt.status(&before), SprtStatus::Running);
+}
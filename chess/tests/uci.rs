@@ -0,0 +1,306 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::{io::ErrorKind, process::Command, time::Duration};
+
+use chess::{
+    Chessboard, KIWIPETE_POS, START_POS,
+    uci::{Bound, Engine, EngineOption, Go, Info, OptionKind, Score, format_move},
+};
+
+/// Engine answering from a script: `go` searches until `stop` when infinite
+/// and echoes the last position in an `info string`
+const FAKE_ENGINE: &str = r#"
+position=""
+while read -r cmd rest; do
+    case "$cmd" in
+        uci)
+            echo "id name Fake Engine 1.0"
+            echo "id author The Tests"
+            echo "option name Hash type spin default 16 min 1 max 1024"
+            echo "option name Ponder type check default false"
+            echo "option name Style type combo default Normal var Solid var Normal var Risky"
+            echo "option name Clear Hash type button"
+            echo "uciok"
+            ;;
+        isready) echo "readyok" ;;
+        position) position="$rest" ;;
+        go)
+            echo "info string $position"
+            echo "info depth 1 seldepth 2 multipv 1 score cp 20 nodes 20 nps 2000 time 10 pv e2e4 e7e5"
+            echo "info depth 1 multipv 2 score cp 10 pv d2d4"
+            echo "info depth 2 multipv 1 score cp 35 lowerbound nodes 80 pv d2d4 d7d5 e2e2"
+            case "$rest" in
+                *infinite*) ;;
+                *) echo "bestmove d2d4 ponder d7d5" ;;
+            esac
+            ;;
+        stop) echo "bestmove e2e4 ponder e1e2" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+fn fake_engine() -> Engine {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(FAKE_ENGINE);
+    Engine::new(command, Duration::from_secs(5)).unwrap()
+}
+
+fn start() -> Chessboard {
+    Chessboard::from_fen(START_POS).unwrap()
+}
+
+fn mv(cb: &Chessboard, m: &str) -> chess::Move {
+    chess::MoveGen::new(cb.moves_cache).from_str(cb, m).unwrap()
+}
+
+#[test]
+fn info_lines() {
+    let cb = start();
+    let info = Info::parse(
+        "info depth 12 seldepth 18 multipv 2 score mate -3 upperbound nodes 123456 nps 1000000 \
+         hashfull 12 tbhits 4 time 123 pv e2e4 e7e5 g1f3",
+        &cb,
+    )
+    .unwrap();
+    assert_eq!(info.depth, Some(12));
+    assert_eq!(info.seldepth, Some(18));
+    assert_eq!(info.multipv, 2);
+    assert_eq!(info.score, Some(Score::Mate(-3)));
+    assert_eq!(info.bound, Some(Bound::Upper));
+    assert_eq!(info.nodes, Some(123_456));
+    assert_eq!(info.nps, Some(1_000_000));
+    assert_eq!(info.hashfull, Some(12));
+    assert_eq!(info.tbhits, Some(4));
+    assert_eq!(info.time, Some(Duration::from_millis(123)));
+    let e4 = mv(&cb, "e2e4");
+    let e5 = mv(&cb.move_new(e4), "e7e5");
+    let nf3 = mv(&cb.move_new(e4).move_new(e5), "g1f3");
+    assert_eq!(info.pv, vec![e4, e5, nf3]);
+
+    // Without MultiPV, and the principal variation is cut at an illegal move
+    let info = Info::parse("info score cp 15 pv e2e4 e2e4 e7e5 depth 3", &cb).unwrap();
+    assert_eq!(info.multipv, 1);
+    assert_eq!(info.score, Some(Score::Cp(15)));
+    assert_eq!(info.bound, None);
+    assert_eq!(info.pv, vec![e4]);
+    assert_eq!(info.depth, Some(3));
+
+    let info = Info::parse(
+        "info currmove g1f3 currmovenumber 3 string hello  world",
+        &cb,
+    )
+    .unwrap();
+    assert_eq!(info.currmove, Some(mv(&cb, "g1f3")));
+    assert_eq!(info.string.as_deref(), Some("hello world"));
+
+    assert_eq!(Info::parse("bestmove e2e4", &cb), None);
+}
+
+#[test]
+fn options() {
+    let hash = EngineOption::parse("option name Hash type spin default 16 min 1 max 1024").unwrap();
+    assert_eq!(hash.name, "Hash");
+    assert_eq!(
+        hash.kind,
+        OptionKind::Spin {
+            default: 16,
+            min: 1,
+            max: 1024
+        }
+    );
+    assert!(hash.accepts(Some("1024")));
+    assert!(!hash.accepts(Some("0")));
+    assert!(!hash.accepts(Some("big")));
+    assert!(!hash.accepts(None));
+
+    let style = EngineOption::parse(
+        "option name Play Style type combo default Normal var Solid var Very Risky",
+    )
+    .unwrap();
+    assert_eq!(style.name, "Play Style");
+    assert_eq!(
+        style.kind,
+        OptionKind::Combo {
+            default: String::from("Normal"),
+            vars: vec![String::from("Solid"), String::from("Very Risky")]
+        }
+    );
+    assert!(style.accepts(Some("very risky")));
+    assert!(!style.accepts(Some("Normal ")));
+
+    let path = EngineOption::parse("option name SyzygyPath type string default <empty>").unwrap();
+    assert_eq!(
+        path.kind,
+        OptionKind::String {
+            default: String::new()
+        }
+    );
+    let ponder = EngineOption::parse("option name Ponder type check default true").unwrap();
+    assert_eq!(ponder.kind, OptionKind::Check { default: true });
+    assert!(ponder.accepts(Some("false")));
+    let clear = EngineOption::parse("option name Clear Hash type button").unwrap();
+    assert!(clear.accepts(None));
+
+    assert_eq!(
+        EngineOption::parse("option name Hash type spin default 16"),
+        None
+    );
+    assert_eq!(EngineOption::parse("option type check default true"), None);
+    assert_eq!(EngineOption::parse("id name Hash"), None);
+}
+
+#[test]
+fn go_commands() {
+    let cb = start();
+    assert_eq!(Go::default().to_string(), "go");
+    let go = Go {
+        wtime: Some(Duration::from_secs(60)),
+        btime: Some(Duration::from_millis(59_500)),
+        winc: Some(Duration::from_secs(1)),
+        binc: Some(Duration::from_secs(1)),
+        movestogo: Some(20),
+        ..Default::default()
+    };
+    assert_eq!(
+        go.to_string(),
+        "go wtime 60000 btime 59500 winc 1000 binc 1000 movestogo 20"
+    );
+    let go = Go {
+        depth: Some(10),
+        nodes: Some(5000),
+        searchmoves: vec![mv(&cb, "e2e4"), mv(&cb, "d2d4")],
+        ponder: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        go.to_string(),
+        "go searchmoves e2e4 d2d4 ponder depth 10 nodes 5000"
+    );
+    let go = Go {
+        movetime: Some(Duration::from_millis(250)),
+        infinite: true,
+        ..Default::default()
+    };
+    assert_eq!(go.to_string(), "go movetime 250 infinite");
+}
+
+#[test]
+fn long_algebraic_notation() {
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    for m in cb.to_vec() {
+        let uci = format_move(m).unwrap();
+        assert_eq!(mv(&cb, &uci), m);
+    }
+    let cb = Chessboard::from_fen("8/1P6/8/8/8/8/8/K1k5 w - - 0 1").unwrap();
+    assert_eq!(format_move(mv(&cb, "b7b8n")).unwrap(), "b7b8n");
+    assert_eq!(format_move(chess::Move::Null).unwrap(), "0000");
+}
+
+#[cfg(unix)]
+#[test]
+fn handshake_and_options() {
+    let mut engine = fake_engine();
+    assert_eq!(engine.name(), Some("Fake Engine 1.0"));
+    assert_eq!(engine.author(), Some("The Tests"));
+    assert_eq!(engine.options().len(), 4);
+    assert!(engine.option("hash").is_some());
+
+    engine.set_option("Hash", Some("64")).unwrap();
+    engine.set_option("style", Some("risky")).unwrap();
+    engine.set_option("Clear Hash", None).unwrap();
+    for (name, value) in [
+        ("Hash", Some("0")),
+        ("Threads", Some("2")),
+        ("Ponder", None),
+    ] {
+        let e = engine.set_option(name, value).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput, "{name}");
+    }
+    engine.new_game().unwrap();
+    assert!(engine.quit().unwrap().success());
+}
+
+#[cfg(unix)]
+#[test]
+fn search() {
+    let mut engine = fake_engine();
+    let cb = start();
+    let e4 = mv(&cb, "e2e4");
+    let e5 = mv(&cb.move_new(e4), "e7e5");
+    engine.set_position(&cb, &[e4, e5]).unwrap();
+
+    let mut lines = Vec::new();
+    let go = Go {
+        depth: Some(2),
+        ..Default::default()
+    };
+    let best = engine
+        .search(&go, Duration::from_secs(5), |info| lines.push(info.clone()))
+        .unwrap();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].string.as_deref(), Some("startpos moves e2e4 e7e5"));
+    // The engine answers as if from the start position
+    let position = engine.position();
+    assert_eq!(best.m, Some(mv(position, "d2d4")));
+    assert_eq!(
+        best.ponder,
+        Some(mv(&position.move_new(best.m.unwrap()), "d7d5"))
+    );
+    assert_eq!(best.info.len(), 2);
+    assert_eq!(best.info[0].score, Some(Score::Cp(35)));
+    assert_eq!(best.info[0].bound, Some(Bound::Lower));
+    assert_eq!(best.info[0].pv.len(), 2);
+    assert_eq!(best.info[1].multipv, 2);
+
+    let cb = Chessboard::from_fen(KIWIPETE_POS).unwrap();
+    engine.set_position(&cb, &[]).unwrap();
+    engine.go(&Go::default()).unwrap();
+    let mut fen = None;
+    let e = engine
+        .wait(Duration::from_secs(5), |info| {
+            fen = fen.clone().or_else(|| info.string.clone());
+        })
+        .unwrap_err();
+    assert_eq!(fen, Some(format!("fen {}", cb.get_fen())));
+    // d2d4 is not legal here
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+
+    // Illegal moves are not sent
+    let e = engine.set_position(&start(), &[e5]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
+
+#[cfg(unix)]
+#[test]
+fn stopped_after_the_limit() {
+    let mut engine = fake_engine();
+    let go = Go {
+        infinite: true,
+        ..Default::default()
+    };
+    let best = engine
+        .search(&go, Duration::from_millis(50), |_| ())
+        .unwrap();
+    let cb = start();
+    assert_eq!(best.m, Some(mv(&cb, "e2e4")));
+    // e1e2 is illegal after e2e4 for black, the hint is dropped
+    assert_eq!(best.ponder, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn timeouts() {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg("while read -r line; do :; done");
+    let e = Engine::new(command, Duration::from_millis(50)).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::TimedOut);
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg("exit 0");
+    let e = Engine::new(command, Duration::from_secs(5)).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        ErrorKind::UnexpectedEof | ErrorKind::BrokenPipe
+    ));
+}
@@ -0,0 +1,249 @@
+use chess::{
+    Castling, CastlingInfo, Chessboard, ChessboardRaw, KIWIPETE_POS, Move, Piece, START_POS, Side,
+    Square, UnMove,
+};
+
+const POSITIONS: &[&str] = &[
+    START_POS,
+    KIWIPETE_POS,
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+];
+
+/// The position without the move counters
+fn key(cb: &Chessboard) -> ChessboardRaw {
+    let mut raw = cb.into_raw();
+    raw.half_moves = 0;
+    raw.full_moves = 1;
+    raw
+}
+
+/// The positions and the positions one move later
+fn boards() -> Vec<Chessboard> {
+    let mut boards = Vec::new();
+    for fen in POSITIONS {
+        let cb = Chessboard::from_fen(fen).unwrap();
+        boards.extend(cb.to_vec().into_iter().map(|m| cb.move_new(m)));
+        boards.push(cb);
+    }
+    boards
+}
+
+fn rights(rights: &[(Castling, Side)]) -> CastlingInfo {
+    let mut castling = CastlingInfo::EMPTY;
+    for &(c, side) in rights {
+        castling.set(c, side, true);
+    }
+    castling
+}
+
+fn unmoves(fen: &str) -> Vec<UnMove> {
+    Chessboard::from_fen(fen).unwrap().generate_unmoves()
+}
+
+#[test]
+fn every_move_is_taken_back() {
+    for parent in boards() {
+        for m in parent.to_vec() {
+            let child = parent.move_new(m);
+            let expected = key(&parent);
+
+            let found = child.generate_unmoves().into_iter().any(|um| {
+                let mut cb = child;
+                cb.unmake_move(um);
+                um.m == m && key(&cb) == expected
+            });
+            assert!(found, "{} {m:?}", parent.get_fen());
+        }
+    }
+}
+
+#[test]
+fn unmoves_lead_back() {
+    for cb in boards() {
+        for um in cb.generate_unmoves() {
+            let mut parent = cb;
+            parent.unmake_move(um);
+            assert!(parent.to_vec().contains(&um.m), "{} {um:?}", cb.get_fen());
+            assert_eq!(key(&parent.move_new(um.m)), key(&cb), "{um:?}");
+        }
+    }
+}
+
+#[test]
+fn uncaptures_and_unpromotions() {
+    let ums = unmoves("Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    let promotion = |from| Move::Promotion {
+        from,
+        to: Square::A8,
+        promotion: Piece::WHITE_QUEEN,
+    };
+    assert!(
+        ums.iter()
+            .any(|um| um.m == promotion(Square::A7) && um.captured.is_none())
+    );
+    for captured in [
+        Piece::BLACK_KNIGHT,
+        Piece::BLACK_BISHOP,
+        Piece::BLACK_ROOK,
+        Piece::BLACK_QUEEN,
+    ] {
+        assert!(
+            ums.iter()
+                .any(|um| um.m == promotion(Square::B7) && um.captured == Some(captured))
+        );
+    }
+    assert!(!ums.iter().any(|um| um.captured == Some(Piece::BLACK_PAWN)));
+    // Taking back to d8 would leave the black king in check
+    assert!(!ums.iter().any(|um| matches!(
+        um.m,
+        Move::Standard {
+            from: Square::D8,
+            ..
+        }
+    )));
+    assert!(ums.iter().any(|um| matches!(
+        um.m,
+        Move::Standard {
+            from: Square::A2,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn en_passant() {
+    let ums = unmoves("4k3/8/3P4/8/8/8/8/4K3 b - - 0 1");
+    for from in [Square::C5, Square::E5] {
+        let um = ums
+            .iter()
+            .find(|um| {
+                um.m == Move::EnPassant {
+                    from,
+                    to: Square::D6,
+                }
+            })
+            .unwrap();
+        assert_eq!(um.captured, Some(Piece::BLACK_PAWN));
+        assert_eq!(um.en_passant, Some(Square::D6));
+    }
+
+    let ums = unmoves("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 2");
+    assert_eq!(
+        ums.iter().map(|um| um.m).collect::<Vec<_>>(),
+        [Move::Standard {
+            piece: Piece::WHITE_PAWN,
+            from: Square::E2,
+            to: Square::E4,
+        }]
+    );
+
+    // Black may have pushed the pawn two squares before the knight moved
+    let after_push = |fen: &str| {
+        unmoves(fen)
+            .iter()
+            .filter(|um| {
+                um.captured.is_none() && matches!(um.m, Move::Standard { from: Square::G1, .. })
+            })
+            .map(|um| um.en_passant)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        after_push("4k3/8/8/3p4/8/5N2/8/4K3 b - - 0 1"),
+        [None, Some(Square::D6)]
+    );
+    // The pawn could not have passed the knight
+    assert_eq!(after_push("4k3/3n4/8/3p4/8/5N2/8/4K3 b - - 0 1"), [None]);
+    // The bishop would have given check before the pawn blocked it
+    assert_eq!(after_push("4k3/8/4b3/3p4/8/1K3N2/8/8 b - - 0 1"), [None]);
+}
+
+#[test]
+fn castling_rights() {
+    let ums = unmoves("4k3/8/8/8/8/8/8/R4RK1 b - - 0 1");
+    let castled: Vec<_> = ums
+        .iter()
+        .filter(|um| matches!(um.m, Move::Castling { .. }))
+        .map(|um| um.castling)
+        .collect();
+    assert_eq!(
+        castled,
+        [
+            rights(&[(Castling::KingSide, Side::White)]),
+            rights(&[
+                (Castling::KingSide, Side::White),
+                (Castling::QueenSide, Side::White)
+            ]),
+        ]
+    );
+
+    let ums = unmoves("4k3/8/8/8/8/8/4K3/R6R b - - 0 1");
+    let king_back = ums
+        .iter()
+        .filter(|um| {
+            let m = Move::Standard {
+                piece: Piece::WHITE_KING,
+                from: Square::E1,
+                to: Square::E2,
+            };
+            um.m == m && um.captured.is_none()
+        })
+        .count();
+    assert_eq!(king_back, 4);
+
+    // The rook didn't move while the right is kept
+    let ums = unmoves("4k3/8/8/8/8/8/8/R3K2R b K - 0 1");
+    assert!(
+        !ums.iter()
+            .any(|um| matches!(um.m, Move::Standard { to: Square::H1, .. }))
+    );
+    assert!(
+        !ums.iter()
+            .any(|um| matches!(um.m, Move::Standard { to: Square::E1, .. }))
+    );
+    assert!(
+        ums.iter()
+            .any(|um| matches!(um.m, Move::Standard { to: Square::A1, .. }))
+    );
+}
+
+#[test]
+fn uncaptured_rooks() {
+    let takebacks = |fen: &str| -> Vec<CastlingInfo> {
+        unmoves(fen)
+            .into_iter()
+            .filter(|um| {
+                um.captured == Some(Piece::BLACK_ROOK)
+                    && matches!(um.m, Move::Standard { to: Square::H8, .. })
+            })
+            .map(|um| um.castling)
+            .collect()
+    };
+
+    // The captured rook may have kept its right
+    let rights_before = takebacks("r3k2B/8/8/8/8/8/8/4K3 b q - 0 1");
+    assert!(rights_before.contains(&rights(&[(Castling::QueenSide, Side::Black)])));
+    assert!(rights_before.contains(&rights(&[
+        (Castling::KingSide, Side::Black),
+        (Castling::QueenSide, Side::Black)
+    ])));
+    assert!(
+        rights_before
+            .iter()
+            .all(|castling| castling.is_set(Castling::QueenSide, Side::Black))
+    );
+
+    // Not without the king on its square
+    let rights_before = takebacks("r4k1B/8/8/8/8/8/8/4K3 b - - 0 1");
+    assert!(!rights_before.is_empty());
+    assert!(
+        rights_before
+            .iter()
+            .all(|&castling| castling == CastlingInfo::EMPTY)
+    );
+
+    // A capture on the square of a rook that can still castle is impossible
+    assert!(takebacks("r3k2B/8/8/8/8/8/8/4K3 b kq - 0 1").is_empty());
+}
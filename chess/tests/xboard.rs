@@ -0,0 +1,185 @@
+#![cfg(not(feature = "no_std"))]
+
+use std::time::Duration;
+
+use chess::{KIWIPETE_POS, START_POS, Side, eval::HandCrafted, xboard::XBoard};
+
+/// Output of the engine to the commands
+fn send(xboard: &mut XBoard<HandCrafted>, commands: &[&str]) -> String {
+    let mut out = Vec::new();
+    for command in commands {
+        assert!(xboard.handle(command, &mut out).unwrap(), "{command}");
+    }
+    String::from_utf8(out).unwrap()
+}
+
+fn engine() -> XBoard<HandCrafted> {
+    XBoard::new(HandCrafted::new())
+}
+
+#[test]
+fn handshake() {
+    let mut xboard = engine();
+    let out = send(&mut xboard, &["xboard", "protover 2", "accepted usermove"]);
+    assert!(out.starts_with("feature myname=\"chess "));
+    assert!(out.contains(" usermove=1 ") && out.contains(" setboard=1 "));
+    assert!(out.trim_end().ends_with("done=1"));
+
+    assert_eq!(send(&mut xboard, &["ping 7"]), "pong 7\n");
+    assert_eq!(
+        send(&mut xboard, &["frobnicate"]),
+        "Error (unknown command): frobnicate\n"
+    );
+    assert_eq!(
+        send(&mut xboard, &["sd deep"]),
+        "Error (bad argument): sd deep\n"
+    );
+    assert!(!xboard.handle("quit", &mut Vec::new()).unwrap());
+}
+
+#[test]
+fn force_mode() {
+    let mut xboard = engine();
+    let out = send(
+        &mut xboard,
+        &["new", "force", "usermove e2e4", "usermove e7e5", "g1f3"],
+    );
+    assert_eq!(out, "");
+    assert_eq!(xboard.engine(), None);
+    assert_eq!(xboard.game().played().len(), 3);
+
+    assert_eq!(
+        send(&mut xboard, &["usermove e2e4"]),
+        "Illegal move: e2e4\n"
+    );
+    assert_eq!(
+        send(&mut xboard, &["usermove 0000"]),
+        "Illegal move: 0000\n"
+    );
+
+    send(&mut xboard, &["undo"]);
+    assert_eq!(xboard.game().position().side(), Side::White);
+    send(&mut xboard, &["remove"]);
+    assert_eq!(xboard.game().position().get_fen(), START_POS);
+
+    send(&mut xboard, &[&format!("setboard {KIWIPETE_POS}")]);
+    assert_eq!(xboard.game().position().get_fen(), KIWIPETE_POS);
+    assert_eq!(
+        send(
+            &mut xboard,
+            &["setboard rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1"]
+        ),
+        "tellusererror Illegal position\n"
+    );
+    assert_eq!(xboard.game().position().get_fen(), KIWIPETE_POS);
+}
+
+#[test]
+fn answers_moves() {
+    let mut xboard = engine();
+    let out = send(&mut xboard, &["new", "sd 2", "usermove e2e4"]);
+    let m = out.strip_prefix("move ").unwrap().trim_end();
+    assert_eq!(xboard.game().played().len(), 2);
+    assert_eq!(xboard.game().position().side(), Side::White);
+
+    // Taking back both moves lets the engine think again
+    send(&mut xboard, &["remove", "force", "usermove e2e4"]);
+    assert_eq!(xboard.game().played().len(), 1);
+    let again = send(&mut xboard, &["go"]);
+    assert_eq!(again.trim_end(), format!("move {m}"));
+    assert_eq!(xboard.engine(), Some(Side::Black));
+
+    // The engine takes the side not to move
+    send(&mut xboard, &["new", "playother"]);
+    assert_eq!(xboard.engine(), Some(Side::Black));
+}
+
+#[test]
+fn announces_results() {
+    let mut xboard = engine();
+    let out = send(
+        &mut xboard,
+        &[
+            "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "sd 2",
+            "post",
+            "go",
+        ],
+    );
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 3, "{out}");
+    // Depth, mate in one, centiseconds, nodes and the line
+    let thinking: Vec<_> = lines[0].split(' ').collect();
+    assert_eq!(thinking[1], "100001");
+    assert_eq!(thinking[4], "a1a8");
+    assert_eq!(lines[1], "move a1a8");
+    assert_eq!(lines[2], "1-0 {White mates}");
+
+    // The opponent mates
+    let out = send(
+        &mut xboard,
+        &[
+            "new",
+            "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "usermove a1a8",
+        ],
+    );
+    assert_eq!(out, "1-0 {White mates}\n");
+
+    // The engine no longer moves after the result
+    let out = send(&mut xboard, &["new", "result 1-0 {White resigns}", "e2e4"]);
+    assert_eq!(out, "");
+    assert_eq!(xboard.engine(), None);
+}
+
+#[test]
+fn claims_fifty_moves() {
+    let mut xboard = engine();
+    let out = send(
+        &mut xboard,
+        &["setboard 8/8/4k3/8/8/4K3/8/R7 w - - 99 80", "sd 1", "go"],
+    );
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 2, "{out}");
+    assert!(lines[0].starts_with("move "));
+    assert_eq!(lines[1], "1/2-1/2 {Draw by fifty move rule}");
+}
+
+#[test]
+fn time_controls() {
+    let mut xboard = engine();
+    assert_eq!(xboard.budget(), None);
+    send(
+        &mut xboard,
+        &[
+            "new",
+            "level 40 0:02 0",
+            "time 50",
+            "otim 6000",
+            "force",
+            "usermove e2e4",
+        ],
+    );
+    assert_eq!(
+        xboard.clocks(),
+        (
+            Some(Duration::from_millis(500)),
+            Some(Duration::from_secs(60))
+        )
+    );
+    // The clock less the margin, shared by the 40 moves of the control
+    assert_eq!(xboard.budget(), Some(Duration::from_millis(12)));
+    assert!(send(&mut xboard, &["go"]).starts_with("move "));
+
+    let out = send(&mut xboard, &["new", "st 0.1", "force"]);
+    assert_eq!(out, "");
+    assert_eq!(xboard.budget(), Some(Duration::from_millis(80)));
+    assert!(send(&mut xboard, &["go"]).starts_with("move "));
+    // `new` resets the clocks
+    assert_eq!(xboard.clocks(), (None, None));
+
+    assert_eq!(
+        send(&mut xboard, &["level 40 5:x 0"]),
+        "Error (bad argument): level 40 5:x 0\n"
+    );
+}
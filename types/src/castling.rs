@@ -1,3 +1,5 @@
+use crate::{Side, Square};
+
 /// The enum defining the castling rights
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -8,3 +10,16 @@ pub enum Castling {
     /// Queen side castling (or O-O-O)
     QueenSide = 1,
 }
+
+impl Castling {
+    /// Start and end squares of the rook for the given [`Side`]
+    #[must_use]
+    pub const fn rook_squares(&self, side: Side) -> (Square, Square) {
+        match (side, self) {
+            (Side::White, Self::KingSide) => (Square::H1, Square::F1),
+            (Side::White, Self::QueenSide) => (Square::A1, Square::D1),
+            (Side::Black, Self::KingSide) => (Square::H8, Square::F8),
+            (Side::Black, Self::QueenSide) => (Square::A8, Square::D8),
+        }
+    }
+}
@@ -18,6 +18,32 @@ pub enum ChessError {
     InvalidRank,
     InvalidFile,
     InvalidPiece,
+    /// A side has no king or more than one
+    InvalidKings,
+
+    /// Move cannot be played in the position
+    IllegalMove(IllegalMoveReason),
+    /// The game is already over
+    GameOver,
+
+    /// Opening book data is corrupted
+    InvalidBook,
+    /// Problem stipulation is not like `#2`, `h#3`, `s#2` or `=2`
+    InvalidStipulation,
+    /// Network file is corrupted or has an unknown layout
+    InvalidNetwork,
+    /// Evaluation weights are not in the layout of the weights file
+    InvalidEvalParams,
+    /// Packed position is corrupted
+    InvalidPackedBoard,
+    /// There is no draw offer from the opponent to answer
+    NoDrawOffer,
+    /// The draw claim does not hold in the position
+    InvalidClaim,
+    /// Time control is not like `40/5400+30:1800`
+    InvalidTimeControl,
+    /// PGN text is malformed or has an illegal move
+    InvalidPgn,
 }
 
 /// Errors that occur when parsing FEN
@@ -38,3 +64,60 @@ pub enum FenError {
     /// Hit EOF unexpectedly
     EOF,
 }
+
+/// Reasons why a move cannot be played in the current position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum IllegalMoveReason {
+    /// The piece on the origin square belongs to the side that is not to move
+    WrongSideToMove,
+    /// There is no piece on the origin square
+    NoPieceOnOrigin,
+    /// The piece in the move differs from the piece on the origin square
+    PieceMismatch,
+    /// The piece cannot move that way
+    InvalidMovement,
+    /// Another piece stands in the way
+    PathBlocked,
+    /// The target square is occupied by a piece of the same side
+    OwnPieceOnTarget,
+    /// The move leaves the king in check
+    LeavesKingInCheck,
+    /// The piece is pinned to its king
+    PinnedPiece,
+    /// The king cannot castle while in check
+    CastlingOutOfCheck,
+    /// The king passes through or lands on an attacked square
+    CastlingThroughCheck,
+    /// The side has no right to castle this way
+    NoCastlingRight,
+    /// The pawn reaches the last rank but no promotion piece is given
+    MissingPromotion,
+    /// The promotion piece or the promotion square is invalid
+    InvalidPromotion,
+    /// Editor moves can not be played in a game
+    NotAGameMove,
+}
+
+impl core::fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let reason = match self {
+            Self::WrongSideToMove => "it is the other side's turn to move",
+            Self::NoPieceOnOrigin => "there is no piece on the origin square",
+            Self::PieceMismatch => "the piece does not stand on the origin square",
+            Self::InvalidMovement => "the piece cannot move that way",
+            Self::PathBlocked => "the path is blocked",
+            Self::OwnPieceOnTarget => "the target square is occupied by an own piece",
+            Self::LeavesKingInCheck => "the king would be in check",
+            Self::PinnedPiece => "the piece is pinned to the king",
+            Self::CastlingOutOfCheck => "the king cannot castle out of check",
+            Self::CastlingThroughCheck => "the king cannot castle through an attacked square",
+            Self::NoCastlingRight => "castling is no longer allowed",
+            Self::MissingPromotion => "the pawn must be promoted",
+            Self::InvalidPromotion => "the promotion is invalid",
+            Self::NotAGameMove => "the move is not allowed in a game",
+        };
+        write!(f, "{reason}")
+    }
+}
@@ -1,13 +1,14 @@
+use crate::Side;
+
+/// How the game ended, the winner and the reason
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum GameResult {
     WhiteWin(WinType),
     BlackWin(WinType),
-    Stalemate,
-    FiftyMoveRule,
-    DrawOffer,
-    InsufficientMaterial,
+    Draw(DrawType),
+    /// The game is not over
     None,
 }
 
@@ -18,4 +19,140 @@ pub enum WinType {
     Checkmate,
     Surrounded,
     Resign,
+    /// The opponent ran out of time
+    Timeout,
+    /// The opponent left the game
+    Abandonment,
+    /// The opponent broke the rules or did not show up
+    Forfeit,
+    /// Decided by an arbiter or an engine
+    Adjudication,
+    /// Win condition of a chess variant, like three checks
+    Variant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum DrawType {
+    Stalemate,
+    /// The players agreed to a draw
+    Agreement,
+    /// Threefold repetition, claimed
+    Repetition,
+    /// The position occurred five times
+    FivefoldRepetition,
+    /// No capture or pawn move in fifty moves
+    ///
+    /// A board ends there, a game once a player claims the draw
+    FiftyMoveRule,
+    /// No capture or pawn move in seventy-five moves, ending a game without a
+    /// claim
+    SeventyFiveMoveRule,
+    /// Neither side has the pieces to mate
+    InsufficientMaterial,
+    /// No sequence of legal moves leads to a mate
+    DeadPosition,
+    /// The flag fell, but the opponent cannot mate
+    TimeoutVsInsufficientMaterial,
+    /// Decided by an arbiter or an engine
+    Adjudication,
+}
+
+/// Reason of the PGN `Termination` tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum Termination {
+    /// Ended by the rules or by the players
+    Normal,
+    TimeForfeit,
+    Abandoned,
+    Adjudication,
+    RulesInfraction,
+    /// The game is still going on
+    Unterminated,
+}
+
+impl Termination {
+    /// Value of the PGN `Termination` tag
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::TimeForfeit => "time forfeit",
+            Self::Abandoned => "abandoned",
+            Self::Adjudication => "adjudication",
+            Self::RulesInfraction => "rules infraction",
+            Self::Unterminated => "unterminated",
+        }
+    }
+}
+
+impl GameResult {
+    /// Whether the game has ended, by a win or by a draw
+    pub const fn is_over(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+
+    /// Whether the game ended in a draw, for any reason
+    pub const fn is_draw(&self) -> bool {
+        matches!(self, Self::Draw(_))
+    }
+
+    /// Side that won, none for draws and unfinished games
+    pub const fn winner(&self) -> Option<Side> {
+        match self {
+            Self::WhiteWin(_) => Some(Side::White),
+            Self::BlackWin(_) => Some(Side::Black),
+            Self::Draw(_) | Self::None => None,
+        }
+    }
+
+    /// Side that lost, none for draws and unfinished games
+    pub const fn loser(&self) -> Option<Side> {
+        match self.winner() {
+            Some(side) => Some(side.neg()),
+            None => None,
+        }
+    }
+
+    /// Win of the side for the reason
+    pub const fn win(side: Side, win_type: WinType) -> Self {
+        match side {
+            Side::White => Self::WhiteWin(win_type),
+            Side::Black => Self::BlackWin(win_type),
+        }
+    }
+
+    /// Value of the PGN `Result` tag
+    pub const fn pgn_result(&self) -> &'static str {
+        match self {
+            Self::WhiteWin(_) => "1-0",
+            Self::BlackWin(_) => "0-1",
+            Self::Draw(_) => "1/2-1/2",
+            Self::None => "*",
+        }
+    }
+
+    /// Reason of the PGN `Termination` tag
+    ///
+    /// Flag-falls are time forfeits even when drawn, other wins and draws by
+    /// the rules or the players are normal
+    pub const fn termination(&self) -> Termination {
+        match self {
+            Self::WhiteWin(win_type) | Self::BlackWin(win_type) => match win_type {
+                WinType::Timeout => Termination::TimeForfeit,
+                WinType::Abandonment => Termination::Abandoned,
+                WinType::Forfeit => Termination::RulesInfraction,
+                WinType::Adjudication => Termination::Adjudication,
+                WinType::Checkmate | WinType::Surrounded | WinType::Resign | WinType::Variant => {
+                    Termination::Normal
+                }
+            },
+            Self::Draw(DrawType::TimeoutVsInsufficientMaterial) => Termination::TimeForfeit,
+            Self::Draw(DrawType::Adjudication) => Termination::Adjudication,
+            Self::Draw(_) => Termination::Normal,
+            Self::None => Termination::Unterminated,
+        }
+    }
 }
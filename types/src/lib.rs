@@ -9,18 +9,22 @@ mod game_result;
 mod magic;
 mod r#move;
 mod move_list;
+mod move_outcome;
 mod piece;
 mod side;
 mod square;
+mod unmove;
 
 pub use bitboard::{Bitboard, Directions};
 pub use castling::Castling;
 pub use castling_info::CastlingInfo;
-pub use error::{ChessError, FenError};
-pub use game_result::{GameResult, WinType};
+pub use error::{ChessError, FenError, IllegalMoveReason};
+pub use game_result::{DrawType, GameResult, Termination, WinType};
 pub use magic::Magic;
 pub use r#move::Move;
 pub use move_list::{MoveList, Moves};
+pub use move_outcome::MoveOutcome;
 pub use piece::{Piece, PieceType};
 pub use side::Side;
 pub use square::{File, Rank, Square};
+pub use unmove::UnMove;
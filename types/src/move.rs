@@ -41,4 +41,8 @@ pub enum Move {
     Remove {
         from: Square,
     },
+    // Passes the turn without moving, written as 0000
+    //
+    // Used by engines for null-move pruning
+    Null,
 }
@@ -0,0 +1,14 @@
+use crate::{GameResult, Piece};
+
+/// The effect of a move made on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct MoveOutcome {
+    /// The piece removed from the board by the move
+    pub captured: Option<Piece>,
+    /// Whether the move checks the enemy king
+    pub gave_check: bool,
+    /// The result of the game after the move
+    pub result: GameResult,
+}
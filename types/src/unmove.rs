@@ -0,0 +1,14 @@
+use crate::{CastlingInfo, Move, Piece, Square};
+
+/// A move taken back, leading from a position to one of its predecessors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnMove {
+    /// The move played in the predecessor
+    pub m: Move,
+    /// The piece captured by the move
+    pub captured: Option<Piece>,
+    /// The castling rights of the predecessor
+    pub castling: CastlingInfo,
+    /// The en passant square of the predecessor
+    pub en_passant: Option<Square>,
+}
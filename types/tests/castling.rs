@@ -1,4 +1,4 @@
-use types::CastlingInfo;
+use types::{Castling, CastlingInfo, Side, Square};
 
 #[test]
 fn default() {
@@ -32,3 +32,11 @@ fn from_char() {
     assert!(castling.update(b'Q'));
     assert!(!castling.update(b'I'));
 }
+
+#[test]
+fn rook_squares() {
+    assert_eq!(Castling::KingSide.rook_squares(Side::White), (Square::H1, Square::F1));
+    assert_eq!(Castling::QueenSide.rook_squares(Side::White), (Square::A1, Square::D1));
+    assert_eq!(Castling::KingSide.rook_squares(Side::Black), (Square::H8, Square::F8));
+    assert_eq!(Castling::QueenSide.rook_squares(Side::Black), (Square::A8, Square::D8));
+}
@@ -0,0 +1,63 @@
+use types::{DrawType, GameResult, Side, Termination, WinType};
+
+#[test]
+fn winner() {
+    let result = GameResult::win(Side::Black, WinType::Resign);
+    assert_eq!(result, GameResult::BlackWin(WinType::Resign));
+    assert_eq!(result.winner(), Some(Side::Black));
+    assert_eq!(result.loser(), Some(Side::White));
+    assert!(result.is_over());
+    assert!(!result.is_draw());
+
+    let draw = GameResult::Draw(DrawType::Agreement);
+    assert_eq!(draw.winner(), None);
+    assert_eq!(draw.loser(), None);
+    assert!(draw.is_draw());
+    assert!(draw.is_over());
+
+    assert!(!GameResult::None.is_over());
+}
+
+#[test]
+fn pgn() {
+    let cases = [
+        (GameResult::WhiteWin(WinType::Checkmate), "1-0", "normal"),
+        (
+            GameResult::BlackWin(WinType::Timeout),
+            "0-1",
+            "time forfeit",
+        ),
+        (
+            GameResult::WhiteWin(WinType::Abandonment),
+            "1-0",
+            "abandoned",
+        ),
+        (
+            GameResult::BlackWin(WinType::Forfeit),
+            "0-1",
+            "rules infraction",
+        ),
+        (
+            GameResult::WhiteWin(WinType::Adjudication),
+            "1-0",
+            "adjudication",
+        ),
+        (GameResult::Draw(DrawType::Repetition), "1/2-1/2", "normal"),
+        (
+            GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial),
+            "1/2-1/2",
+            "time forfeit",
+        ),
+        (
+            GameResult::Draw(DrawType::Adjudication),
+            "1/2-1/2",
+            "adjudication",
+        ),
+        (GameResult::None, "*", "unterminated"),
+    ];
+    for (result, pgn, termination) in cases {
+        assert_eq!(result.pgn_result(), pgn);
+        assert_eq!(result.termination().as_str(), termination);
+    }
+    assert_eq!(GameResult::None.termination(), Termination::Unterminated);
+}
@@ -0,0 +1,25 @@
+//! TypeScript declarations of the types crate, added to the bindings
+
+use std::{env, fs, path::Path};
+
+use ts_rs::TS;
+use types::{DrawType, GameResult, IllegalMoveReason, MoveOutcome, Piece, Side, Square, WinType};
+
+fn main() {
+    let decls = [
+        Side::decl(),
+        Piece::decl(),
+        Square::decl(),
+        WinType::decl(),
+        DrawType::decl(),
+        GameResult::decl(),
+        MoveOutcome::decl(),
+        IllegalMoveReason::decl(),
+    ];
+    let ts: String = decls
+        .iter()
+        .map(|decl| format!("export {decl}\n"))
+        .collect();
+    let out = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out).join("types.ts"), ts).expect("OUT_DIR is writable");
+}
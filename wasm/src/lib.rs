@@ -0,0 +1,199 @@
+//! WebAssembly bindings of the chess library
+//!
+//! A [`Chessboard`] handle keeps the moves of a [`Game`], so they can be taken
+//! back. Values reach JavaScript through serde, typed in TypeScript by the
+//! declarations ts-rs writes for the types crate
+
+use chess::{Chessboard as Board, Move, MoveGen, game::Game, perft, uci::format_move};
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use ts_rs::TS;
+use types::{ChessError, IllegalMoveReason, MoveOutcome, Piece, Square};
+use wasm_bindgen::prelude::*;
+
+/// TypeScript declarations of the values crossing to JavaScript
+pub const TYPESCRIPT: &str = concat!(
+    include_str!(concat!(env!("OUT_DIR"), "/types.ts")),
+    "export type LegalMove = { uci: string, san: string, from: Square, to: Square, \
+     promotion: Piece | null, };\n",
+    "export type Failure = { message: string, reason: IllegalMoveReason | null, };\n"
+);
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DECLARATIONS: &str = TYPESCRIPT;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "LegalMove[]")]
+    pub type LegalMoves;
+    #[wasm_bindgen(typescript_type = "MoveOutcome")]
+    pub type MoveOutcomeJs;
+    #[wasm_bindgen(typescript_type = "GameResult")]
+    pub type GameResultJs;
+}
+
+/// Move of the position with its notations, see [`TYPESCRIPT`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct LegalMove {
+    pub uci: String,
+    pub san: String,
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Piece>,
+}
+
+/// Error thrown to JavaScript, with the reason when a move cannot be played,
+/// see [`TYPESCRIPT`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct Failure {
+    pub message: String,
+    pub reason: Option<IllegalMoveReason>,
+}
+
+impl Failure {
+    /// Failure of the move given as text, with the reason when it is illegal
+    pub fn cannot_play(m: &str, error: &ChessError) -> Self {
+        let (message, reason) = match *error {
+            ChessError::IllegalMove(reason) => (format!("Cannot play {m}: {reason}"), Some(reason)),
+            ChessError::GameOver => (format!("Cannot play {m}: the game is over"), None),
+            _ => (format!("Cannot play {m}"), None),
+        };
+        Self { message, reason }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Chessboard {
+    game: Game,
+}
+
+#[wasm_bindgen]
+impl Chessboard {
+    /// The starting position
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { game: Game::new() }
+    }
+
+    /// Throws a [`Failure`] for an invalid FEN
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str) -> Result<Self, JsValue> {
+        Game::from_fen(fen).map(|game| Self { game }).map_err(|e| {
+            to_js(&Failure {
+                message: format!("Invalid FEN: {e:?}"),
+                reason: None,
+            })
+        })
+    }
+
+    pub fn fen(&self) -> String {
+        self.game.position().get_fen()
+    }
+
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> LegalMoves {
+        to_js(&self.moves())
+    }
+
+    /// Play the move given in UCI or SAN, throwing a [`Failure`] with the
+    /// reason when it cannot be played
+    #[wasm_bindgen(js_name = "move")]
+    pub fn make_move(&mut self, m: &str) -> Result<MoveOutcomeJs, JsValue> {
+        self.play(m)
+            .map(|outcome| to_js(&outcome))
+            .map_err(|e| to_js(&Failure::cannot_play(m, &e)))
+    }
+
+    /// Take back the last move, returning it in UCI
+    pub fn undo(&mut self) -> Option<String> {
+        self.game.undo().and_then(format_move)
+    }
+
+    pub fn result(&self) -> GameResultJs {
+        to_js(&self.game.result())
+    }
+
+    /// SAN of the legal move given in UCI
+    pub fn san(&self, uci: &str) -> Option<String> {
+        let cb = self.game.position();
+        MoveGen::new(cb.moves_cache)
+            .from_str(cb, uci)
+            .and_then(|m| cb.to_san(m))
+    }
+
+    /// Leaf nodes of the move tree at the depth
+    pub fn perft(&self, depth: u32) -> usize {
+        let mut cb = *self.game.position();
+        perft(&mut cb, depth)
+    }
+}
+
+/// Rust side of the bindings
+impl Chessboard {
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Legal moves of the position, none once the game is over
+    pub fn moves(&self) -> Vec<LegalMove> {
+        if self.game.is_over() {
+            return Vec::new();
+        }
+        let mut cb = *self.game.position();
+        let mut move_gen = MoveGen::new(cb.generate_moves());
+        let mut moves = Vec::new();
+        while let Some(m) = move_gen.next_const() {
+            moves.extend(legal_move(&cb, m));
+        }
+        moves
+    }
+
+    /// Play the move given in UCI or SAN
+    ///
+    /// Text that is no legal move gives [`IllegalMoveReason::InvalidMovement`]
+    pub fn play(&mut self, m: &str) -> Result<MoveOutcome, ChessError> {
+        let cb = self.game.position();
+        let m = MoveGen::new(cb.moves_cache)
+            .from_str(cb, m)
+            .filter(|&m| m != Move::Null)
+            .or_else(|| cb.from_san(m))
+            .ok_or(ChessError::IllegalMove(IllegalMoveReason::InvalidMovement))?;
+        self.game.make_move(m)
+    }
+}
+
+impl Default for Chessboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn legal_move(cb: &Board, m: Move) -> Option<LegalMove> {
+    let (from, to, promotion) = match m {
+        Move::Standard { from, to, .. }
+        | Move::EnPassant { from, to }
+        | Move::Castling { from, to, .. } => (from, to, None),
+        Move::Promotion {
+            from,
+            to,
+            promotion,
+        } => (from, to, Some(promotion)),
+        Move::Null | Move::Put { .. } | Move::Remove { .. } => return None,
+    };
+    Some(LegalMove {
+        uci: format_move(m)?,
+        san: cb.to_san(m)?,
+        from,
+        to,
+        promotion,
+    })
+}
+
+/// JavaScript value of the typed declaration, `None` as `null`
+fn to_js<T: Serialize, J: JsCast>(value: &T) -> J {
+    value
+        .serialize(&Serializer::json_compatible())
+        .unwrap_or(JsValue::NULL)
+        .unchecked_into()
+}
@@ -0,0 +1,82 @@
+use chess::{KIWIPETE_POS, START_POS};
+use chess_wasm::{Chessboard, Failure, LegalMove, TYPESCRIPT};
+use ts_rs::TS;
+use types::{ChessError, IllegalMoveReason, Piece, Square};
+
+#[test]
+fn positions() {
+    assert_eq!(Chessboard::new().fen(), START_POS);
+    assert_eq!(
+        Chessboard::from_fen(KIWIPETE_POS).unwrap().fen(),
+        KIWIPETE_POS
+    );
+    assert_eq!(Chessboard::new().perft(2), 400);
+    assert_eq!(Chessboard::from_fen(KIWIPETE_POS).unwrap().perft(2), 2039);
+}
+
+#[test]
+fn moves() {
+    let board = Chessboard::new();
+    let moves = board.moves();
+    assert_eq!(moves.len(), 20);
+    assert!(moves.contains(&LegalMove {
+        uci: "g1f3".to_string(),
+        san: "Nf3".to_string(),
+        from: Square::G1,
+        to: Square::F3,
+        promotion: None,
+    }));
+    assert_eq!(board.san("e2e4").as_deref(), Some("e4"));
+    assert_eq!(board.san("e2e5"), None);
+
+    let board = Chessboard::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let promotions: Vec<_> = board
+        .moves()
+        .into_iter()
+        .filter_map(|m| m.promotion)
+        .collect();
+    assert_eq!(promotions.len(), 4);
+    assert!(promotions.contains(&Piece::WHITE_QUEEN));
+}
+
+#[test]
+fn play_and_undo() {
+    let mut board = Chessboard::new();
+    assert!(board.play("e4").unwrap().captured.is_none());
+    board.play("d7d5").unwrap();
+    assert!(board.play("exd5").unwrap().captured.is_some());
+    assert_eq!(
+        board.play("e4"),
+        Err(ChessError::IllegalMove(IllegalMoveReason::InvalidMovement))
+    );
+    assert_eq!(board.game().played().len(), 3);
+    assert_eq!(
+        Failure::cannot_play("e4", &board.play("e4").unwrap_err()),
+        Failure {
+            message: "Cannot play e4: the piece cannot move that way".to_string(),
+            reason: Some(IllegalMoveReason::InvalidMovement),
+        }
+    );
+
+    assert_eq!(board.undo().as_deref(), Some("e4d5"));
+    assert_eq!(board.undo().as_deref(), Some("d7d5"));
+    assert_eq!(board.undo().as_deref(), Some("e2e4"));
+    assert_eq!(board.undo(), None);
+    assert_eq!(board.fen(), START_POS);
+
+    // No moves once mated
+    for m in ["f3", "e5", "g4", "Qh4#"] {
+        board.play(m).unwrap();
+    }
+    assert!(board.game().is_over());
+    assert!(board.moves().is_empty());
+}
+
+#[test]
+fn typescript() {
+    assert!(TYPESCRIPT.contains(&format!("export {}", LegalMove::decl())));
+    assert!(TYPESCRIPT.contains("export type GameResult"));
+    assert!(TYPESCRIPT.contains("export type MoveOutcome"));
+    assert!(TYPESCRIPT.contains(&format!("export {}", Failure::decl())));
+    assert!(TYPESCRIPT.contains("export type IllegalMoveReason"));
+}